@@ -0,0 +1,28 @@
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientError {
+    // 账户数据的长度和 config 布局不一致
+    InvalidAccountData,
+    // 账户的版本号不是客户端认识的版本 旧布局需要先调用 Migrate
+    UnsupportedVersion(u8),
+    // 指令参数不合法 比如数量为 0
+    InvalidArgument,
+    // 只读指令返回的数据长度不对
+    InvalidReturnData,
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::InvalidAccountData => f.write_str("invalid config account data"),
+            ClientError::UnsupportedVersion(version) => {
+                write!(f, "unsupported config version {version}")
+            }
+            ClientError::InvalidArgument => f.write_str("invalid instruction argument"),
+            ClientError::InvalidReturnData => f.write_str("invalid return data"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
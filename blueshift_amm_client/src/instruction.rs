@@ -0,0 +1,49 @@
+use solana_address::Address;
+
+// 和 solana-instruction 中的 AccountMeta / Instruction 字段一致 方便转换成任意 SDK 的指令类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: Address,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    pub fn new(pubkey: Address, is_signer: bool) -> Self {
+        Self {
+            pubkey,
+            is_signer,
+            is_writable: true,
+        }
+    }
+
+    pub fn new_readonly(pubkey: Address, is_signer: bool) -> Self {
+        Self {
+            pubkey,
+            is_signer,
+            is_writable: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: Address,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+// 链上程序的指令编号
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmmInstruction {
+    Initialize = 0,
+    Deposit = 1,
+    Withdraw = 2,
+    Swap = 3,
+    OpenPosition = 33,
+    QuotePosition = 34,
+    CreateSettings = 35,
+    UpdateSettings = 36,
+    CollectCreationFees = 37,
+}
@@ -0,0 +1,72 @@
+use crate::{
+    find_associated_token_address, AccountMeta, AmmInstruction, ClientError, Instruction, Pool,
+    NATIVE_MINT, PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use solana_address::Address;
+
+// 存入 x y 铸造 amount 个 LP
+#[derive(Debug, Clone, Default)]
+pub struct Deposit {
+    pub user: Address,
+    pub pool: Pool,
+    // 希望铸造的 LP 数量
+    pub amount: u64,
+    // 愿意存入的最大 x y 数量
+    pub max_x: u64,
+    pub max_y: u64,
+    // unix 时间戳截止时间
+    pub expiration: i64,
+    // 池子中 wSOL 一侧直接使用原生 SOL 支付
+    pub native: bool,
+    // slot 截止时间 0 表示不限制
+    pub expiration_slot: u64,
+    // 按顺序排列的可选账户：TWAMM 账户、白名单记录、LP 仓位记录
+    pub remaining_accounts: Vec<AccountMeta>,
+}
+
+impl Deposit {
+    pub fn instruction(&self) -> Result<Instruction, ClientError> {
+        if self.amount == 0 || self.max_x == 0 || self.max_y == 0 {
+            return Err(ClientError::InvalidArgument);
+        }
+        let mut data = Vec::with_capacity(1 + 8 * 4 + 1 + 8);
+        data.push(AmmInstruction::Deposit as u8);
+        data.extend_from_slice(&self.amount.to_le_bytes());
+        data.extend_from_slice(&self.max_x.to_le_bytes());
+        data.extend_from_slice(&self.max_y.to_le_bytes());
+        data.extend_from_slice(&self.expiration.to_le_bytes());
+        if self.native || self.expiration_slot != 0 {
+            data.push(self.native as u8);
+        }
+        if self.expiration_slot != 0 {
+            data.extend_from_slice(&self.expiration_slot.to_le_bytes());
+        }
+
+        // 直接支付 lamports 的一侧传入系统程序
+        let user_ata = |mint: &Address| match self.native && mint.eq(&NATIVE_MINT) {
+            true => AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            false => AccountMeta::new(find_associated_token_address(&self.user, mint), false),
+        };
+        let mut accounts = vec![
+            AccountMeta::new(self.user.clone(), true),
+            AccountMeta::new(self.pool.mint_lp.clone(), false),
+            AccountMeta::new(self.pool.vault_x.clone(), false),
+            AccountMeta::new(self.pool.vault_y.clone(), false),
+            user_ata(&self.pool.mint_x),
+            user_ata(&self.pool.mint_y),
+            AccountMeta::new(
+                find_associated_token_address(&self.user, &self.pool.mint_lp),
+                false,
+            ),
+            AccountMeta::new(self.pool.config.clone(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ];
+        accounts.extend(self.remaining_accounts.iter().cloned());
+
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data,
+        })
+    }
+}
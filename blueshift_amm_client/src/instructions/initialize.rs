@@ -0,0 +1,55 @@
+use crate::{
+    find_config_address, find_mint_lp_address, find_settings_address, AccountMeta, AmmInstruction,
+    ClientError, Instruction, PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use solana_address::Address;
+
+// 创建池子 config 和 LP 铸币的地址由 seed 和两个铸币推导
+#[derive(Debug, Clone, Default)]
+pub struct Initialize {
+    // 支付租金的创建者 必须签名
+    pub initializer: Address,
+    pub seed: u64,
+    // 以基点表示的交换费
+    pub fee: u16,
+    pub mint_x: Address,
+    pub mint_y: Address,
+    // 池子的管理员 None 表示创建一个不可变的池子
+    pub authority: Option<Address>,
+}
+
+impl Initialize {
+    pub fn instruction(&self) -> Result<Instruction, ClientError> {
+        if self.fee >= 10_000 {
+            return Err(ClientError::InvalidArgument);
+        }
+        let (config, config_bump) = find_config_address(self.seed, &self.mint_x, &self.mint_y);
+        let (mint_lp, lp_bump) = find_mint_lp_address(&config);
+
+        // [seed: u64, fee: u16, mint_x, mint_y, config_bump: u8, lp_bump: u8, authority]
+        let mut data = Vec::with_capacity(1 + 8 + 2 + 32 * 3 + 2);
+        data.push(AmmInstruction::Initialize as u8);
+        data.extend_from_slice(&self.seed.to_le_bytes());
+        data.extend_from_slice(&self.fee.to_le_bytes());
+        data.extend_from_slice(self.mint_x.as_ref());
+        data.extend_from_slice(self.mint_y.as_ref());
+        data.push(config_bump);
+        data.push(lp_bump);
+        if let Some(authority) = &self.authority {
+            data.extend_from_slice(authority.as_ref());
+        }
+
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.initializer.clone(), true),
+                AccountMeta::new(mint_lp, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new(find_settings_address().0, false),
+            ],
+            data,
+        })
+    }
+}
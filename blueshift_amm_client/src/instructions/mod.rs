@@ -0,0 +1,13 @@
+pub mod deposit;
+pub mod initialize;
+pub mod position;
+pub mod settings;
+pub mod swap;
+pub mod withdraw;
+
+pub use deposit::*;
+pub use initialize::*;
+pub use position::*;
+pub use settings::*;
+pub use swap::*;
+pub use withdraw::*;
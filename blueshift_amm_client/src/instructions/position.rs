@@ -0,0 +1,93 @@
+use crate::{
+    find_position_address, AccountMeta, AmmInstruction, ClientError, Instruction, Pool, PROGRAM_ID,
+    SYSTEM_PROGRAM_ID,
+};
+use solana_address::Address;
+
+// 创建用户的 LP 仓位记录 之后在 Deposit / Withdraw 的 remaining_accounts 中带上
+// AccountMeta::new(find_position_address(..).0, false) 就会记录成本
+#[derive(Debug, Clone, Default)]
+pub struct OpenPosition {
+    // 仓位的所有者 同时支付租金
+    pub user: Address,
+    pub pool: Pool,
+}
+
+impl OpenPosition {
+    pub fn instruction(&self) -> Result<Instruction, ClientError> {
+        let (position, _) = find_position_address(&self.pool.config, &self.user);
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.user.clone(), true),
+                AccountMeta::new_readonly(self.pool.config.clone(), false),
+                AccountMeta::new(position, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data: vec![AmmInstruction::OpenPosition as u8],
+        })
+    }
+}
+
+// 按当前储备计算仓位盈亏的只读指令 结果用 PositionPnl::from_return_data 解析
+#[derive(Debug, Clone, Default)]
+pub struct QuotePosition {
+    pub user: Address,
+    pub pool: Pool,
+}
+
+impl QuotePosition {
+    pub fn instruction(&self) -> Result<Instruction, ClientError> {
+        let (position, _) = find_position_address(&self.pool.config, &self.user);
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.pool.config.clone(), false),
+                AccountMeta::new_readonly(self.pool.mint_lp.clone(), false),
+                AccountMeta::new_readonly(position, false),
+            ],
+            data: vec![AmmInstruction::QuotePosition as u8],
+        })
+    }
+}
+
+// QuotePosition 的返回数据 盈亏按 x y 两种代币分别计算
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PositionPnl {
+    // 记录中还持有的 LP
+    pub lp_held: u64,
+    // 持有的 LP 现在可以取回的 x y
+    pub value_x: u64,
+    pub value_y: u64,
+    // 持有的 LP 的成本
+    pub cost_x: u64,
+    pub cost_y: u64,
+    // 未实现盈亏 = 现值 - 成本
+    pub unrealized_x: i64,
+    pub unrealized_y: i64,
+    // 已实现盈亏 = 累计取回 - 已经销毁的 LP 的成本
+    pub realized_x: i64,
+    pub realized_y: i64,
+}
+
+impl PositionPnl {
+    pub const LEN: usize = 72;
+
+    pub fn from_return_data(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() != Self::LEN {
+            return Err(ClientError::InvalidReturnData);
+        }
+        let word = |index: usize| -> [u8; 8] { data[index * 8..index * 8 + 8].try_into().unwrap() };
+        Ok(Self {
+            lp_held: u64::from_le_bytes(word(0)),
+            value_x: u64::from_le_bytes(word(1)),
+            value_y: u64::from_le_bytes(word(2)),
+            cost_x: u64::from_le_bytes(word(3)),
+            cost_y: u64::from_le_bytes(word(4)),
+            unrealized_x: i64::from_le_bytes(word(5)),
+            unrealized_y: i64::from_le_bytes(word(6)),
+            realized_x: i64::from_le_bytes(word(7)),
+            realized_y: i64::from_le_bytes(word(8)),
+        })
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    find_program_data_address, find_settings_address, AccountMeta, AmmInstruction, ClientError,
+    Instruction, MAX_FEE_TIERS, PROGRAM_ID, SYSTEM_PROGRAM_ID,
+};
+use solana_address::Address;
+
+// 创建全局设置 payer 必须是程序的升级权限持有者
+#[derive(Debug, Clone, Default)]
+pub struct CreateSettings {
+    pub payer: Address,
+    // 协议管理员 可以和 payer 不同 比如多签账户
+    pub admin: Address,
+    // 创建池子需要支付的 lamports
+    pub creation_fee: u64,
+    // 允许的费率档位 以基点表示 比如 [1, 5, 30, 100]
+    pub fee_tiers: Vec<u16>,
+}
+
+impl CreateSettings {
+    pub fn instruction(&self) -> Result<Instruction, ClientError> {
+        let data = settings_data(
+            AmmInstruction::CreateSettings,
+            &self.admin,
+            self.creation_fee,
+            &self.fee_tiers,
+        )?;
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.payer.clone(), true),
+                AccountMeta::new(find_settings_address().0, false),
+                AccountMeta::new_readonly(find_program_data_address(), false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        })
+    }
+}
+
+// 整体替换管理员、创建费和费率档位 已经创建的池子不受影响
+#[derive(Debug, Clone, Default)]
+pub struct UpdateSettings {
+    // 当前的协议管理员
+    pub admin: Address,
+    // 新的协议管理员 不转移时和 admin 相同
+    pub new_admin: Address,
+    pub creation_fee: u64,
+    pub fee_tiers: Vec<u16>,
+}
+
+impl UpdateSettings {
+    pub fn instruction(&self) -> Result<Instruction, ClientError> {
+        let data = settings_data(
+            AmmInstruction::UpdateSettings,
+            &self.new_admin,
+            self.creation_fee,
+            &self.fee_tiers,
+        )?;
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.admin.clone(), true),
+                AccountMeta::new(find_settings_address().0, false),
+            ],
+            data,
+        })
+    }
+}
+
+// 把设置账户中累积的创建费转给 destination
+#[derive(Debug, Clone, Default)]
+pub struct CollectCreationFees {
+    pub admin: Address,
+    pub destination: Address,
+}
+
+impl CollectCreationFees {
+    pub fn instruction(&self) -> Result<Instruction, ClientError> {
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.admin.clone(), true),
+                AccountMeta::new(find_settings_address().0, false),
+                AccountMeta::new(self.destination.clone(), false),
+            ],
+            data: vec![AmmInstruction::CollectCreationFees as u8],
+        })
+    }
+}
+
+// [discriminator, admin, creation_fee: u64, fee_tiers: [u16; n]] 校验规则和链上一致
+fn settings_data(
+    instruction: AmmInstruction,
+    admin: &Address,
+    creation_fee: u64,
+    fee_tiers: &[u16],
+) -> Result<Vec<u8>, ClientError> {
+    if fee_tiers.is_empty() || fee_tiers.len() > MAX_FEE_TIERS {
+        return Err(ClientError::InvalidArgument);
+    }
+    for (index, tier) in fee_tiers.iter().enumerate() {
+        if *tier >= 10_000 || fee_tiers[..index].contains(tier) {
+            return Err(ClientError::InvalidArgument);
+        }
+    }
+    let mut data = Vec::with_capacity(1 + 32 + 8 + 2 * fee_tiers.len());
+    data.push(instruction as u8);
+    data.extend_from_slice(admin.as_ref());
+    data.extend_from_slice(&creation_fee.to_le_bytes());
+    for tier in fee_tiers {
+        data.extend_from_slice(&tier.to_le_bytes());
+    }
+    Ok(data)
+}
@@ -0,0 +1,151 @@
+use crate::{
+    find_associated_token_address, AccountMeta, AmmInstruction, ClientError, Instruction, Pool,
+    NATIVE_MINT, PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use solana_address::Address;
+
+// 支付 amount 个代币交换另一种代币
+#[derive(Debug, Clone, Default)]
+pub struct Swap {
+    pub user: Address,
+    pub pool: Pool,
+    // true 表示支付 x 获取 y
+    pub is_x: bool,
+    pub amount: u64,
+    // 愿意接受的最小数量
+    pub min: u64,
+    // unix 时间戳截止时间
+    pub expiration: i64,
+    // 池子中 wSOL 一侧直接使用原生 SOL 支付或接收
+    // 接收一侧的输出先转入一个临时 wSOL 账户 交易结束时关闭换成 SOL 不会动用户的 wSOL 关联账户
+    // 临时账户需要在同一笔交易中先创建 instructions() 会把创建的指令放在交换前面
+    pub native: bool,
+    // 临时 wSOL 账户的种子 每笔交易使用不同的值
+    // 地址是公开可推导的 固定的种子可能被别人提前转入 lamports 导致创建失败
+    pub temporary_seed: u64,
+    // slot 截止时间 0 表示不限制
+    pub expiration_slot: u64,
+    // 按顺序排列的可选账户：TWAMM 账户、预言机价格账户、白名单记录、hook 程序和 hook 账户
+    pub remaining_accounts: Vec<AccountMeta>,
+}
+
+impl Swap {
+    pub fn instruction(&self) -> Result<Instruction, ClientError> {
+        if self.amount == 0 || self.min == 0 {
+            return Err(ClientError::InvalidArgument);
+        }
+        let mut data = Vec::with_capacity(1 + 1 + 8 * 3 + 1 + 8);
+        data.push(AmmInstruction::Swap as u8);
+        data.push(self.is_x as u8);
+        data.extend_from_slice(&self.amount.to_le_bytes());
+        data.extend_from_slice(&self.min.to_le_bytes());
+        data.extend_from_slice(&self.expiration.to_le_bytes());
+        if self.native || self.expiration_slot != 0 {
+            data.push(self.native as u8);
+        }
+        if self.expiration_slot != 0 {
+            data.extend_from_slice(&self.expiration_slot.to_le_bytes());
+        }
+
+        // 支付一侧的原生 SOL 不需要代币账户 接收一侧使用临时 wSOL 账户
+        let mint_in = match self.is_x {
+            true => &self.pool.mint_x,
+            false => &self.pool.mint_y,
+        };
+        let temporary = self.temporary_wsol()?;
+        let user_ata = |mint: &Address| match (self.native && mint.eq(&NATIVE_MINT), &temporary) {
+            (true, _) if mint.eq(mint_in) => AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            (true, Some(temporary)) => AccountMeta::new(temporary.clone(), false),
+            _ => AccountMeta::new(find_associated_token_address(&self.user, mint), false),
+        };
+        let mut accounts = vec![
+            AccountMeta::new(self.user.clone(), true),
+            user_ata(&self.pool.mint_x),
+            user_ata(&self.pool.mint_y),
+            AccountMeta::new(self.pool.vault_x.clone(), false),
+            AccountMeta::new(self.pool.vault_y.clone(), false),
+            AccountMeta::new(self.pool.config.clone(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ];
+        accounts.extend(self.remaining_accounts.iter().cloned());
+
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data,
+        })
+    }
+
+    // 接收原生 SOL 时的临时 wSOL 账户 由用户地址和 temporary_seed 推导 其他情况返回 None
+    pub fn temporary_wsol(&self) -> Result<Option<Address>, ClientError> {
+        let mint_out = match self.is_x {
+            true => &self.pool.mint_y,
+            false => &self.pool.mint_x,
+        };
+        if !self.native || mint_out.ne(&NATIVE_MINT) {
+            return Ok(None);
+        }
+        Address::create_with_seed(&self.user, &self.temporary_seed(), &TOKEN_PROGRAM_ID)
+            .map(Some)
+            .map_err(|_| ClientError::InvalidArgument)
+    }
+
+    // 完整的指令序列 接收原生 SOL 时先创建并初始化临时 wSOL 账户 交换结束时程序会关闭它
+    pub fn instructions(&self) -> Result<Vec<Instruction>, ClientError> {
+        let swap = self.instruction()?;
+        let Some(temporary) = self.temporary_wsol()? else {
+            return Ok(vec![swap]);
+        };
+        Ok(vec![
+            self.create_temporary(&temporary),
+            initialize_account(&temporary, &self.user),
+            swap,
+        ])
+    }
+
+    #[inline(always)]
+    fn temporary_seed(&self) -> String {
+        format!("amm-native-{:016x}", self.temporary_seed)
+    }
+
+    // 系统程序的 CreateAccountWithSeed 用户同时是付款方和 base 不需要额外的签名
+    // [3u32, base, seed_len: u64, seed, lamports, space, owner]
+    fn create_temporary(&self, temporary: &Address) -> Instruction {
+        let seed = self.temporary_seed();
+        let mut data = Vec::with_capacity(4 + 32 + 8 + seed.len() + 8 + 8 + 32);
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(self.user.as_ref());
+        data.extend_from_slice(&(seed.len() as u64).to_le_bytes());
+        data.extend_from_slice(seed.as_bytes());
+        data.extend_from_slice(&TOKEN_ACCOUNT_RENT.to_le_bytes());
+        data.extend_from_slice(&(TOKEN_ACCOUNT_LEN as u64).to_le_bytes());
+        data.extend_from_slice(TOKEN_PROGRAM_ID.as_ref());
+        Instruction {
+            program_id: SYSTEM_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.user.clone(), true),
+                AccountMeta::new(temporary.clone(), false),
+            ],
+            data,
+        }
+    }
+}
+
+// 代币账户的长度和免租金需要的 lamports 关闭临时账户时租金会一起返还给用户
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const TOKEN_ACCOUNT_RENT: u64 = 2_039_280;
+
+// 代币程序的 InitializeAccount3 [18, owner]
+fn initialize_account(account: &Address, owner: &Address) -> Instruction {
+    let mut data = Vec::with_capacity(1 + 32);
+    data.push(18);
+    data.extend_from_slice(owner.as_ref());
+    Instruction {
+        program_id: TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(account.clone(), false),
+            AccountMeta::new_readonly(NATIVE_MINT, false),
+        ],
+        data,
+    }
+}
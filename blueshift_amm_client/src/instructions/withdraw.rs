@@ -0,0 +1,68 @@
+use crate::{
+    find_associated_token_address, AccountMeta, AmmInstruction, ClientError, Instruction, Pool,
+    PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use solana_address::Address;
+
+// 销毁 amount 个 LP 取回 x y
+#[derive(Debug, Clone, Default)]
+pub struct Withdraw {
+    pub user: Address,
+    pub pool: Pool,
+    // 希望销毁的 LP 数量
+    pub amount: u64,
+    // 愿意取回的最小 x y 数量
+    pub min_x: u64,
+    pub min_y: u64,
+    // unix 时间戳截止时间
+    pub expiration: i64,
+    // slot 截止时间 0 表示不限制
+    pub expiration_slot: u64,
+    // 按顺序排列的可选账户：TWAMM 账户、白名单记录、LP 仓位记录
+    pub remaining_accounts: Vec<AccountMeta>,
+}
+
+impl Withdraw {
+    pub fn instruction(&self) -> Result<Instruction, ClientError> {
+        if self.amount == 0 || self.min_x == 0 || self.min_y == 0 {
+            return Err(ClientError::InvalidArgument);
+        }
+        let mut data = Vec::with_capacity(1 + 8 * 5);
+        data.push(AmmInstruction::Withdraw as u8);
+        data.extend_from_slice(&self.amount.to_le_bytes());
+        data.extend_from_slice(&self.min_x.to_le_bytes());
+        data.extend_from_slice(&self.min_y.to_le_bytes());
+        data.extend_from_slice(&self.expiration.to_le_bytes());
+        if self.expiration_slot != 0 {
+            data.extend_from_slice(&self.expiration_slot.to_le_bytes());
+        }
+
+        let mut accounts = vec![
+            AccountMeta::new(self.user.clone(), true),
+            AccountMeta::new(self.pool.mint_lp.clone(), false),
+            AccountMeta::new(self.pool.vault_x.clone(), false),
+            AccountMeta::new(self.pool.vault_y.clone(), false),
+            AccountMeta::new(
+                find_associated_token_address(&self.user, &self.pool.mint_x),
+                false,
+            ),
+            AccountMeta::new(
+                find_associated_token_address(&self.user, &self.pool.mint_y),
+                false,
+            ),
+            AccountMeta::new(
+                find_associated_token_address(&self.user, &self.pool.mint_lp),
+                false,
+            ),
+            AccountMeta::new(self.pool.config.clone(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ];
+        accounts.extend(self.remaining_accounts.iter().cloned());
+
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data,
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use solana_address::{address, Address};
+
+// blueshift_native_amm 的链下客户端
+// 负责拼装指令数据和账户顺序、推导 PDA、解析 config 账户 不依赖链上程序本身 可以在任何平台上编译
+
+pub mod error;
+pub use error::*;
+
+pub mod instruction;
+pub use instruction::*;
+
+pub mod instructions;
+pub use instructions::*;
+
+pub mod pda;
+pub use pda::*;
+
+pub mod state;
+pub use state::*;
+
+// AMM 程序地址 和链上程序的 declare_id! 保持一致
+pub const PROGRAM_ID: Address = address!("22222222222222222222222222222222222222222222");
+pub const SYSTEM_PROGRAM_ID: Address = address!("11111111111111111111111111111111");
+pub const TOKEN_PROGRAM_ID: Address = address!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Address =
+    address!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const BPF_LOADER_UPGRADEABLE_ID: Address =
+    address!("BPFLoaderUpgradeab1e11111111111111111111111");
+// 原生 SOL 对应的铸币地址（wrapped SOL）
+pub const NATIVE_MINT: Address = address!("So11111111111111111111111111111111111111112");
@@ -0,0 +1,88 @@
+use crate::{ASSOCIATED_TOKEN_PROGRAM_ID, BPF_LOADER_UPGRADEABLE_ID, PROGRAM_ID, TOKEN_PROGRAM_ID};
+use solana_address::Address;
+
+// 池子的配置账户 seeds = [b"config", seed, mint_x, mint_y]
+pub fn find_config_address(seed: u64, mint_x: &Address, mint_y: &Address) -> (Address, u8) {
+    Address::find_program_address(
+        &[
+            b"config",
+            &seed.to_le_bytes(),
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+// 池子的 LP 铸币 seeds = [b"mint_lp", config]
+pub fn find_mint_lp_address(config: &Address) -> (Address, u8) {
+    Address::find_program_address(&[b"mint_lp", config.as_ref()], &PROGRAM_ID)
+}
+
+// 全局设置 seeds = [b"settings"]
+pub fn find_settings_address() -> (Address, u8) {
+    Address::find_program_address(&[b"settings"], &PROGRAM_ID)
+}
+
+// 程序的 ProgramData 账户 记录了升级权限 CreateSettings 需要
+pub fn find_program_data_address() -> Address {
+    Address::find_program_address(&[PROGRAM_ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID).0
+}
+
+// 用户在池子中的 LP 仓位记录 seeds = [b"position", config, owner]
+pub fn find_position_address(config: &Address, owner: &Address) -> (Address, u8) {
+    Address::find_program_address(&[b"position", config.as_ref(), owner.as_ref()], &PROGRAM_ID)
+}
+
+// 钱包持有 mint 的关联代币账户
+pub fn find_associated_token_address(wallet: &Address, mint: &Address) -> Address {
+    Address::find_program_address(
+        &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+// 池子的金库是 config 持有的关联代币账户
+pub fn find_vault_address(config: &Address, mint: &Address) -> Address {
+    find_associated_token_address(config, mint)
+}
+
+// 构建指令需要的池子地址
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pool {
+    pub config: Address,
+    pub mint_x: Address,
+    pub mint_y: Address,
+    pub mint_lp: Address,
+    pub vault_x: Address,
+    pub vault_y: Address,
+}
+
+impl Pool {
+    // 按照 Initialize 的规则推导全部地址 适用于通过这个客户端创建的池子
+    pub fn new(seed: u64, mint_x: Address, mint_y: Address) -> Self {
+        let (config, _) = find_config_address(seed, &mint_x, &mint_y);
+        let (mint_lp, _) = find_mint_lp_address(&config);
+        Self {
+            vault_x: find_vault_address(&config, &mint_x),
+            vault_y: find_vault_address(&config, &mint_y),
+            config,
+            mint_x,
+            mint_y,
+            mint_lp,
+        }
+    }
+
+    // 使用链上 config 中记录的地址 LP 铸币的 bump 由创建者决定 不一定能重新推导出来
+    pub fn from_config(address: Address, config: &crate::Config) -> Self {
+        Self {
+            config: address,
+            mint_x: config.mint_x.clone(),
+            mint_y: config.mint_y.clone(),
+            mint_lp: config.mint_lp.clone(),
+            vault_x: config.vault_x.clone(),
+            vault_y: config.vault_y.clone(),
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use crate::ClientError;
+use solana_address::Address;
+
+// 池子的状态 和链上的 AmmState 一致
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmmState {
+    Uninitialized = 0,
+    Initialized = 1,
+    Disabled = 2,
+    WithdrawOnly = 3,
+}
+
+impl TryFrom<u8> for AmmState {
+    type Error = ClientError;
+
+    fn try_from(state: u8) -> Result<Self, Self::Error> {
+        match state {
+            0 => Ok(AmmState::Uninitialized),
+            1 => Ok(AmmState::Initialized),
+            2 => Ok(AmmState::Disabled),
+            3 => Ok(AmmState::WithdrawOnly),
+            _ => Err(ClientError::InvalidAccountData),
+        }
+    }
+}
+
+// 链上 config 账户解析之后的内容 字段顺序和链上布局一致
+// 全 0 的地址表示对应的功能没有启用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub version: u8,
+    pub state: AmmState,
+    pub seed: u64,
+    pub authority: Address,
+    pub mint_x: Address,
+    pub mint_y: Address,
+    pub fee: u16,
+    pub config_bump: u8,
+    pub oracle: Address,
+    pub oracle_max_deviation: u16,
+    pub oracle_max_age: u64,
+    pub decimals_x: u8,
+    pub decimals_y: u8,
+    pub permissioned: bool,
+    pub hook_program: Address,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub initializer: Address,
+    pub vault_x: Address,
+    pub vault_y: Address,
+    pub mint_lp: Address,
+    pub twamm: bool,
+    pub twamm_escrow_x: u64,
+    pub twamm_escrow_y: u64,
+    pub slot_price_band: u16,
+    pub open_slot: u64,
+    pub open_reserve_x: u64,
+    pub open_reserve_y: u64,
+    pub guardian: Address,
+}
+
+impl Config {
+    // 链上 config 账户的长度
+    pub const LEN: usize = 586;
+    // 客户端认识的最新布局版本
+    pub const VERSION: u8 = 4;
+
+    // 解析链上 config 账户的数据
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() != Self::LEN {
+            return Err(ClientError::InvalidAccountData);
+        }
+        let mut reader = Reader { data, offset: 0 };
+        let version = reader.u8();
+        if version == 0 || version > Self::VERSION {
+            return Err(ClientError::UnsupportedVersion(version));
+        }
+        Ok(Self {
+            version,
+            state: AmmState::try_from(reader.u8())?,
+            seed: reader.u64(),
+            authority: reader.address(),
+            mint_x: reader.address(),
+            mint_y: reader.address(),
+            fee: reader.u16(),
+            config_bump: reader.u8(),
+            oracle: reader.address(),
+            oracle_max_deviation: reader.u16(),
+            oracle_max_age: reader.u64(),
+            decimals_x: reader.u8(),
+            decimals_y: reader.u8(),
+            permissioned: reader.u8() != 0,
+            hook_program: reader.address(),
+            reserve_x: reader.u64(),
+            reserve_y: reader.u64(),
+            initializer: reader.address(),
+            vault_x: reader.address(),
+            vault_y: reader.address(),
+            mint_lp: reader.address(),
+            twamm: reader.u8() != 0,
+            twamm_escrow_x: reader.u64(),
+            twamm_escrow_y: reader.u64(),
+            slot_price_band: reader.u16(),
+            open_slot: reader.u64(),
+            open_reserve_x: reader.u64(),
+            open_reserve_y: reader.u64(),
+            guardian: reader.address(),
+        })
+    }
+
+    // 没有设置管理员的池子是不可变的
+    pub fn has_authority(&self) -> bool {
+        self.authority.ne(&Address::default())
+    }
+}
+
+// 费率档位的最大数量 和链上程序一致
+pub const MAX_FEE_TIERS: usize = 8;
+
+// 链上全局设置解析之后的内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub admin: Address,
+    // 创建池子需要支付的 lamports
+    pub creation_fee: u64,
+    // 允许的费率档位 以基点表示
+    pub fee_tiers: Vec<u16>,
+    pub bump: u8,
+}
+
+impl Settings {
+    // 链上设置账户的长度
+    pub const LEN: usize = 58;
+
+    // 解析链上设置账户的数据
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() != Self::LEN {
+            return Err(ClientError::InvalidAccountData);
+        }
+        let mut reader = Reader { data, offset: 0 };
+        let admin = reader.address();
+        let creation_fee = reader.u64();
+        let count = reader.u8() as usize;
+        if count > MAX_FEE_TIERS {
+            return Err(ClientError::InvalidAccountData);
+        }
+        let tiers: Vec<u16> = (0..MAX_FEE_TIERS).map(|_| reader.u16()).collect();
+        Ok(Self {
+            admin,
+            creation_fee,
+            fee_tiers: tiers[..count].to_vec(),
+            bump: reader.u8(),
+        })
+    }
+
+    pub fn is_allowed_fee(&self, fee: u16) -> bool {
+        self.fee_tiers.contains(&fee)
+    }
+}
+
+// 链上 LP 仓位记录解析之后的内容 字段顺序和链上布局一致
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LpPosition {
+    pub config: Address,
+    pub owner: Address,
+    // 累计存入和取回的 x y 数量
+    pub deposited_x: u64,
+    pub deposited_y: u64,
+    pub withdrawn_x: u64,
+    pub withdrawn_y: u64,
+    // 累计铸造和销毁的 LP 数量
+    pub lp_minted: u64,
+    pub lp_burned: u64,
+    // 还持有的 LP 对应的成本
+    pub cost_x: u64,
+    pub cost_y: u64,
+    // unix 时间戳 还没有存入或取回过时为 0
+    pub opened_at: i64,
+    pub last_deposit_at: i64,
+    pub last_withdraw_at: i64,
+    pub bump: u8,
+}
+
+impl LpPosition {
+    // 链上仓位记录的长度
+    pub const LEN: usize = 153;
+
+    // 解析链上仓位记录的数据
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() != Self::LEN {
+            return Err(ClientError::InvalidAccountData);
+        }
+        let mut reader = Reader { data, offset: 0 };
+        Ok(Self {
+            config: reader.address(),
+            owner: reader.address(),
+            deposited_x: reader.u64(),
+            deposited_y: reader.u64(),
+            withdrawn_x: reader.u64(),
+            withdrawn_y: reader.u64(),
+            lp_minted: reader.u64(),
+            lp_burned: reader.u64(),
+            cost_x: reader.u64(),
+            cost_y: reader.u64(),
+            opened_at: reader.i64(),
+            last_deposit_at: reader.i64(),
+            last_withdraw_at: reader.i64(),
+            bump: reader.u8(),
+        })
+    }
+
+    // 记录中还持有的 LP
+    pub fn lp_held(&self) -> u64 {
+        self.lp_minted.saturating_sub(self.lp_burned)
+    }
+}
+
+// 按顺序读取小端字节 调用前已经校验过总长度
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let bytes = self.data[self.offset..self.offset + N].try_into().unwrap();
+        self.offset += N;
+        bytes
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.take::<1>()[0]
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes(self.take())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take())
+    }
+
+    fn i64(&mut self) -> i64 {
+        i64::from_le_bytes(self.take())
+    }
+
+    fn address(&mut self) -> Address {
+        Address::from(self.take::<32>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(byte: u8) -> Address {
+        Address::new_from_array([byte; 32])
+    }
+
+    // 按链上 Config 的字节偏移写入 每个字段使用不同的值 错位时解析结果一定不同
+    fn config_bytes() -> Vec<u8> {
+        let mut data = vec![0u8; Config::LEN];
+        data[0] = Config::VERSION;
+        data[1] = AmmState::WithdrawOnly as u8;
+        data[2..10].copy_from_slice(&0x0102_0304_0506_0708u64.to_le_bytes());
+        data[10..42].copy_from_slice(address(1).as_ref());
+        data[42..74].copy_from_slice(address(2).as_ref());
+        data[74..106].copy_from_slice(address(3).as_ref());
+        data[106..108].copy_from_slice(&30u16.to_le_bytes());
+        data[108] = 254;
+        data[109..141].copy_from_slice(address(4).as_ref());
+        data[141..143].copy_from_slice(&150u16.to_le_bytes());
+        data[143..151].copy_from_slice(&60u64.to_le_bytes());
+        data[151] = 9;
+        data[152] = 6;
+        data[153] = 1;
+        data[154..186].copy_from_slice(address(5).as_ref());
+        data[186..194].copy_from_slice(&1_000_000u64.to_le_bytes());
+        data[194..202].copy_from_slice(&2_000_000u64.to_le_bytes());
+        data[202..234].copy_from_slice(address(6).as_ref());
+        data[234..266].copy_from_slice(address(7).as_ref());
+        data[266..298].copy_from_slice(address(8).as_ref());
+        data[298..330].copy_from_slice(address(9).as_ref());
+        data[330] = 1;
+        data[331..339].copy_from_slice(&11u64.to_le_bytes());
+        data[339..347].copy_from_slice(&12u64.to_le_bytes());
+        data[347..349].copy_from_slice(&25u16.to_le_bytes());
+        data[349..357].copy_from_slice(&13u64.to_le_bytes());
+        data[357..365].copy_from_slice(&14u64.to_le_bytes());
+        data[365..373].copy_from_slice(&15u64.to_le_bytes());
+        data[373..405].copy_from_slice(address(10).as_ref());
+        data
+    }
+
+    #[test]
+    fn config_fields_match_on_chain_offsets() {
+        let config = Config::from_bytes(&config_bytes()).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                version: Config::VERSION,
+                state: AmmState::WithdrawOnly,
+                seed: 0x0102_0304_0506_0708,
+                authority: address(1),
+                mint_x: address(2),
+                mint_y: address(3),
+                fee: 30,
+                config_bump: 254,
+                oracle: address(4),
+                oracle_max_deviation: 150,
+                oracle_max_age: 60,
+                decimals_x: 9,
+                decimals_y: 6,
+                permissioned: true,
+                hook_program: address(5),
+                reserve_x: 1_000_000,
+                reserve_y: 2_000_000,
+                initializer: address(6),
+                vault_x: address(7),
+                vault_y: address(8),
+                mint_lp: address(9),
+                twamm: true,
+                twamm_escrow_x: 11,
+                twamm_escrow_y: 12,
+                slot_price_band: 25,
+                open_slot: 13,
+                open_reserve_x: 14,
+                open_reserve_y: 15,
+                guardian: address(10),
+            }
+        );
+        assert!(config.has_authority());
+    }
+
+    #[test]
+    fn config_rejects_bad_length_version_and_state() {
+        let data = config_bytes();
+        assert_eq!(
+            Config::from_bytes(&data[..Config::LEN - 1]),
+            Err(ClientError::InvalidAccountData)
+        );
+
+        let mut data = config_bytes();
+        data[0] = 0;
+        assert_eq!(
+            Config::from_bytes(&data),
+            Err(ClientError::UnsupportedVersion(0))
+        );
+        data[0] = Config::VERSION + 1;
+        assert_eq!(
+            Config::from_bytes(&data),
+            Err(ClientError::UnsupportedVersion(Config::VERSION + 1))
+        );
+
+        let mut data = config_bytes();
+        data[1] = AmmState::WithdrawOnly as u8 + 1;
+        assert_eq!(
+            Config::from_bytes(&data),
+            Err(ClientError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,532 @@
+// 用链上程序自己的 TryFrom 解析客户端拼出来的指令 校验数据字节和账户顺序
+// 同时用链上 Config 的 setter 写入账户 校验客户端按同样的偏移解析
+use blueshift_amm_client::{
+    find_associated_token_address, find_config_address, find_mint_lp_address,
+    find_position_address, find_program_data_address, find_settings_address, AccountMeta, AmmState,
+    CollectCreationFees, Config, CreateSettings, Deposit, Initialize, Instruction, OpenPosition,
+    Pool, QuotePosition, Swap, UpdateSettings, Withdraw, BPF_LOADER_UPGRADEABLE_ID, NATIVE_MINT,
+    PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use blueshift_native_amm as program;
+use pinocchio::account::{RuntimeAccount, NOT_BORROWED};
+use pinocchio::AccountView;
+use solana_address::Address;
+
+fn address(byte: u8) -> Address {
+    Address::new_from_array([byte; 32])
+}
+
+fn pool() -> Pool {
+    Pool::new(7, address(1), address(2))
+}
+
+// 链上解析账户时会检查所有者和数据长度 没有登记的账户是空的系统账户
+#[derive(Default)]
+struct Ledger {
+    accounts: Vec<(Address, Address, Vec<u8>)>,
+}
+
+impl Ledger {
+    fn with_pool(pool: &Pool) -> Self {
+        let mut ledger = Self::default();
+        ledger.add(&pool.config, &PROGRAM_ID, vec![0; program::Config::LEN]);
+        ledger.add(&pool.mint_lp, &TOKEN_PROGRAM_ID, vec![0; 82]);
+        ledger
+    }
+
+    fn add(&mut self, address: &Address, owner: &Address, data: Vec<u8>) {
+        self.accounts.push((address.clone(), owner.clone(), data));
+    }
+
+    fn views(&self, instruction: &Instruction) -> Accounts {
+        assert_eq!(instruction.program_id, PROGRAM_ID);
+        let mut accounts = Accounts::default();
+        for meta in &instruction.accounts {
+            let (owner, data) = self
+                .accounts
+                .iter()
+                .find(|(address, _, _)| address.eq(&meta.pubkey))
+                .map_or((SYSTEM_PROGRAM_ID, &[][..]), |(_, owner, data)| {
+                    (owner.clone(), &data[..])
+                });
+            accounts.push(meta, owner, data);
+        }
+        accounts
+    }
+}
+
+// 按运行时的输入布局保存账户 RuntimeAccount 之后紧跟账户数据
+#[derive(Default)]
+struct Accounts {
+    buffers: Vec<Vec<u64>>,
+    views: Vec<AccountView>,
+}
+
+impl Accounts {
+    fn push(&mut self, meta: &AccountMeta, owner: Address, data: &[u8]) {
+        let header = size_of::<RuntimeAccount>();
+        let mut buffer = vec![0u64; (header + data.len()).div_ceil(8)];
+        let raw = buffer.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe {
+            raw.write(RuntimeAccount {
+                borrow_state: NOT_BORROWED,
+                is_signer: meta.is_signer as u8,
+                is_writable: meta.is_writable as u8,
+                executable: 0,
+                resize_delta: 0,
+                address: meta.pubkey.clone(),
+                owner,
+                lamports: 1_000_000_000,
+                data_len: data.len() as u64,
+            });
+            core::ptr::copy_nonoverlapping(data.as_ptr(), (raw as *mut u8).add(header), data.len());
+            self.views.push(AccountView::new_unchecked(raw));
+        }
+        // Vec 移动时堆上的内存不会移动 views 中的指针一直有效
+        self.buffers.push(buffer);
+    }
+}
+
+fn addresses(accounts: &[&AccountView]) -> Vec<Address> {
+    accounts
+        .iter()
+        .map(|account| account.address().clone())
+        .collect()
+}
+
+#[test]
+fn initialize_matches_program_layout() {
+    let initialize = Initialize {
+        initializer: address(9),
+        seed: 7,
+        fee: 30,
+        mint_x: address(1),
+        mint_y: address(2),
+        authority: Some(address(3)),
+    };
+    let instruction = initialize.instruction().unwrap();
+    assert_eq!(instruction.data[0], 0);
+
+    let data = program::InitializeInstructionData::try_from(&instruction.data[1..]).unwrap();
+    let (config, config_bump) = find_config_address(7, &address(1), &address(2));
+    let (mint_lp, lp_bump) = find_mint_lp_address(&config);
+    assert_eq!({ data.seed }, 7);
+    assert_eq!({ data.fee }, 30);
+    assert_eq!(data.mint_x, *address(1).as_array());
+    assert_eq!(data.mint_y, *address(2).as_array());
+    assert_eq!(data.config_bump, [config_bump]);
+    assert_eq!(data.lp_bump, [lp_bump]);
+    assert_eq!(data.authority, *address(3).as_array());
+
+    let accounts = Ledger::default().views(&instruction);
+    let parsed = program::InitializeAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        addresses(&[
+            parsed.initializer,
+            parsed.mint_lp,
+            parsed.config,
+            parsed.settings
+        ]),
+        vec![address(9), mint_lp, config, find_settings_address().0]
+    );
+
+    // 不可变的池子不带 authority 链上补成全 0
+    let instruction = Initialize {
+        authority: None,
+        ..initialize
+    }
+    .instruction()
+    .unwrap();
+    let data = program::InitializeInstructionData::try_from(&instruction.data[1..]).unwrap();
+    assert_eq!(data.authority, [0; 32]);
+}
+
+#[test]
+fn deposit_matches_program_layout() {
+    let pool = pool();
+    let deposit = Deposit {
+        user: address(9),
+        pool: pool.clone(),
+        amount: 100,
+        max_x: 200,
+        max_y: 300,
+        expiration: 400,
+        native: false,
+        expiration_slot: 0,
+        remaining_accounts: vec![AccountMeta::new(address(10), false)],
+    };
+    let instruction = deposit.instruction().unwrap();
+    assert_eq!(instruction.data[0], 1);
+
+    let data = program::DepositInstructionData::try_from(&instruction.data[1..]).unwrap();
+    assert_eq!(
+        (data.amount, data.max_x, data.max_y, data.expiration),
+        (100, 200, 300, 400)
+    );
+    assert!(!data.native);
+    assert_eq!(data.expiration_slot, 0);
+
+    let accounts = Ledger::with_pool(&pool).views(&instruction);
+    let parsed = program::DepositAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        addresses(&[
+            parsed.user,
+            parsed.mint_lp,
+            parsed.vault_x,
+            parsed.vault_y,
+            parsed.config,
+            parsed.token_program,
+        ]),
+        vec![
+            address(9),
+            pool.mint_lp.clone(),
+            pool.vault_x.clone(),
+            pool.vault_y.clone(),
+            pool.config.clone(),
+            TOKEN_PROGRAM_ID,
+        ]
+    );
+    assert_eq!(
+        addresses(&[parsed.user_x_ata, parsed.user_y_ata, parsed.user_lp_ata]),
+        vec![
+            find_associated_token_address(&address(9), &pool.mint_x),
+            find_associated_token_address(&address(9), &pool.mint_y),
+            find_associated_token_address(&address(9), &pool.mint_lp),
+        ]
+    );
+    assert_eq!(parsed.remaining[0].address(), &address(10));
+
+    // 原生 SOL 和 slot 截止时间都是可选的尾部字段
+    let native = Deposit {
+        pool: Pool::new(7, NATIVE_MINT, address(2)),
+        native: true,
+        expiration_slot: 500,
+        ..deposit
+    };
+    let instruction = native.instruction().unwrap();
+    let data = program::DepositInstructionData::try_from(&instruction.data[1..]).unwrap();
+    assert!(data.native);
+    assert_eq!(data.expiration_slot, 500);
+    let accounts = Ledger::with_pool(&native.pool).views(&instruction);
+    let parsed = program::DepositAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(parsed.user_x_ata.address(), &SYSTEM_PROGRAM_ID);
+}
+
+#[test]
+fn withdraw_matches_program_layout() {
+    let pool = pool();
+    let withdraw = Withdraw {
+        user: address(9),
+        pool: pool.clone(),
+        amount: 100,
+        min_x: 200,
+        min_y: 300,
+        expiration: 400,
+        expiration_slot: 500,
+        remaining_accounts: Vec::new(),
+    };
+    let instruction = withdraw.instruction().unwrap();
+    assert_eq!(instruction.data[0], 2);
+
+    let data = program::WithdrawInstructionData::try_from(&instruction.data[1..]).unwrap();
+    assert_eq!(
+        (
+            data.amount,
+            data.min_x,
+            data.min_y,
+            data.expiration,
+            data.expiration_slot
+        ),
+        (100, 200, 300, 400, 500)
+    );
+
+    let accounts = Ledger::with_pool(&pool).views(&instruction);
+    let parsed = program::WithdrawAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        addresses(&[
+            parsed.user,
+            parsed.mint_lp,
+            parsed.vault_x,
+            parsed.vault_y,
+            parsed.user_x_ata,
+            parsed.user_y_ata,
+            parsed.user_lp_ata,
+            parsed.config,
+            parsed.token_program,
+        ]),
+        vec![
+            address(9),
+            pool.mint_lp.clone(),
+            pool.vault_x.clone(),
+            pool.vault_y.clone(),
+            find_associated_token_address(&address(9), &pool.mint_x),
+            find_associated_token_address(&address(9), &pool.mint_y),
+            find_associated_token_address(&address(9), &pool.mint_lp),
+            pool.config.clone(),
+            TOKEN_PROGRAM_ID,
+        ]
+    );
+    assert!(parsed.remaining.is_empty());
+}
+
+#[test]
+fn swap_matches_program_layout() {
+    let pool = pool();
+    let swap = Swap {
+        user: address(9),
+        pool: pool.clone(),
+        is_x: true,
+        amount: 100,
+        min: 200,
+        expiration: 400,
+        native: false,
+        temporary_seed: 0,
+        expiration_slot: 0,
+        remaining_accounts: Vec::new(),
+    };
+    let instruction = swap.instruction().unwrap();
+    assert_eq!(instruction.data[0], 3);
+
+    let data = program::SwapInstructionData::try_from(&instruction.data[1..]).unwrap();
+    assert!(data.is_x);
+    assert_eq!((data.amount, data.min, data.expiration), (100, 200, 400));
+    assert!(!data.native);
+    assert_eq!(data.expiration_slot, 0);
+
+    let accounts = Ledger::with_pool(&pool).views(&instruction);
+    let parsed = program::SwapAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        addresses(&[
+            parsed.user,
+            parsed.user_x_ata,
+            parsed.user_y_ata,
+            parsed.vault_x,
+            parsed.vault_y,
+            parsed.config,
+            parsed.token_program,
+        ]),
+        vec![
+            address(9),
+            find_associated_token_address(&address(9), &pool.mint_x),
+            find_associated_token_address(&address(9), &pool.mint_y),
+            pool.vault_x.clone(),
+            pool.vault_y.clone(),
+            pool.config.clone(),
+            TOKEN_PROGRAM_ID,
+        ]
+    );
+
+    // 用 x 换原生 SOL 时 接收一侧是临时 wSOL 账户
+    let native = Swap {
+        pool: Pool::new(7, address(1), NATIVE_MINT),
+        native: true,
+        temporary_seed: 1,
+        expiration_slot: 500,
+        ..swap
+    };
+    let instruction = native.instruction().unwrap();
+    let data = program::SwapInstructionData::try_from(&instruction.data[1..]).unwrap();
+    assert!(data.native);
+    assert_eq!(data.expiration_slot, 500);
+    let accounts = Ledger::with_pool(&native.pool).views(&instruction);
+    let parsed = program::SwapAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        Some(parsed.user_y_ata.address().clone()),
+        native.temporary_wsol().unwrap()
+    );
+}
+
+#[test]
+fn position_instructions_match_program_layout() {
+    let pool = pool();
+    let (position, _) = find_position_address(&pool.config, &address(9));
+
+    let instruction = OpenPosition {
+        user: address(9),
+        pool: pool.clone(),
+    }
+    .instruction()
+    .unwrap();
+    assert_eq!(instruction.data, vec![33]);
+    let accounts = Ledger::with_pool(&pool).views(&instruction);
+    let parsed = program::OpenPositionAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        addresses(&[
+            parsed.user,
+            parsed.config,
+            parsed.position,
+            parsed.system_program
+        ]),
+        vec![
+            address(9),
+            pool.config.clone(),
+            position.clone(),
+            SYSTEM_PROGRAM_ID
+        ]
+    );
+
+    let instruction = QuotePosition {
+        user: address(9),
+        pool: pool.clone(),
+    }
+    .instruction()
+    .unwrap();
+    assert_eq!(instruction.data, vec![34]);
+    let accounts = Ledger::with_pool(&pool).views(&instruction);
+    let parsed = program::QuotePositionAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        addresses(&[parsed.config, parsed.mint_lp, parsed.position]),
+        vec![pool.config.clone(), pool.mint_lp.clone(), position]
+    );
+}
+
+#[test]
+fn settings_instructions_match_program_layout() {
+    let instruction = CreateSettings {
+        payer: address(9),
+        admin: address(3),
+        creation_fee: 1_000,
+        fee_tiers: vec![1, 5, 30, 100],
+    }
+    .instruction()
+    .unwrap();
+    assert_eq!(instruction.data[0], 35);
+    let data = program::SettingsInstructionData::try_from(&instruction.data[1..]).unwrap();
+    assert_eq!(data.admin, address(3));
+    assert_eq!(data.creation_fee, 1_000);
+    assert_eq!(data.fee_tiers(), &[1, 5, 30, 100]);
+
+    // 升级权限记录在 ProgramData 中 [3u32, slot: u64, Some, authority]
+    let mut program_data = vec![0u8; 45];
+    program_data[0..4].copy_from_slice(&3u32.to_le_bytes());
+    program_data[12] = 1;
+    program_data[13..45].copy_from_slice(address(9).as_ref());
+    let mut ledger = Ledger::default();
+    ledger.add(
+        &find_program_data_address(),
+        &BPF_LOADER_UPGRADEABLE_ID,
+        program_data,
+    );
+    let accounts = ledger.views(&instruction);
+    let parsed = program::CreateSettingsAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        addresses(&[
+            parsed.payer,
+            parsed.settings,
+            parsed.program_data,
+            parsed.system_program,
+        ]),
+        vec![
+            address(9),
+            find_settings_address().0,
+            find_program_data_address(),
+            SYSTEM_PROGRAM_ID,
+        ]
+    );
+
+    let instruction = UpdateSettings {
+        admin: address(3),
+        new_admin: address(4),
+        creation_fee: 0,
+        fee_tiers: vec![30],
+    }
+    .instruction()
+    .unwrap();
+    assert_eq!(instruction.data[0], 36);
+    let data = program::SettingsInstructionData::try_from(&instruction.data[1..]).unwrap();
+    assert_eq!(data.admin, address(4));
+    assert_eq!(data.creation_fee, 0);
+    assert_eq!(data.fee_tiers(), &[30]);
+    let accounts = Ledger::default().views(&instruction);
+    let parsed = program::UpdateSettingsAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        addresses(&[parsed.admin, parsed.settings]),
+        vec![address(3), find_settings_address().0]
+    );
+    assert!(parsed.admin.is_signer());
+
+    let instruction = CollectCreationFees {
+        admin: address(3),
+        destination: address(5),
+    }
+    .instruction()
+    .unwrap();
+    assert_eq!(instruction.data, vec![37]);
+    let accounts = Ledger::default().views(&instruction);
+    let parsed = program::CollectCreationFeesAccounts::try_from(&accounts.views[..]).unwrap();
+    assert_eq!(
+        addresses(&[parsed.admin, parsed.settings, parsed.destination]),
+        vec![address(3), find_settings_address().0, address(5)]
+    );
+}
+
+#[test]
+fn config_decodes_what_program_writes() {
+    let mut data = vec![0u8; program::Config::LEN];
+    assert_eq!(program::Config::LEN, Config::LEN);
+    assert_eq!(program::Config::VERSION, Config::VERSION);
+    {
+        let config = unsafe { program::Config::from_bytes_unchecked_mut(&mut data) };
+        config.set_version(program::Config::VERSION).unwrap();
+        config
+            .set_inner(
+                0x0102_0304_0506_0708,
+                address(1),
+                address(2),
+                address(3),
+                30,
+                [254],
+            )
+            .unwrap();
+        config.set_state(AmmState::WithdrawOnly as u8).unwrap();
+        config.set_oracle(address(4), 150, 60, 9, 6).unwrap();
+        config.set_permissioned(true).unwrap();
+        config.set_hook_program(address(5)).unwrap();
+        config.set_initializer(address(6)).unwrap();
+        config.set_vaults(address(7), address(8)).unwrap();
+        config.set_mint_lp(address(9)).unwrap();
+        config.set_twamm(true).unwrap();
+        config.set_twamm_escrow(11, 12).unwrap();
+        config.set_slot_price_band(25).unwrap();
+        // 新 slot 的第一笔交易记下开盘储备
+        config.check_slot_price_band(13, 14, 15, 14, 15).unwrap();
+        config.set_reserves(1_000_000, 2_000_000).unwrap();
+        config.set_guardian(address(10)).unwrap();
+    }
+
+    let config = Config::from_bytes(&data).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            version: Config::VERSION,
+            state: AmmState::WithdrawOnly,
+            seed: 0x0102_0304_0506_0708,
+            authority: address(1),
+            mint_x: address(2),
+            mint_y: address(3),
+            fee: 30,
+            config_bump: 254,
+            oracle: address(4),
+            oracle_max_deviation: 150,
+            oracle_max_age: 60,
+            decimals_x: 9,
+            decimals_y: 6,
+            permissioned: true,
+            hook_program: address(5),
+            reserve_x: 1_000_000,
+            reserve_y: 2_000_000,
+            initializer: address(6),
+            vault_x: address(7),
+            vault_y: address(8),
+            mint_lp: address(9),
+            twamm: true,
+            twamm_escrow_x: 11,
+            twamm_escrow_y: 12,
+            slot_price_band: 25,
+            open_slot: 13,
+            open_reserve_x: 14,
+            open_reserve_y: 15,
+            guardian: address(10),
+        }
+    );
+}
@@ -0,0 +1,15 @@
+#![no_main]
+
+// 用覆盖率引导的模糊测试检查曲线的不变量
+// cargo +nightly fuzz run curve
+// 种子和发现的反例保存在 corpus/curve 中 tests/regressions.rs 会在 cargo test 时回放
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use common::{check_all, CurveInput};
+
+fuzz_target!(|data: &[u8]| {
+    check_all(&CurveInput::from_bytes(data));
+});
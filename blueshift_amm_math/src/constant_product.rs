@@ -0,0 +1,199 @@
+use crate::{mul_div_ceil, mul_div_floor, Curve, CurveError, LiquidityPair};
+
+// 校验数组中的全部数据是否包含 0
+macro_rules! assert_non_zero {
+    ($array:expr) => {
+        if $array.contains(&0u64) {
+            return Err(CurveError::ZeroReserve);
+        }
+    };
+}
+
+// 比较两个数的大小 如果第一个数比第二个数小就报错
+macro_rules! swap_slippage {
+    ($x:expr, $x_min:expr) => {
+        if $x < $x_min {
+            return Err(CurveError::SlippageExceeded);
+        }
+    };
+}
+
+// 恒定乘积曲线 x * y = k
+// x-代币x的储备 y-代币y的储备 fee-费率
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantProduct {
+    x: u64,   // Balance of Token X
+    y: u64,   // Balance of Token Y
+    fee: u16, // Fee in basis points, ie: 100 = 1%
+}
+
+impl ConstantProduct {
+    // 创建一条可以交换的曲线 储备不能为 0
+    // Create a new Constant Product Curve
+    pub fn init(x: u64, y: u64, fee: u16) -> Result<ConstantProduct, CurveError> {
+        // Assert non-zero values of X and Y
+        assert_non_zero!([x, y]);
+        Self::new(x, y, fee)
+    }
+
+    // 允许储备为 0 首次注入流动性之前的池子也可以计算存款报价
+    pub fn new(x: u64, y: u64, fee: u16) -> Result<ConstantProduct, CurveError> {
+        if fee >= 10_000 {
+            return Err(CurveError::InvalidFee);
+        }
+        Ok(ConstantProduct { x, y, fee })
+    }
+
+    #[inline(always)]
+    pub fn fee(&self) -> u16 {
+        self.fee
+    }
+
+    // p: x代表 支付x 获取y；y代表 支付y 获取x
+    // a: 愿意支付的代币数量
+    // min: 愿意接受的最小代币数量
+    // 成交之后更新曲线的储备 返回 (支付的代币数量, 手续费, 用户拿到的代币数量)
+    // Swap asset X for asset Y or vice versa with slippage protection
+    pub fn swap(
+        &mut self,
+        p: LiquidityPair,
+        a: u64,
+        min: u64,
+    ) -> Result<(u64, u64, u64), CurveError> {
+        assert_non_zero!([self.x, self.y]);
+        // 手续费 = 支付金额 * 费率 / 10_000 向上取整
+        // 扣掉手续费之后 才是实际能用来买新代币的代币数量
+        let fee = mul_div_ceil(a, self.fee as u64, 10_000)?;
+        let a2 = a - fee;
+
+        // 返回的是新的x值 新的y值和 支付给用户的代币数量
+        // 手续费留在池子里 所以新的储备加上的是用户支付的全部金额
+        let (new_x, new_y, withdraw) = match p {
+            // 支付x 获取y
+            LiquidityPair::X => {
+                let delta_y = Self::delta_y_from_x_swap_amount(self.x, self.y, a2)?;
+                let x2 = self.x.checked_add(a).ok_or(CurveError::Overflow)?;
+                (x2, self.y - delta_y, delta_y)
+            }
+            // 支付y 获取x
+            LiquidityPair::Y => {
+                let delta_x = Self::delta_x_from_y_swap_amount(self.x, self.y, a2)?;
+                let y2 = self.y.checked_add(a).ok_or(CurveError::Overflow)?;
+                (self.x - delta_x, y2, delta_x)
+            }
+        };
+        // 如果提现的数据小于用户愿意获取的最小值 报错返回
+        swap_slippage!(withdraw, min);
+        self.x = new_x;
+        self.y = new_y;
+
+        Ok((a, fee, withdraw))
+    }
+
+    // x-之前的代币x的数量 y-之前的代币y的数量 a-支付的代币x的数量
+    pub fn y2_from_x_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, CurveError> {
+        Self::x2_from_y_swap_amount(y, x, a)
+    }
+
+    // 这里是一个公用方法 x转y 和 y转x 都走这个方法 但是这里的x和y传的值不一样
+    // 用原有的k值除以最新的y值 得到新的 x值 向上取整 保证 k 不会变小
+    // Calculate new value of X after depositing Y
+    // When we swap amount A of Y for X, we must calculate the new balance of X from invariant K
+    // Y₂ = Y₁ + Amount
+    // X₂ = ⌈K / Y₂⌉
+    pub fn x2_from_y_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, CurveError> {
+        assert_non_zero!([x, y]);
+        let y2 = y.checked_add(a).ok_or(CurveError::Overflow)?;
+        mul_div_ceil(x, y, y2)
+    }
+
+    // 就是旧的代币数量减去新的代币数量 也就是用户买到的代币数量 相当于向下取整
+    // Calculate the withdraw amount of X from swapping in Y
+    // ΔX = X₁ - X₂
+    pub fn delta_x_from_y_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, CurveError> {
+        x.checked_sub(Self::x2_from_y_swap_amount(x, y, a)?)
+            .ok_or(CurveError::Overflow)
+    }
+
+    // Calculate difference in Y from swapping in X
+    // ΔY = Y₁ - Y₂
+    pub fn delta_y_from_x_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, CurveError> {
+        Self::delta_x_from_y_swap_amount(y, x, a)
+    }
+
+    // 这里就是校验 x 和 y都 不为0 之后 获取 x*y 也就是k的值 然后返回
+    // Static Invariant calculation
+    pub fn k_from_xy(x: u64, y: u64) -> Result<u128, CurveError> {
+        assert_non_zero!([x, y]);
+        Ok(x as u128 * y as u128)
+    }
+
+    // x: 库存的x代币数量
+    // y: 库存的y代币数量
+    // l: 流动性代币lp的流通量
+    // a: 用户希望接收的 LP 代币数量
+    // 用户需要支付的数量 = 储备 * a / l 向上取整 保证新铸造的 LP 不会稀释已有的 LP
+    pub fn xy_deposit_amounts_from_l(
+        x: u64,
+        y: u64,
+        l: u64,
+        a: u64,
+    ) -> Result<(u64, u64), CurveError> {
+        let deposit_x = mul_div_ceil(x, a, l)?;
+        let deposit_y = mul_div_ceil(y, a, l)?;
+        Ok((deposit_x, deposit_y))
+    }
+
+    // Get amount of X and Y to withdraw from liquidity token amount
+    // a: 用户希望销毁的 LP 代币数量
+    // 用户取回的数量 = 储备 * a / l 向下取整 全部销毁时正好取回全部储备
+    pub fn xy_withdraw_amounts_from_l(
+        x: u64,
+        y: u64,
+        l: u64,
+        a: u64,
+    ) -> Result<(u64, u64), CurveError> {
+        let withdraw_x = mul_div_floor(x, a, l)?;
+        let withdraw_y = mul_div_floor(y, a, l)?;
+        Ok((withdraw_x, withdraw_y))
+    }
+}
+
+impl Curve for ConstantProduct {
+    fn reserves(&self) -> (u64, u64) {
+        (self.x, self.y)
+    }
+
+    fn quote_swap(
+        &self,
+        p: LiquidityPair,
+        amount: u64,
+        min: u64,
+    ) -> Result<(u64, u64, u64), CurveError> {
+        let mut curve = *self;
+        curve.swap(p, amount, min)
+    }
+
+    fn quote_deposit(
+        &self,
+        supply: u64,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+    ) -> Result<(u64, u64), CurveError> {
+        match supply == 0 && self.x == 0 && self.y == 0 {
+            // 储备和 LP 供应量全部都是 0 也就是首次注入流动性
+            // 这时由用户定下 x y 和 LP 的兑换比率
+            true => Ok((max_x, max_y)),
+            false => Self::xy_deposit_amounts_from_l(self.x, self.y, supply, amount),
+        }
+    }
+
+    fn quote_withdraw(&self, supply: u64, amount: u64) -> Result<(u64, u64), CurveError> {
+        // 不能销毁超过流通量的 LP
+        if amount > supply {
+            return Err(CurveError::InvalidAmount);
+        }
+        Self::xy_withdraw_amounts_from_l(self.x, self.y, supply, amount)
+    }
+}
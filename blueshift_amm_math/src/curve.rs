@@ -0,0 +1,38 @@
+use crate::CurveError;
+
+// 交换方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidityPair {
+    // 支付 x 获取 y
+    X,
+    // 支付 y 获取 x
+    Y,
+}
+
+// 一条定价曲线需要提供的报价
+// 所有方法都只根据当前储备计算 不修改状态 调用方自己决定是否更新储备
+pub trait Curve {
+    // 当前的储备 (x, y)
+    fn reserves(&self) -> (u64, u64);
+
+    // 支付 amount 个代币进行交换 拿到的数量少于 min 时报错
+    // 返回 (支付的代币数量, 手续费, 用户拿到的代币数量)
+    fn quote_swap(
+        &self,
+        p: LiquidityPair,
+        amount: u64,
+        min: u64,
+    ) -> Result<(u64, u64, u64), CurveError>;
+
+    // 铸造 amount 个 LP 需要支付的 (x, y) 首次注入流动性时直接使用 max_x 和 max_y
+    fn quote_deposit(
+        &self,
+        supply: u64,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+    ) -> Result<(u64, u64), CurveError>;
+
+    // 销毁 amount 个 LP 可以取回的 (x, y)
+    fn quote_withdraw(&self, supply: u64, amount: u64) -> Result<(u64, u64), CurveError>;
+}
@@ -0,0 +1,31 @@
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveError {
+    // 储备为 0 时无法计算价格
+    ZeroReserve,
+    // 费率必须小于 10_000 基点
+    InvalidFee,
+    // 支付数量为 0 或者超过了可用数量
+    InvalidAmount,
+    // 除数为 0
+    DivisionByZero,
+    // 计算结果溢出
+    Overflow,
+    // 拿到的代币少于用户愿意接受的最小值
+    SlippageExceeded,
+}
+
+impl fmt::Display for CurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            CurveError::ZeroReserve => "reserve is zero",
+            CurveError::InvalidFee => "fee must be below 10000 basis points",
+            CurveError::InvalidAmount => "invalid amount",
+            CurveError::DivisionByZero => "division by zero",
+            CurveError::Overflow => "arithmetic overflow",
+            CurveError::SlippageExceeded => "slippage exceeded",
+        };
+        f.write_str(message)
+    }
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+// AMM 的曲线计算 不依赖 Solana 的任何类型
+// 链上程序、链下机器人和以后新增的曲线共用这一份实现
+
+pub mod constant_product;
+pub mod curve;
+pub mod error;
+pub mod math;
+
+pub use constant_product::*;
+pub use curve::*;
+pub use error::*;
+pub use math::*;
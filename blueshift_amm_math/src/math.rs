@@ -0,0 +1,26 @@
+use crate::CurveError;
+
+// 所有涉及比例的计算都走这里 中间结果用 u128 保存 不会丢精度
+// 两个 u64 相乘最大不超过 u128 所以这里不需要 U256
+// 调用方需要明确选择取整方向 原则是让池子占便宜：
+// 用户拿走的数量向下取整 用户需要支付的数量向上取整
+
+// a * b / c 向下取整
+#[inline(always)]
+pub fn mul_div_floor(a: u64, b: u64, c: u64) -> Result<u64, CurveError> {
+    if c == 0 {
+        return Err(CurveError::DivisionByZero);
+    }
+    let result = (a as u128 * b as u128) / c as u128;
+    u64::try_from(result).map_err(|_| CurveError::Overflow)
+}
+
+// a * b / c 向上取整
+#[inline(always)]
+pub fn mul_div_ceil(a: u64, b: u64, c: u64) -> Result<u64, CurveError> {
+    if c == 0 {
+        return Err(CurveError::DivisionByZero);
+    }
+    let result = (a as u128 * b as u128).div_ceil(c as u128);
+    u64::try_from(result).map_err(|_| CurveError::Overflow)
+}
@@ -0,0 +1,156 @@
+// cargo test 和模糊测试共用的不变量检查
+// 违反不变量时直接 panic 两种测试都会把 panic 当成失败并保存触发的输入
+#![allow(dead_code)]
+
+use blueshift_amm_math::{ConstantProduct, Curve, CurveError, LiquidityPair};
+
+// 一组随机输入 模糊测试直接从字节解析
+#[derive(Debug, Clone, Copy)]
+pub struct CurveInput {
+    pub x: u64,
+    pub y: u64,
+    pub fee: u16,
+    pub amount: u64,
+    pub is_x: bool,
+    pub supply: u64,
+    pub lp: u64,
+}
+
+impl CurveInput {
+    // 8 + 8 + 2 + 8 + 1 + 8 + 8 字节 不足的部分补 0
+    pub const LEN: usize = 43;
+
+    pub fn from_bytes(data: &[u8]) -> Self {
+        let mut bytes = [0u8; Self::LEN];
+        let len = data.len().min(Self::LEN);
+        bytes[..len].copy_from_slice(&data[..len]);
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        Self {
+            x: u64_at(0),
+            y: u64_at(8),
+            fee: u16::from_le_bytes([bytes[16], bytes[17]]),
+            amount: u64_at(18),
+            is_x: bytes[26] & 1 == 1,
+            supply: u64_at(27),
+            lp: u64_at(35),
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[0..8].copy_from_slice(&self.x.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.y.to_le_bytes());
+        bytes[16..18].copy_from_slice(&self.fee.to_le_bytes());
+        bytes[18..26].copy_from_slice(&self.amount.to_le_bytes());
+        bytes[26] = self.is_x as u8;
+        bytes[27..35].copy_from_slice(&self.supply.to_le_bytes());
+        bytes[35..43].copy_from_slice(&self.lp.to_le_bytes());
+        bytes
+    }
+}
+
+// 交换之后 k 不会变小 拿走的代币不会超过储备 手续费不会超过支付的数量
+pub fn check_swap(input: &CurveInput) {
+    let Ok(mut curve) = ConstantProduct::init(input.x, input.y, input.fee) else {
+        assert!(input.x == 0 || input.y == 0 || input.fee >= 10_000);
+        return;
+    };
+    let p = match input.is_x {
+        true => LiquidityPair::X,
+        false => LiquidityPair::Y,
+    };
+    let quote = curve.quote_swap(p, input.amount, 0);
+    match curve.swap(p, input.amount, 0) {
+        Ok((amount_in, fee, amount_out)) => {
+            assert_eq!(quote, Ok((amount_in, fee, amount_out)));
+            assert_eq!(amount_in, input.amount);
+            assert!(fee <= amount_in);
+            let (reserve_in, reserve_out) = match input.is_x {
+                true => (input.x, input.y),
+                false => (input.y, input.x),
+            };
+            assert!(
+                amount_out < reserve_out,
+                "output {amount_out} drains reserve {reserve_out}"
+            );
+            let (new_x, new_y) = curve.reserves();
+            let (new_in, new_out) = match input.is_x {
+                true => (new_x, new_y),
+                false => (new_y, new_x),
+            };
+            assert_eq!(new_in, reserve_in + amount_in);
+            assert_eq!(new_out, reserve_out - amount_out);
+            let k = input.x as u128 * input.y as u128;
+            let new_k = new_x as u128 * new_y as u128;
+            assert!(new_k >= k, "k decreased from {k} to {new_k}");
+            // 带滑点保护的报价 要求的最小值超过实际数量时必须报错
+            if amount_out < u64::MAX {
+                let strict = ConstantProduct::init(input.x, input.y, input.fee)
+                    .unwrap()
+                    .quote_swap(p, input.amount, amount_out + 1);
+                assert_eq!(strict, Err(CurveError::SlippageExceeded));
+            }
+        }
+        // 失败时只允许溢出 储备的状态保持不变
+        Err(error) => {
+            assert_eq!(error, CurveError::Overflow);
+            assert_eq!(quote, Err(error));
+            assert_eq!(curve.reserves(), (input.x, input.y));
+        }
+    }
+}
+
+// 先存入再取出同样数量的 LP 拿回的代币不会超过存入的代币
+pub fn check_deposit_withdraw(input: &CurveInput) {
+    if input.supply == 0 || input.lp == 0 {
+        return;
+    }
+    let Ok((deposit_x, deposit_y)) =
+        ConstantProduct::xy_deposit_amounts_from_l(input.x, input.y, input.supply, input.lp)
+    else {
+        return;
+    };
+    let (Some(x), Some(y), Some(supply)) = (
+        input.x.checked_add(deposit_x),
+        input.y.checked_add(deposit_y),
+        input.supply.checked_add(input.lp),
+    ) else {
+        return;
+    };
+    let (withdraw_x, withdraw_y) =
+        ConstantProduct::xy_withdraw_amounts_from_l(x, y, supply, input.lp)
+            .expect("withdrawing freshly minted LP must succeed");
+    assert!(
+        withdraw_x <= deposit_x,
+        "withdrew {withdraw_x} x after depositing {deposit_x}"
+    );
+    assert!(
+        withdraw_y <= deposit_y,
+        "withdrew {withdraw_y} y after depositing {deposit_y}"
+    );
+}
+
+// 取出的代币不会超过储备 全部销毁时正好取回全部储备
+pub fn check_withdraw(input: &CurveInput) {
+    let curve = ConstantProduct::new(input.x, input.y, 0).unwrap();
+    match curve.quote_withdraw(input.supply, input.lp) {
+        Ok((x, y)) => {
+            assert!(input.lp <= input.supply);
+            assert!(x <= input.x && y <= input.y);
+            if input.lp == input.supply {
+                assert_eq!((x, y), (input.x, input.y));
+            }
+        }
+        Err(error) => assert!(
+            input.lp > input.supply || error == CurveError::DivisionByZero,
+            "unexpected {error:?}"
+        ),
+    }
+}
+
+pub fn check_all(input: &CurveInput) {
+    check_swap(input);
+    check_deposit_withdraw(input);
+    check_withdraw(input);
+}
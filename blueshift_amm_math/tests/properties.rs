@@ -0,0 +1,125 @@
+// 曲线的性质测试 proptest 随机生成输入 失败时自动缩小到最小的反例
+// 同样的检查也由 fuzz/ 下的模糊测试驱动 见 tests/common/mod.rs
+mod common;
+
+use blueshift_amm_math::{ConstantProduct, Curve, CurveError, LiquidityPair};
+use common::{check_all, check_deposit_withdraw, check_swap, check_withdraw, CurveInput};
+use proptest::prelude::*;
+
+// 一半概率取小数值 一半概率取整个 u64 范围 这样既能覆盖取整的边界也能覆盖溢出
+fn amount() -> impl Strategy<Value = u64> {
+    prop_oneof![0..1_000u64, 1..1_000_000_000_000u64, any::<u64>()]
+}
+
+fn curve_input() -> impl Strategy<Value = CurveInput> {
+    (
+        amount(),
+        amount(),
+        prop_oneof![0..10_000u16, any::<u16>()],
+        amount(),
+        any::<bool>(),
+        amount(),
+        amount(),
+    )
+        .prop_map(|(x, y, fee, amount, is_x, supply, lp)| CurveInput {
+            x,
+            y,
+            fee,
+            amount,
+            is_x,
+            supply,
+            lp,
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2_000))]
+
+    #[test]
+    fn swap_never_decreases_k(input in curve_input()) {
+        check_swap(&input);
+    }
+
+    #[test]
+    fn deposit_then_withdraw_never_profits(input in curve_input()) {
+        check_deposit_withdraw(&input);
+    }
+
+    // supply 不小于 lp 的情况更有意义 单独生成一次
+    #[test]
+    fn withdraw_never_exceeds_reserves(input in curve_input(), lp in any::<u64>()) {
+        let lp = lp.checked_rem(input.supply).unwrap_or(0);
+        check_withdraw(&CurveInput { lp, ..input });
+        check_withdraw(&input);
+    }
+
+    // 模糊测试的字节格式和结构体要能互相转换 种子文件才能在两边通用
+    #[test]
+    fn input_bytes_roundtrip(input in curve_input()) {
+        let decoded = CurveInput::from_bytes(&input.to_bytes());
+        prop_assert_eq!(decoded.to_bytes(), input.to_bytes());
+        check_all(&decoded);
+    }
+
+    // 连续交换时每一步的 k 都不会变小
+    #[test]
+    fn swap_sequence_never_decreases_k(
+        x in 1..1_000_000_000u64,
+        y in 1..1_000_000_000u64,
+        fee in 0..10_000u16,
+        swaps in prop::collection::vec((any::<bool>(), 0..1_000_000_000u64), 1..32),
+    ) {
+        let mut curve = ConstantProduct::init(x, y, fee).unwrap();
+        for (is_x, amount) in swaps {
+            let (x, y) = curve.reserves();
+            let p = match is_x {
+                true => LiquidityPair::X,
+                false => LiquidityPair::Y,
+            };
+            curve.swap(p, amount, 0).unwrap();
+            let (new_x, new_y) = curve.reserves();
+            prop_assert!(new_x as u128 * new_y as u128 >= x as u128 * y as u128);
+        }
+    }
+}
+
+#[test]
+fn swap_overflow_returns_error() {
+    let mut curve = ConstantProduct::init(u64::MAX, u64::MAX, 0).unwrap();
+    assert_eq!(
+        curve.swap(LiquidityPair::X, u64::MAX, 0),
+        Err(CurveError::Overflow)
+    );
+    assert_eq!(
+        curve.swap(LiquidityPair::Y, 1, 0),
+        Err(CurveError::Overflow)
+    );
+    // 失败的交换不会修改储备
+    assert_eq!(curve.reserves(), (u64::MAX, u64::MAX));
+}
+
+#[test]
+fn deposit_overflow_returns_error() {
+    assert_eq!(
+        ConstantProduct::xy_deposit_amounts_from_l(u64::MAX, 1, 1, u64::MAX),
+        Err(CurveError::Overflow)
+    );
+    assert_eq!(
+        ConstantProduct::xy_deposit_amounts_from_l(1, 1, 0, 1),
+        Err(CurveError::DivisionByZero)
+    );
+}
+
+#[test]
+fn withdraw_overflow_returns_error() {
+    assert_eq!(
+        ConstantProduct::xy_withdraw_amounts_from_l(u64::MAX, 1, 1, u64::MAX),
+        Err(CurveError::Overflow)
+    );
+    let curve = ConstantProduct::new(u64::MAX, u64::MAX, 0).unwrap();
+    assert_eq!(curve.quote_withdraw(1, 2), Err(CurveError::InvalidAmount));
+    assert_eq!(
+        curve.quote_withdraw(u64::MAX, u64::MAX),
+        Ok((u64::MAX, u64::MAX))
+    );
+}
@@ -0,0 +1,22 @@
+// 回放 fuzz/corpus/curve 中的种子文件
+// 模糊测试发现的反例修复之后放进这个目录 cargo test 每次都会重新检查
+mod common;
+
+use common::{check_all, CurveInput};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn replay_fuzz_corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/curve");
+    let mut count = 0;
+    for entry in fs::read_dir(&dir).expect("missing fuzz/corpus/curve") {
+        let path = entry.unwrap().path();
+        let data = fs::read(&path).unwrap();
+        let input = CurveInput::from_bytes(&data);
+        let result = std::panic::catch_unwind(|| check_all(&input));
+        assert!(result.is_ok(), "seed {} failed: {input:?}", path.display());
+        count += 1;
+    }
+    assert!(count > 0, "no seeds in {}", dir.display());
+}
@@ -0,0 +1,11 @@
+// 链下回测工具 用链上程序同一份曲线计算回放历史交易
+// 用来比较不同费率和曲线下 LP 的收益
+
+pub mod pool;
+pub use pool::*;
+
+pub mod report;
+pub use report::*;
+
+pub mod trades;
+pub use trades::*;
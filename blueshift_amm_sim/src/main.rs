@@ -0,0 +1,65 @@
+use blueshift_amm_sim::{backtest, parse_trades, ConstantProductModel, Pool};
+
+const USAGE: &str = "\
+usage: blueshift_amm_sim <trades.csv> --x <u64> --y <u64> [--lp <u64>] [--fees <bps,bps,...>] [--curve constant-product]
+
+replays the trades against a pool seeded with x / y and prints one report per fee tier.
+csv columns: timestamp,action,amount,price  (action: swap_x | swap_y | deposit | withdraw)";
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("error: {error}\n{USAGE}");
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().ok_or("missing trades file")?;
+    let (mut x, mut y, mut lp, mut fees, mut curve) = (None, None, None, vec![30u16], None);
+    while let Some(key) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {key}"))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("invalid value for {key}"))
+        };
+        match key.as_str() {
+            "--x" => x = Some(number()?),
+            "--y" => y = Some(number()?),
+            "--lp" => lp = Some(number()?),
+            "--fees" => {
+                fees = value
+                    .split(',')
+                    .map(|fee| fee.trim().parse().map_err(|_| format!("invalid fee {fee}")))
+                    .collect::<Result<_, _>>()?
+            }
+            "--curve" => curve = Some(value),
+            _ => return Err(format!("unknown option {key}")),
+        }
+    }
+    let x = x.ok_or("missing --x")?;
+    let y = y.ok_or("missing --y")?;
+    // 默认按 √(x·y) 铸造初始 LP
+    let lp = lp.unwrap_or(((x as f64) * (y as f64)).sqrt() as u64).max(1);
+    // 目前只有恒定乘积曲线 新的曲线实现 CurveModel 之后在这里注册
+    match curve.as_deref().unwrap_or("constant-product") {
+        "constant-product" => {}
+        other => return Err(format!("unknown curve {other}")),
+    }
+
+    let csv = std::fs::read_to_string(&path).map_err(|error| format!("{path}: {error}"))?;
+    let trades = parse_trades(&csv)?;
+    for (i, fee) in fees.into_iter().enumerate() {
+        let pool =
+            Pool::new(ConstantProductModel, fee, x, y, lp).map_err(|error| error.to_string())?;
+        let report = backtest(pool, lp, &trades).map_err(|error| error.to_string())?;
+        if i > 0 {
+            println!();
+        }
+        println!("{report}");
+    }
+    Ok(())
+}
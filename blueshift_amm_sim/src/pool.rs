@@ -0,0 +1,133 @@
+use blueshift_amm_math::{ConstantProduct, Curve, CurveError, LiquidityPair};
+
+// 回测使用的曲线 每一步都用当前储备重新构建 和链上每笔交易读取 config 储备的方式一致
+pub trait CurveModel {
+    type Curve: Curve;
+
+    fn name(&self) -> &'static str;
+
+    fn build(&self, x: u64, y: u64, fee: u16) -> Result<Self::Curve, CurveError>;
+}
+
+pub struct ConstantProductModel;
+
+impl CurveModel for ConstantProductModel {
+    type Curve = ConstantProduct;
+
+    fn name(&self) -> &'static str {
+        "constant-product"
+    }
+
+    fn build(&self, x: u64, y: u64, fee: u16) -> Result<ConstantProduct, CurveError> {
+        ConstantProduct::new(x, y, fee)
+    }
+}
+
+// 池子的状态机 和链上的 Swap / Deposit / Withdraw 一样更新储备和 LP 供应量
+#[derive(Debug, Clone)]
+pub struct Pool<M: CurveModel> {
+    pub model: M,
+    pub fee: u16,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub supply: u64,
+}
+
+// 一笔交换的结果 手续费以支付的代币计价
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapFill {
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub fee: u64,
+    pub amount_out: u64,
+}
+
+impl<M: CurveModel> Pool<M> {
+    // 首次注入流动性 铸造 lp 个 LP
+    pub fn new(model: M, fee: u16, x: u64, y: u64, lp: u64) -> Result<Self, CurveError> {
+        let mut pool = Self {
+            model,
+            fee,
+            reserve_x: 0,
+            reserve_y: 0,
+            supply: 0,
+        };
+        pool.deposit(lp, x, y)?;
+        Ok(pool)
+    }
+
+    fn curve(&self) -> Result<M::Curve, CurveError> {
+        self.model.build(self.reserve_x, self.reserve_y, self.fee)
+    }
+
+    // 以 y 计价的 x 的价格
+    pub fn price(&self) -> f64 {
+        self.reserve_y as f64 / self.reserve_x as f64
+    }
+
+    pub fn swap(&mut self, is_x: bool, amount: u64) -> Result<SwapFill, CurveError> {
+        let p = match is_x {
+            true => LiquidityPair::X,
+            false => LiquidityPair::Y,
+        };
+        let (amount_in, fee, amount_out) = self.curve()?.quote_swap(p, amount, 0)?;
+        // 链上不允许支付或者拿到 0 个代币
+        if amount_in == 0 || amount_out == 0 {
+            return Err(CurveError::InvalidAmount);
+        }
+        let (reserve_x, reserve_y) = match is_x {
+            true => (
+                self.reserve_x.checked_add(amount_in),
+                self.reserve_y.checked_sub(amount_out),
+            ),
+            false => (
+                self.reserve_x.checked_sub(amount_out),
+                self.reserve_y.checked_add(amount_in),
+            ),
+        };
+        self.reserve_x = reserve_x.ok_or(CurveError::Overflow)?;
+        self.reserve_y = reserve_y.ok_or(CurveError::Overflow)?;
+        Ok(SwapFill {
+            is_x,
+            amount_in,
+            fee,
+            amount_out,
+        })
+    }
+
+    // 铸造 amount 个 LP 返回支付的 (x, y)
+    pub fn deposit(
+        &mut self,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+    ) -> Result<(u64, u64), CurveError> {
+        let (x, y) = self
+            .curve()?
+            .quote_deposit(self.supply, amount, max_x, max_y)?;
+        if x > max_x || y > max_y {
+            return Err(CurveError::SlippageExceeded);
+        }
+        self.reserve_x = self.reserve_x.checked_add(x).ok_or(CurveError::Overflow)?;
+        self.reserve_y = self.reserve_y.checked_add(y).ok_or(CurveError::Overflow)?;
+        self.supply = self
+            .supply
+            .checked_add(amount)
+            .ok_or(CurveError::Overflow)?;
+        Ok((x, y))
+    }
+
+    // 销毁 amount 个 LP 返回取回的 (x, y)
+    pub fn withdraw(&mut self, amount: u64) -> Result<(u64, u64), CurveError> {
+        let (x, y) = self.curve()?.quote_withdraw(self.supply, amount)?;
+        self.reserve_x -= x;
+        self.reserve_y -= y;
+        self.supply -= amount;
+        Ok((x, y))
+    }
+
+    // lp 个 LP 当前对应的 (x, y)
+    pub fn share(&self, lp: u64) -> Result<(u64, u64), CurveError> {
+        self.curve()?.quote_withdraw(self.supply, lp)
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{Action, CurveModel, Pool, Trade};
+use blueshift_amm_math::CurveError;
+use std::fmt;
+
+// 一次回测的结果 所有价值都以 y 计价
+// 只统计开始时注入流动性的那个 LP 其他 LP 的存取只影响它的份额
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub curve: &'static str,
+    pub fee: u16,
+    // 成功和失败的交易数量 失败的交易在链上同样会被拒绝 不影响池子
+    pub executed: usize,
+    pub failed: usize,
+    // 交易者支付的总量
+    pub volume_x: u64,
+    pub volume_y: u64,
+    // 按当时的份额分给这个 LP 的手续费
+    pub fees_x: f64,
+    pub fees_y: f64,
+    // 开始时存入的数量和结束时可以取回的数量
+    pub deposited: (u64, u64),
+    pub withdrawable: (u64, u64),
+    // 池子自己的开始和结束价格 所有估值都用这两个价格 和持仓的储备保持一致
+    pub start_price: f64,
+    pub end_price: f64,
+    // CSV 中最后一个市场价格 只用来对比池子价格偏离市场多少 不参与估值
+    pub market_price: Option<f64>,
+    // 结束时 LP 持仓的价值 如果一直持有存入的代币的价值
+    pub lp_value: f64,
+    pub hodl_value: f64,
+    // LP 相对开始时价值的收益率
+    pub lp_return: f64,
+    // LP 相对一直持有的收益率 包含手续费
+    pub lp_vs_hodl: f64,
+    // 只由价格变化导致的无常损失 2√r / (1 + r) - 1
+    pub impermanent_loss: f64,
+}
+
+// 用 lp 个 LP 开始 回放全部交易
+pub fn backtest<M: CurveModel>(
+    mut pool: Pool<M>,
+    lp: u64,
+    trades: &[Trade],
+) -> Result<Report, CurveError> {
+    let deposited = pool.share(lp)?;
+    let start_price = pool.price();
+    let mut market_price = None;
+    let (mut executed, mut failed) = (0, 0);
+    let (mut volume_x, mut volume_y) = (0u64, 0u64);
+    let (mut fees_x, mut fees_y) = (0f64, 0f64);
+
+    for trade in trades {
+        market_price = trade.price.or(market_price);
+        let result = match trade.action {
+            Action::SwapX | Action::SwapY => {
+                let share = lp as f64 / pool.supply as f64;
+                pool.swap(trade.action == Action::SwapX, trade.amount)
+                    .map(|fill| match fill.is_x {
+                        true => {
+                            volume_x = volume_x.saturating_add(fill.amount_in);
+                            fees_x += fill.fee as f64 * share;
+                        }
+                        false => {
+                            volume_y = volume_y.saturating_add(fill.amount_in);
+                            fees_y += fill.fee as f64 * share;
+                        }
+                    })
+            }
+            // 其他 LP 按当前比例存入 不限制最大数量
+            Action::Deposit => pool.deposit(trade.amount, u64::MAX, u64::MAX).map(|_| ()),
+            // 其他 LP 不能销毁这个 LP 的份额
+            Action::Withdraw => match trade.amount <= pool.supply - lp {
+                true => pool.withdraw(trade.amount).map(|_| ()),
+                false => Err(CurveError::InvalidAmount),
+            },
+        };
+        match result {
+            Ok(()) => executed += 1,
+            Err(_) => failed += 1,
+        }
+    }
+
+    // 没有套利时池子价格可能偏离市场价格 用市场价格给池子的持仓估值会把偏离算成盈亏
+    let end_price = pool.price();
+    let withdrawable = pool.share(lp)?;
+    let value = |(x, y): (u64, u64), price: f64| x as f64 * price + y as f64;
+    let lp_value = value(withdrawable, end_price);
+    let hodl_value = value(deposited, end_price);
+    let ratio = end_price / start_price;
+    Ok(Report {
+        curve: pool.model.name(),
+        fee: pool.fee,
+        executed,
+        failed,
+        volume_x,
+        volume_y,
+        fees_x,
+        fees_y,
+        deposited,
+        withdrawable,
+        start_price,
+        end_price,
+        market_price,
+        lp_value,
+        hodl_value,
+        lp_return: lp_value / value(deposited, start_price) - 1.0,
+        lp_vs_hodl: lp_value / hodl_value - 1.0,
+        impermanent_loss: 2.0 * ratio.sqrt() / (1.0 + ratio) - 1.0,
+    })
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "curve {} fee {} bps", self.curve, self.fee)?;
+        writeln!(
+            f,
+            "  trades           {} executed, {} failed",
+            self.executed, self.failed
+        )?;
+        writeln!(
+            f,
+            "  volume           x {} / y {}",
+            self.volume_x, self.volume_y
+        )?;
+        writeln!(
+            f,
+            "  fees earned      x {:.2} / y {:.2}",
+            self.fees_x, self.fees_y
+        )?;
+        writeln!(
+            f,
+            "  position         x {} y {} -> x {} y {}",
+            self.deposited.0, self.deposited.1, self.withdrawable.0, self.withdrawable.1
+        )?;
+        writeln!(
+            f,
+            "  price            {:.6} -> {:.6}",
+            self.start_price, self.end_price
+        )?;
+        if let Some(market_price) = self.market_price {
+            writeln!(f, "  market price     {market_price:.6}")?;
+        }
+        writeln!(
+            f,
+            "  lp value         {:.2} (hodl {:.2})",
+            self.lp_value, self.hodl_value
+        )?;
+        writeln!(f, "  lp return        {:+.4}%", self.lp_return * 100.0)?;
+        writeln!(f, "  lp vs hodl       {:+.4}%", self.lp_vs_hodl * 100.0)?;
+        write!(
+            f,
+            "  impermanent loss {:+.4}%",
+            self.impermanent_loss * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConstantProductModel;
+
+    fn pool() -> Pool<ConstantProductModel> {
+        Pool::new(
+            ConstantProductModel,
+            30,
+            1_000_000_000,
+            2_000_000_000,
+            1_000_000,
+        )
+        .unwrap()
+    }
+
+    fn trade(action: Action, amount: u64) -> Trade {
+        Trade {
+            timestamp: 0,
+            action,
+            amount,
+            price: None,
+        }
+    }
+
+    #[test]
+    fn no_trades_keeps_position() {
+        let report = backtest(pool(), 1_000_000, &[]).unwrap();
+        assert_eq!(report.withdrawable, report.deposited);
+        assert_eq!(report.start_price, 2.0);
+        assert_eq!(report.end_price, 2.0);
+        assert_eq!(report.impermanent_loss, 0.0);
+        assert_eq!(report.lp_vs_hodl, 0.0);
+    }
+
+    #[test]
+    fn values_position_at_pool_price() {
+        // 市场价格和池子价格完全无关 估值仍然只用池子的价格
+        let trades = [
+            Trade {
+                price: Some(100.0),
+                ..trade(Action::SwapX, 500_000_000)
+            },
+            trade(Action::SwapY, 100_000_000),
+        ];
+        let report = backtest(pool(), 1_000_000, &trades).unwrap();
+        assert_eq!((report.executed, report.failed), (2, 0));
+        assert_eq!(report.market_price, Some(100.0));
+        assert!(report.end_price < report.start_price);
+
+        let (x, y) = report.withdrawable;
+        assert_eq!(report.lp_value, x as f64 * report.end_price + y as f64);
+        // 恒定乘积的持仓按池子价格估值 两边价值相等
+        assert!((x as f64 * report.end_price - y as f64).abs() / (y as f64) < 1e-6);
+        // 价格变化带来无常损失 手续费让 LP 比无常损失少亏一些
+        assert!(report.impermanent_loss < 0.0);
+        assert!(report.lp_vs_hodl > report.impermanent_loss);
+        assert!(report.fees_x > 0.0 && report.fees_y > 0.0);
+    }
+
+    #[test]
+    fn other_lps_cannot_burn_tracked_share() {
+        let trades = [
+            trade(Action::Deposit, 500_000),
+            trade(Action::Withdraw, 600_000),
+            trade(Action::Withdraw, 500_000),
+        ];
+        let report = backtest(pool(), 1_000_000, &trades).unwrap();
+        assert_eq!((report.executed, report.failed), (2, 1));
+        assert_eq!(report.withdrawable, report.deposited);
+    }
+}
@@ -0,0 +1,132 @@
+use std::str::FromStr;
+
+// CSV 中的一行
+// timestamp,action,amount,price
+// action: swap_x（支付 amount 个 x） swap_y（支付 amount 个 y） deposit / withdraw（其他 LP 铸造或销毁 amount 个 LP）
+// price: 可选 当时市场上以 y 计价的 x 的价格 只在报告中和池子价格对比 估值始终使用池子的价格
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trade {
+    pub timestamp: i64,
+    pub action: Action,
+    pub amount: u64,
+    pub price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    SwapX,
+    SwapY,
+    Deposit,
+    Withdraw,
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        match action {
+            "swap_x" => Ok(Action::SwapX),
+            "swap_y" => Ok(Action::SwapY),
+            "deposit" => Ok(Action::Deposit),
+            "withdraw" => Ok(Action::Withdraw),
+            _ => Err(format!("unknown action {action}")),
+        }
+    }
+}
+
+// 解析整个 CSV 空行、# 开头的注释和表头会被跳过
+pub fn parse_trades(csv: &str) -> Result<Vec<Trade>, String> {
+    let mut trades = Vec::new();
+    for (number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("timestamp") {
+            continue;
+        }
+        let trade = parse_line(line).map_err(|error| format!("line {}: {error}", number + 1))?;
+        trades.push(trade);
+    }
+    Ok(trades)
+}
+
+fn parse_line(line: &str) -> Result<Trade, String> {
+    let mut columns = line.split(',').map(str::trim);
+    let mut next = |name: &str| columns.next().ok_or_else(|| format!("missing {name}"));
+    let timestamp = next("timestamp")?
+        .parse()
+        .map_err(|_| "invalid timestamp".to_string())?;
+    let action = next("action")?.parse()?;
+    let amount = next("amount")?
+        .parse()
+        .map_err(|_| "invalid amount".to_string())?;
+    let price = match next("price").unwrap_or("") {
+        "" => None,
+        price => Some(price.parse().map_err(|_| "invalid price".to_string())?),
+    };
+    Ok(Trade {
+        timestamp,
+        action,
+        amount,
+        price,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_trades() {
+        let csv = "\
+# comment
+timestamp,action,amount,price
+
+1,swap_x,100,2.5
+2, swap_y , 200 ,
+3,deposit,300
+4,withdraw,400,1.0
+";
+        let trades = parse_trades(csv).unwrap();
+        assert_eq!(
+            trades,
+            vec![
+                Trade {
+                    timestamp: 1,
+                    action: Action::SwapX,
+                    amount: 100,
+                    price: Some(2.5),
+                },
+                Trade {
+                    timestamp: 2,
+                    action: Action::SwapY,
+                    amount: 200,
+                    price: None,
+                },
+                Trade {
+                    timestamp: 3,
+                    action: Action::Deposit,
+                    amount: 300,
+                    price: None,
+                },
+                Trade {
+                    timestamp: 4,
+                    action: Action::Withdraw,
+                    amount: 400,
+                    price: Some(1.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_line_of_invalid_trade() {
+        let error = |csv: &str| parse_trades(csv).unwrap_err();
+        assert_eq!(
+            error("1,swap_x,100\n2,buy,100"),
+            "line 2: unknown action buy"
+        );
+        assert_eq!(error("x,swap_x,100"), "line 1: invalid timestamp");
+        assert_eq!(error("1,swap_x,-1"), "line 1: invalid amount");
+        assert_eq!(error("1,swap_x,1,abc"), "line 1: invalid price");
+        assert_eq!(error("1,swap_x"), "line 1: missing amount");
+    }
+}
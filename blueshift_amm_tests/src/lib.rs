@@ -0,0 +1,350 @@
+use blueshift_amm_client::{
+    find_associated_token_address, find_settings_address, Config, Initialize,
+    Instruction as ClientInstruction, LpPosition, Pool, Settings, MAX_FEE_TIERS, PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+};
+use mollusk_svm::{program, Mollusk};
+use mollusk_svm_programs_token::{associated_token, token};
+use solana_account::Account;
+use solana_address::Address;
+use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// blueshift_native_amm 的进程内测试工具
+// 用 Mollusk 加载 cargo build-sbf 编译出来的程序和 SPL Token / ATA 程序 不需要启动验证节点
+// 账户保存在 Svm 中 指令成功之后才写回 和链上失败的交易不修改状态的行为一致
+
+pub const PROGRAM_NAME: &str = "blueshift_native_amm";
+
+// 系统程序的地址是全 0
+const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::new_from_array([0; 32]);
+
+// 编译产物所在的目录 SBF_OUT_DIR 中没有这个程序时使用 cargo build-sbf 的默认输出目录
+pub fn program_dir(program_name: &str) -> PathBuf {
+    std::env::var_os("SBF_OUT_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.join(format!("{program_name}.so")).exists())
+        .unwrap_or_else(|| {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("..")
+                .join(program_name)
+                .join("target/deploy")
+        })
+}
+
+// 程序的编译产物 不存在时返回 None
+pub fn program_path(program_name: &str) -> Option<PathBuf> {
+    let path = program_dir(program_name).join(format!("{program_name}.so"));
+    path.exists().then_some(path)
+}
+
+// 客户端使用的 solana-address 和 Mollusk 使用的 Pubkey 版本不同 通过字节转换
+pub fn pubkey(address: &Address) -> Pubkey {
+    Pubkey::new_from_array(address.as_ref().try_into().unwrap())
+}
+
+pub fn address(pubkey: &Pubkey) -> Address {
+    Address::from(pubkey.to_bytes())
+}
+
+// 每次返回一个不同的地址 测试中的钱包和铸币不需要私钥
+pub fn new_address() -> Address {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    let mut bytes = [0u8; 32];
+    bytes[..8].copy_from_slice(&NEXT.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    Address::from(bytes)
+}
+
+pub fn instruction(instruction: &ClientInstruction) -> Instruction {
+    Instruction {
+        program_id: pubkey(&instruction.program_id),
+        accounts: instruction
+            .accounts
+            .iter()
+            .map(|meta| AccountMeta {
+                pubkey: pubkey(&meta.pubkey),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: instruction.data.clone(),
+    }
+}
+
+// SPL Token 的 Mint 布局 82 字节
+pub fn pack_mint(authority: &Address, supply: u64, decimals: u8) -> Vec<u8> {
+    let mut data = Vec::with_capacity(82);
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(&supply.to_le_bytes());
+    data.push(decimals);
+    data.push(1);
+    data.extend_from_slice(&[0u8; 36]);
+    data
+}
+
+// AMM 全局设置的布局 58 字节 没用到的费率档位填 0
+pub fn pack_settings(admin: &Address, creation_fee: u64, fee_tiers: &[u16], bump: u8) -> Vec<u8> {
+    let mut data = Vec::with_capacity(Settings::LEN);
+    data.extend_from_slice(admin.as_ref());
+    data.extend_from_slice(&creation_fee.to_le_bytes());
+    data.push(fee_tiers.len() as u8);
+    for index in 0..MAX_FEE_TIERS {
+        let tier = fee_tiers.get(index).copied().unwrap_or_default();
+        data.extend_from_slice(&tier.to_le_bytes());
+    }
+    data.push(bump);
+    data
+}
+
+// BPF Upgradeable Loader 的 ProgramData 账户头部 45 字节
+// [3u32, 部署的 slot u64, Some(1u8), 升级权限持有者]
+pub fn pack_program_data(upgrade_authority: &Address) -> Vec<u8> {
+    let mut data = Vec::with_capacity(45);
+    data.extend_from_slice(&3u32.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(upgrade_authority.as_ref());
+    data
+}
+
+// Pyth v2 价格账户 只填 blueshift_native_amm::OraclePrice 读取的字段 处于 Trading 状态
+pub fn pack_oracle_price(price: i64, conf: u64, expo: i32, timestamp: i64) -> Vec<u8> {
+    let mut data = vec![0u8; 240];
+    data[0..4].copy_from_slice(&0xa1b2c3d4u32.to_le_bytes());
+    data[4..8].copy_from_slice(&2u32.to_le_bytes());
+    data[8..12].copy_from_slice(&3u32.to_le_bytes());
+    data[20..24].copy_from_slice(&expo.to_le_bytes());
+    data[96..104].copy_from_slice(&timestamp.to_le_bytes());
+    data[208..216].copy_from_slice(&price.to_le_bytes());
+    data[216..224].copy_from_slice(&conf.to_le_bytes());
+    data[224..228].copy_from_slice(&1u32.to_le_bytes());
+    data
+}
+
+// SPL Token 的 TokenAccount 布局 165 字节 没有委托和关闭权限的已初始化账户
+pub fn pack_token_account(mint: &Address, owner: &Address, amount: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(165);
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(owner.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&[0u8; 36]);
+    data.push(1);
+    data.extend_from_slice(&[0u8; 12]);
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.extend_from_slice(&[0u8; 36]);
+    data
+}
+
+pub struct Svm {
+    pub mollusk: Mollusk,
+    pub accounts: HashMap<Pubkey, Account>,
+    // 最近一条指令消耗的计算单元
+    pub compute_units: u64,
+}
+
+impl Svm {
+    // 没有编译产物时直接 panic 不能让测试在没有执行任何指令的情况下通过
+    pub fn load() -> Self {
+        Self::load_program(PROGRAM_NAME, &PROGRAM_ID).unwrap_or_else(|| {
+            panic!(
+                "{PROGRAM_NAME}.so not found in {}, run cargo build-sbf in ../{PROGRAM_NAME} first",
+                program_dir(PROGRAM_NAME).display()
+            )
+        })
+    }
+
+    // 加载 ../<program_name> 编译出来的任意程序 vault 和 escrow 的基准测试也使用这里
+    // 没有编译产物时返回 None 由调用方决定如何报错
+    pub fn load_program(program_name: &str, program_id: &Address) -> Option<Self> {
+        let path = program_path(program_name)?;
+        std::env::set_var("SBF_OUT_DIR", path.parent().unwrap());
+        let mut mollusk = Mollusk::new(&pubkey(program_id), program_name);
+        token::add_program(&mut mollusk);
+        associated_token::add_program(&mut mollusk);
+
+        let accounts = HashMap::from([
+            program::keyed_account_for_system_program(),
+            token::keyed_account(),
+            associated_token::keyed_account(),
+        ]);
+        Some(Self {
+            mollusk,
+            accounts,
+            compute_units: 0,
+        })
+    }
+
+    // 执行一条指令 成功时把修改后的账户写回
+    pub fn process(
+        &mut self,
+        client_instruction: &ClientInstruction,
+    ) -> Result<(), InstructionError> {
+        let instruction = instruction(client_instruction);
+        let accounts: Vec<(Pubkey, Account)> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let account = self.accounts.get(&meta.pubkey).cloned().unwrap_or_default();
+                (meta.pubkey, account)
+            })
+            .collect();
+        let result = self.mollusk.process_instruction(&instruction, &accounts);
+        self.compute_units = result.compute_units_consumed;
+        if result.raw_result.is_ok() {
+            self.accounts.extend(result.resulting_accounts);
+        }
+        result.raw_result
+    }
+
+    pub fn set_clock(&mut self, unix_timestamp: i64, slot: u64) {
+        self.mollusk.warp_to_slot(slot);
+        self.mollusk.sysvars.clock.unix_timestamp = unix_timestamp;
+    }
+
+    pub fn account(&self, address: &Address) -> Option<&Account> {
+        self.accounts.get(&pubkey(address))
+    }
+
+    pub fn set_account(&mut self, address: &Address, owner: &Address, data: Vec<u8>) {
+        let lamports = self.mollusk.sysvars.rent.minimum_balance(data.len());
+        self.accounts.insert(
+            pubkey(address),
+            Account {
+                lamports,
+                data,
+                owner: pubkey(owner),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    pub fn fund(&mut self, address: &Address, lamports: u64) {
+        self.accounts.insert(
+            pubkey(address),
+            Account::new(lamports, 0, &SYSTEM_PROGRAM_ID),
+        );
+    }
+
+    pub fn create_mint(&mut self, address: &Address, authority: &Address, decimals: u8) {
+        self.set_account(
+            address,
+            &TOKEN_PROGRAM_ID,
+            pack_mint(authority, 0, decimals),
+        );
+    }
+
+    pub fn create_token_account(
+        &mut self,
+        address: &Address,
+        mint: &Address,
+        owner: &Address,
+        amount: u64,
+    ) {
+        let data = pack_token_account(mint, owner, amount);
+        self.set_account(address, &TOKEN_PROGRAM_ID, data);
+    }
+
+    // TokenAccount 的 amount 字段在 64..72
+    pub fn token_amount(&self, address: &Address) -> u64 {
+        let account = self.account(address).expect("missing token account");
+        u64::from_le_bytes(account.data[64..72].try_into().unwrap())
+    }
+
+    // Mint 的 supply 字段在 36..44
+    pub fn mint_supply(&self, address: &Address) -> u64 {
+        let account = self.account(address).expect("missing mint");
+        u64::from_le_bytes(account.data[36..44].try_into().unwrap())
+    }
+
+    pub fn config(&self, address: &Address) -> Config {
+        let account = self.account(address).expect("missing config");
+        Config::from_bytes(&account.data).expect("invalid config")
+    }
+
+    pub fn settings(&self) -> Settings {
+        let account = self
+            .account(&find_settings_address().0)
+            .expect("missing settings");
+        Settings::from_bytes(&account.data).expect("invalid settings")
+    }
+
+    pub fn lamports(&self, address: &Address) -> u64 {
+        self.account(address).map_or(0, |account| account.lamports)
+    }
+
+    pub fn position(&self, address: &Address) -> LpPosition {
+        let account = self.account(address).expect("missing position");
+        LpPosition::from_bytes(&account.data).expect("invalid position")
+    }
+}
+
+// 一个已经初始化的池子 用户持有足够的 x y 代币 金库和 LP 代币账户都已经创建
+pub struct PoolFixture {
+    pub svm: Svm,
+    pub admin: Address,
+    pub user: Address,
+    pub pool: Pool,
+}
+
+impl PoolFixture {
+    pub const SEED: u64 = 42;
+    pub const USER_BALANCE: u64 = 1_000_000_000_000;
+    // 全局设置 admin 同时也是协议管理员
+    pub const FEE_TIERS: [u16; 4] = [1, 5, 30, 100];
+    pub const CREATION_FEE: u64 = 100_000_000;
+
+    pub fn new(mut svm: Svm, fee: u16) -> Self {
+        let admin = new_address();
+        let user = new_address();
+        let mint_x = new_address();
+        let mint_y = new_address();
+        svm.fund(&admin, 10_000_000_000);
+        svm.fund(&user, 10_000_000_000);
+        svm.create_mint(&mint_x, &admin, 6);
+        svm.create_mint(&mint_y, &admin, 6);
+
+        let pool = Pool::new(Self::SEED, mint_x.clone(), mint_y.clone());
+        // 金库是 config 的 ATA 由调用方在 Initialize 之前创建
+        svm.create_token_account(&pool.vault_x, &mint_x, &pool.config, 0);
+        svm.create_token_account(&pool.vault_y, &mint_y, &pool.config, 0);
+        for mint in [&mint_x, &mint_y] {
+            let ata = find_associated_token_address(&user, mint);
+            svm.create_token_account(&ata, mint, &user, Self::USER_BALANCE);
+        }
+
+        // CreateSettings 需要程序的 ProgramData 账户 这里直接写入设置账户
+        let (settings, bump) = find_settings_address();
+        let data = pack_settings(&admin, Self::CREATION_FEE, &Self::FEE_TIERS, bump);
+        svm.set_account(&settings, &PROGRAM_ID, data);
+
+        let initialize = Initialize {
+            initializer: admin.clone(),
+            seed: Self::SEED,
+            fee,
+            mint_x,
+            mint_y,
+            authority: Some(admin.clone()),
+        };
+        svm.process(&initialize.instruction().unwrap())
+            .expect("initialize failed");
+        // LP 铸币在 Initialize 中创建 用户的 LP 代币账户只能在这之后创建
+        let user_lp = find_associated_token_address(&user, &pool.mint_lp);
+        svm.create_token_account(&user_lp, &pool.mint_lp, &user, 0);
+
+        Self {
+            svm,
+            admin,
+            user,
+            pool,
+        }
+    }
+
+    pub fn user_ata(&self, mint: &Address) -> Address {
+        find_associated_token_address(&self.user, mint)
+    }
+}
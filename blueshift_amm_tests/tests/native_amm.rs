@@ -0,0 +1,575 @@
+// 在 Mollusk 中执行 Initialize → Deposit → Swap → Withdraw 的完整流程和常见的失败场景
+// 需要先在 ../blueshift_native_amm 中运行 cargo build-sbf 找不到编译产物时测试失败 见 Svm::load
+use blueshift_amm_client::{
+    find_associated_token_address, find_position_address, find_settings_address, AccountMeta,
+    AmmState, CollectCreationFees, Deposit, Initialize, Instruction, OpenPosition, Swap,
+    UpdateSettings, Withdraw, PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use blueshift_amm_math::{ConstantProduct, Curve, LiquidityPair};
+use blueshift_amm_tests::{new_address, PoolFixture, Svm};
+use solana_address::Address;
+use solana_instruction::error::InstructionError;
+
+const FEE: u16 = 30;
+const NO_EXPIRATION: i64 = i64::MAX;
+const LP_AMOUNT: u64 = 1_000_000;
+const RESERVE_X: u64 = 1_000_000_000;
+const RESERVE_Y: u64 = 2_000_000_000;
+
+fn setup() -> PoolFixture {
+    PoolFixture::new(Svm::load(), FEE)
+}
+
+// 首次注入流动性之后的池子
+fn setup_with_liquidity() -> PoolFixture {
+    let mut fixture = setup();
+    let deposit = deposit(&fixture, LP_AMOUNT, RESERVE_X, RESERVE_Y);
+    fixture.svm.process(&deposit).expect("deposit failed");
+    fixture
+}
+
+fn deposit(fixture: &PoolFixture, amount: u64, max_x: u64, max_y: u64) -> Instruction {
+    Deposit {
+        user: fixture.user.clone(),
+        pool: fixture.pool.clone(),
+        amount,
+        max_x,
+        max_y,
+        expiration: NO_EXPIRATION,
+        ..Default::default()
+    }
+    .instruction()
+    .unwrap()
+}
+
+fn swap(fixture: &PoolFixture, is_x: bool, amount: u64, min: u64) -> Swap {
+    Swap {
+        user: fixture.user.clone(),
+        pool: fixture.pool.clone(),
+        is_x,
+        amount,
+        min,
+        expiration: NO_EXPIRATION,
+        ..Default::default()
+    }
+}
+
+fn withdraw(fixture: &PoolFixture, amount: u64, min_x: u64, min_y: u64) -> Withdraw {
+    Withdraw {
+        user: fixture.user.clone(),
+        pool: fixture.pool.clone(),
+        amount,
+        min_x,
+        min_y,
+        expiration: NO_EXPIRATION,
+        ..Default::default()
+    }
+}
+
+// 客户端没有 SetState 的构建器 按照 [authority, config] 和 [5, state] 拼装
+fn set_state(fixture: &PoolFixture, state: AmmState) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(fixture.admin.clone(), true),
+            AccountMeta::new(fixture.pool.config.clone(), false),
+        ],
+        data: vec![5, state as u8],
+    }
+}
+
+// 客户端没有 ClosePool 的构建器 按照程序的账户顺序和 [12] 拼装 剩余的代币转入 closer 的 ATA
+fn close_pool(fixture: &PoolFixture, closer: &Address) -> Instruction {
+    let pool = &fixture.pool;
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(closer.clone(), true),
+            AccountMeta::new(pool.config.clone(), false),
+            AccountMeta::new_readonly(pool.mint_lp.clone(), false),
+            AccountMeta::new(pool.vault_x.clone(), false),
+            AccountMeta::new(pool.vault_y.clone(), false),
+            AccountMeta::new(find_associated_token_address(closer, &pool.mint_x), false),
+            AccountMeta::new(find_associated_token_address(closer, &pool.mint_y), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+        data: vec![12],
+    }
+}
+
+#[test]
+fn initialize_creates_pool() {
+    let fixture = setup();
+    let config = fixture.svm.config(&fixture.pool.config);
+    assert_eq!(config.state, AmmState::Initialized);
+    assert_eq!(config.fee, FEE);
+    assert_eq!(config.authority, fixture.admin);
+    assert_eq!(config.mint_lp, fixture.pool.mint_lp);
+    assert_eq!(config.vault_x, fixture.pool.vault_x);
+    assert_eq!(config.vault_y, fixture.pool.vault_y);
+    assert_eq!((config.reserve_x, config.reserve_y), (0, 0));
+    assert_eq!(fixture.svm.mint_supply(&fixture.pool.mint_lp), 0);
+}
+
+#[test]
+fn initialize_twice_fails() {
+    let mut fixture = setup();
+    let initialize = Initialize {
+        initializer: fixture.admin.clone(),
+        seed: PoolFixture::SEED,
+        fee: FEE,
+        mint_x: fixture.pool.mint_x.clone(),
+        mint_y: fixture.pool.mint_y.clone(),
+        authority: None,
+    };
+    assert!(fixture
+        .svm
+        .process(&initialize.instruction().unwrap())
+        .is_err());
+}
+
+#[test]
+fn deposit_swap_withdraw() {
+    let mut fixture = setup_with_liquidity();
+    let pool = fixture.pool.clone();
+    let user_x = fixture.user_ata(&pool.mint_x);
+    let user_y = fixture.user_ata(&pool.mint_y);
+    let user_lp = fixture.user_ata(&pool.mint_lp);
+
+    // 首次存入由用户定下比例 全部的 max_x max_y 都进入金库
+    assert_eq!(fixture.svm.token_amount(&pool.vault_x), RESERVE_X);
+    assert_eq!(fixture.svm.token_amount(&pool.vault_y), RESERVE_Y);
+    assert_eq!(fixture.svm.token_amount(&user_lp), LP_AMOUNT);
+    assert_eq!(fixture.svm.mint_supply(&pool.mint_lp), LP_AMOUNT);
+    let config = fixture.svm.config(&pool.config);
+    assert_eq!((config.reserve_x, config.reserve_y), (RESERVE_X, RESERVE_Y));
+
+    // 按比例再存入一半
+    let deposit = deposit(&fixture, LP_AMOUNT / 2, RESERVE_X, RESERVE_Y);
+    fixture.svm.process(&deposit).unwrap();
+    let (reserve_x, reserve_y) = (RESERVE_X * 3 / 2, RESERVE_Y * 3 / 2);
+    let config = fixture.svm.config(&pool.config);
+    assert_eq!((config.reserve_x, config.reserve_y), (reserve_x, reserve_y));
+
+    // 用 x 换 y 成交数量和曲线库的报价一致
+    let amount = 10_000_000;
+    let (_, _, out) = ConstantProduct::init(reserve_x, reserve_y, FEE)
+        .unwrap()
+        .quote_swap(LiquidityPair::X, amount, 0)
+        .unwrap();
+    let before_y = fixture.svm.token_amount(&user_y);
+    let swap = swap(&fixture, true, amount, out).instruction().unwrap();
+    fixture.svm.process(&swap).unwrap();
+    assert_eq!(fixture.svm.token_amount(&user_y), before_y + out);
+    let config = fixture.svm.config(&pool.config);
+    assert_eq!(
+        (config.reserve_x, config.reserve_y),
+        (reserve_x + amount, reserve_y - out)
+    );
+    assert_eq!(fixture.svm.token_amount(&pool.vault_x), config.reserve_x);
+    assert_eq!(fixture.svm.token_amount(&pool.vault_y), config.reserve_y);
+
+    // 销毁全部 LP 取回全部储备
+    let supply = fixture.svm.mint_supply(&pool.mint_lp);
+    let before_x = fixture.svm.token_amount(&user_x);
+    let before_y = fixture.svm.token_amount(&user_y);
+    let withdraw = withdraw(&fixture, supply, 1, 1).instruction().unwrap();
+    fixture.svm.process(&withdraw).unwrap();
+    assert_eq!(fixture.svm.token_amount(&user_lp), 0);
+    assert_eq!(fixture.svm.mint_supply(&pool.mint_lp), 0);
+    assert_eq!(fixture.svm.token_amount(&pool.vault_x), 0);
+    assert_eq!(fixture.svm.token_amount(&pool.vault_y), 0);
+    assert_eq!(
+        fixture.svm.token_amount(&user_x),
+        before_x + config.reserve_x
+    );
+    assert_eq!(
+        fixture.svm.token_amount(&user_y),
+        before_y + config.reserve_y
+    );
+}
+
+#[test]
+fn wrong_vault_fails() {
+    let mut fixture = setup_with_liquidity();
+    // 换成一个同样铸币、同样由 config 持有 但不是 ATA 的代币账户
+    let fake_vault = new_address();
+    let (mint_x, config) = (fixture.pool.mint_x.clone(), fixture.pool.config.clone());
+    fixture
+        .svm
+        .create_token_account(&fake_vault, &mint_x, &config, 0);
+    let mut swap = swap(&fixture, true, 1_000, 1);
+    swap.pool.vault_x = fake_vault.clone();
+    assert_eq!(
+        fixture.svm.process(&swap.instruction().unwrap()),
+        Err(InstructionError::InvalidAccountData)
+    );
+
+    let mut withdraw = withdraw(&fixture, 1_000, 1, 1);
+    withdraw.pool.vault_x = fake_vault;
+    assert_eq!(
+        fixture.svm.process(&withdraw.instruction().unwrap()),
+        Err(InstructionError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn disabled_pool_rejects_everything() {
+    let mut fixture = setup_with_liquidity();
+    let disable = set_state(&fixture, AmmState::Disabled);
+    fixture.svm.process(&disable).unwrap();
+    assert_eq!(
+        fixture.svm.config(&fixture.pool.config).state,
+        AmmState::Disabled
+    );
+
+    let swap = swap(&fixture, true, 1_000, 1).instruction().unwrap();
+    assert_eq!(
+        fixture.svm.process(&swap),
+        Err(InstructionError::InvalidArgument)
+    );
+    let deposit = deposit(&fixture, 1_000, RESERVE_X, RESERVE_Y);
+    assert_eq!(
+        fixture.svm.process(&deposit),
+        Err(InstructionError::InvalidArgument)
+    );
+    let withdraw = withdraw(&fixture, 1_000, 1, 1).instruction().unwrap();
+    assert_eq!(
+        fixture.svm.process(&withdraw),
+        Err(InstructionError::InvalidArgument)
+    );
+}
+
+#[test]
+fn withdraw_only_pool_allows_withdraw() {
+    let mut fixture = setup_with_liquidity();
+    let withdraw_only = set_state(&fixture, AmmState::WithdrawOnly);
+    fixture.svm.process(&withdraw_only).unwrap();
+
+    let swap = swap(&fixture, false, 1_000, 1).instruction().unwrap();
+    assert_eq!(
+        fixture.svm.process(&swap),
+        Err(InstructionError::InvalidArgument)
+    );
+    let withdraw = withdraw(&fixture, LP_AMOUNT, 1, 1).instruction().unwrap();
+    fixture.svm.process(&withdraw).unwrap();
+}
+
+#[test]
+fn set_state_requires_authority() {
+    let mut fixture = setup_with_liquidity();
+    let mut disable = set_state(&fixture, AmmState::Disabled);
+    disable.accounts[0].pubkey = fixture.user.clone();
+    assert_eq!(
+        fixture.svm.process(&disable),
+        Err(InstructionError::IncorrectAuthority)
+    );
+}
+
+#[test]
+fn expired_deadline_fails() {
+    let mut fixture = setup_with_liquidity();
+    fixture.svm.set_clock(1_000, 500);
+
+    let mut swap = swap(&fixture, true, 1_000, 1);
+    swap.expiration = 999;
+    assert_eq!(
+        fixture.svm.process(&swap.instruction().unwrap()),
+        Err(InstructionError::InvalidInstructionData)
+    );
+    // 时间戳没有过期 但是 slot 过期了
+    swap.expiration = NO_EXPIRATION;
+    swap.expiration_slot = 499;
+    assert_eq!(
+        fixture.svm.process(&swap.instruction().unwrap()),
+        Err(InstructionError::InvalidInstructionData)
+    );
+    swap.expiration_slot = 500;
+    fixture.svm.process(&swap.instruction().unwrap()).unwrap();
+
+    let mut withdraw = withdraw(&fixture, 1_000, 1, 1);
+    withdraw.expiration = 999;
+    assert_eq!(
+        fixture.svm.process(&withdraw.instruction().unwrap()),
+        Err(InstructionError::InvalidInstructionData)
+    );
+}
+
+#[test]
+fn slippage_fails() {
+    let mut fixture = setup_with_liquidity();
+    let amount = 10_000_000;
+    let (_, _, out) = ConstantProduct::init(RESERVE_X, RESERVE_Y, FEE)
+        .unwrap()
+        .quote_swap(LiquidityPair::X, amount, 0)
+        .unwrap();
+    let swap = swap(&fixture, true, amount, out + 1).instruction().unwrap();
+    assert_eq!(
+        fixture.svm.process(&swap),
+        Err(InstructionError::InvalidArgument)
+    );
+
+    // 存入 LP_AMOUNT 个 LP 需要正好 RESERVE_X 个 x
+    let deposit = deposit(&fixture, LP_AMOUNT, RESERVE_X - 1, RESERVE_Y);
+    assert_eq!(
+        fixture.svm.process(&deposit),
+        Err(InstructionError::InvalidArgument)
+    );
+
+    // 销毁一半 LP 只能取回一半的储备
+    let withdraw = withdraw(&fixture, LP_AMOUNT / 2, RESERVE_X / 2 + 1, 1)
+        .instruction()
+        .unwrap();
+    assert_eq!(
+        fixture.svm.process(&withdraw),
+        Err(InstructionError::InvalidArgument)
+    );
+
+    // 失败的指令不会修改池子
+    let config = fixture.svm.config(&fixture.pool.config);
+    assert_eq!((config.reserve_x, config.reserve_y), (RESERVE_X, RESERVE_Y));
+}
+
+#[test]
+fn position_tracks_cost_basis() {
+    let mut fixture = setup();
+    let (position, _) = find_position_address(&fixture.pool.config, &fixture.user);
+    fixture.svm.set_clock(1_000, 10);
+    let open = OpenPosition {
+        user: fixture.user.clone(),
+        pool: fixture.pool.clone(),
+    }
+    .instruction()
+    .unwrap();
+    fixture.svm.process(&open).unwrap();
+    // 同一个用户不能重复创建
+    assert!(fixture.svm.process(&open).is_err());
+
+    // 仓位记录跟在 TWAMM 和白名单记录后面 这个池子两个都没有
+    let mut deposit = Deposit {
+        user: fixture.user.clone(),
+        pool: fixture.pool.clone(),
+        amount: LP_AMOUNT,
+        max_x: RESERVE_X,
+        max_y: RESERVE_Y,
+        expiration: NO_EXPIRATION,
+        ..Default::default()
+    };
+    deposit.remaining_accounts = vec![AccountMeta::new(position.clone(), false)];
+    fixture
+        .svm
+        .process(&deposit.instruction().unwrap())
+        .unwrap();
+    let record = fixture.svm.position(&position);
+    assert_eq!(record.owner, fixture.user);
+    assert_eq!(
+        (record.deposited_x, record.deposited_y),
+        (RESERVE_X, RESERVE_Y)
+    );
+    assert_eq!((record.cost_x, record.cost_y), (RESERVE_X, RESERVE_Y));
+    assert_eq!(record.lp_minted, LP_AMOUNT);
+    assert_eq!((record.opened_at, record.last_deposit_at), (1_000, 1_000));
+
+    // 销毁一半 LP 成本也扣除一半 没有交易发生 已实现盈亏为 0
+    fixture.svm.set_clock(2_000, 20);
+    let mut burn = withdraw(&fixture, LP_AMOUNT / 2, 1, 1);
+    burn.remaining_accounts = vec![AccountMeta::new(position.clone(), false)];
+    fixture.svm.process(&burn.instruction().unwrap()).unwrap();
+    let record = fixture.svm.position(&position);
+    assert_eq!(record.lp_held(), LP_AMOUNT / 2);
+    assert_eq!(
+        (record.withdrawn_x, record.withdrawn_y),
+        (RESERVE_X / 2, RESERVE_Y / 2)
+    );
+    assert_eq!(
+        (record.cost_x, record.cost_y),
+        (RESERVE_X / 2, RESERVE_Y / 2)
+    );
+    assert_eq!(record.withdrawn_x, record.deposited_x - record.cost_x);
+    assert_eq!(record.withdrawn_y, record.deposited_y - record.cost_y);
+    assert_eq!(record.last_withdraw_at, 2_000);
+
+    // 不带仓位记录的操作不会修改记录
+    let burn = withdraw(&fixture, LP_AMOUNT / 4, 1, 1)
+        .instruction()
+        .unwrap();
+    fixture.svm.process(&burn).unwrap();
+    assert_eq!(fixture.svm.position(&position), record);
+}
+
+#[test]
+fn position_ignores_lp_from_elsewhere() {
+    // 创建记录之前铸造的 LP 和从别处转入的 LP 一样 不在记录中
+    let mut fixture = setup_with_liquidity();
+    let (position, _) = find_position_address(&fixture.pool.config, &fixture.user);
+    let open = OpenPosition {
+        user: fixture.user.clone(),
+        pool: fixture.pool.clone(),
+    }
+    .instruction()
+    .unwrap();
+    fixture.svm.process(&open).unwrap();
+    let tracked = |fixture: &PoolFixture| {
+        let mut deposit = Deposit {
+            user: fixture.user.clone(),
+            pool: fixture.pool.clone(),
+            amount: LP_AMOUNT,
+            max_x: RESERVE_X,
+            max_y: RESERVE_Y,
+            expiration: NO_EXPIRATION,
+            ..Default::default()
+        };
+        deposit.remaining_accounts = vec![AccountMeta::new(position.clone(), false)];
+        deposit.instruction().unwrap()
+    };
+    let instruction = tracked(&fixture);
+    fixture.svm.process(&instruction).unwrap();
+
+    // 一次销毁全部 LP 只有记录中的一半计入记录
+    let mut burn = withdraw(&fixture, 2 * LP_AMOUNT, 1, 1);
+    burn.remaining_accounts = vec![AccountMeta::new(position.clone(), false)];
+    fixture.svm.process(&burn.instruction().unwrap()).unwrap();
+    let record = fixture.svm.position(&position);
+    assert_eq!(record.lp_burned, LP_AMOUNT);
+    assert_eq!(record.lp_held(), 0);
+    assert_eq!(
+        (record.withdrawn_x, record.withdrawn_y),
+        (RESERVE_X, RESERVE_Y)
+    );
+    assert_eq!((record.cost_x, record.cost_y), (0, 0));
+
+    // 再次存入后记录中的持有量和成本都是新存入的部分
+    let instruction = tracked(&fixture);
+    fixture.svm.process(&instruction).unwrap();
+    let record = fixture.svm.position(&position);
+    assert_eq!(record.lp_held(), LP_AMOUNT);
+    assert_eq!((record.cost_x, record.cost_y), (RESERVE_X, RESERVE_Y));
+}
+
+// 用同一对铸币按另一个 seed 再创建一个池子
+fn initialize_another(fixture: &PoolFixture, fee: u16) -> Instruction {
+    Initialize {
+        initializer: fixture.admin.clone(),
+        seed: PoolFixture::SEED + 1,
+        fee,
+        mint_x: fixture.pool.mint_x.clone(),
+        mint_y: fixture.pool.mint_y.clone(),
+        authority: None,
+    }
+    .instruction()
+    .unwrap()
+}
+
+#[test]
+fn initialize_requires_fee_tier_and_collects_creation_fee() {
+    let mut fixture = setup();
+    let (settings, _) = find_settings_address();
+    assert_eq!(fixture.svm.settings().fee_tiers, PoolFixture::FEE_TIERS);
+
+    // 不在档位中的费率被拒绝
+    let unlisted = initialize_another(&fixture, 25);
+    assert_eq!(
+        fixture.svm.process(&unlisted),
+        Err(InstructionError::InvalidArgument)
+    );
+
+    // 创建费从创建者转入设置账户
+    let before = fixture.svm.lamports(&settings);
+    let listed = initialize_another(&fixture, 100);
+    fixture.svm.process(&listed).unwrap();
+    assert_eq!(
+        fixture.svm.lamports(&settings),
+        before + PoolFixture::CREATION_FEE
+    );
+}
+
+#[test]
+fn settings_require_admin() {
+    let mut fixture = setup();
+    let update = |admin: &Address| {
+        UpdateSettings {
+            admin: admin.clone(),
+            new_admin: admin.clone(),
+            creation_fee: 0,
+            fee_tiers: vec![25],
+        }
+        .instruction()
+        .unwrap()
+    };
+    assert_eq!(
+        fixture.svm.process(&update(&fixture.user)),
+        Err(InstructionError::IncorrectAuthority)
+    );
+
+    // 管理员修改档位之后 新的费率可以建池 已经创建的池子不受影响
+    fixture.svm.process(&update(&fixture.admin)).unwrap();
+    let initialize = initialize_another(&fixture, 25);
+    fixture.svm.process(&initialize).unwrap();
+    assert_eq!(fixture.svm.config(&fixture.pool.config).fee, FEE);
+
+    // 第一个池子的创建费可以取出 第二个池子已经免费
+    let destination = new_address();
+    fixture.svm.fund(&destination, 1_000_000);
+    let collect = |admin: &Address| {
+        CollectCreationFees {
+            admin: admin.clone(),
+            destination: destination.clone(),
+        }
+        .instruction()
+        .unwrap()
+    };
+    assert_eq!(
+        fixture.svm.process(&collect(&fixture.user)),
+        Err(InstructionError::IncorrectAuthority)
+    );
+    fixture.svm.process(&collect(&fixture.admin)).unwrap();
+    assert_eq!(
+        fixture.svm.lamports(&destination),
+        1_000_000 + PoolFixture::CREATION_FEE
+    );
+}
+
+#[test]
+fn close_pool_sweeps_donated_tokens() {
+    let mut fixture = setup_with_liquidity();
+    let admin = fixture.admin.clone();
+    let admin_x = find_associated_token_address(&admin, &fixture.pool.mint_x);
+    let admin_y = find_associated_token_address(&admin, &fixture.pool.mint_y);
+    fixture
+        .svm
+        .create_token_account(&admin_x, &fixture.pool.mint_x, &admin, 0);
+    fixture
+        .svm
+        .create_token_account(&admin_y, &fixture.pool.mint_y, &admin, 0);
+
+    // 还有流动性时不能关闭
+    assert_eq!(
+        fixture.svm.process(&close_pool(&fixture, &admin)),
+        Err(InstructionError::InvalidArgument)
+    );
+
+    // 全部取出之后 其他人向金库转入的代币不会阻止关闭
+    let withdraw = withdraw(&fixture, LP_AMOUNT, 1, 1).instruction().unwrap();
+    fixture.svm.process(&withdraw).unwrap();
+    let donation = Instruction {
+        program_id: TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(fixture.user_ata(&fixture.pool.mint_x), false),
+            AccountMeta::new(fixture.pool.vault_x.clone(), false),
+            AccountMeta::new_readonly(fixture.user.clone(), true),
+        ],
+        data: [vec![3], 1u64.to_le_bytes().to_vec()].concat(),
+    };
+    fixture.svm.process(&donation).unwrap();
+    assert_eq!(fixture.svm.token_amount(&fixture.pool.vault_x), 1);
+
+    // 非管理员也不是创建者时不能关闭
+    assert_eq!(
+        fixture.svm.process(&close_pool(&fixture, &fixture.user)),
+        Err(InstructionError::IncorrectAuthority)
+    );
+    fixture.svm.process(&close_pool(&fixture, &admin)).unwrap();
+    assert_eq!(fixture.svm.token_amount(&admin_x), 1);
+    assert_eq!(fixture.svm.token_amount(&admin_y), 0);
+    assert_eq!(fixture.svm.lamports(&fixture.pool.config), 0);
+}
@@ -0,0 +1,122 @@
+// 原生程序的计算单元基准测试
+// cargo run --release -- [--update] [--threshold <percent>] [--report <path>]
+// 和检查进仓库的报告比较 任何指令或程序大小超过基准 threshold% 时以非 0 状态退出
+// 任何程序没有编译产物 或者报告中的指令这次没有测量到 都以非 0 状态退出
+// --update 用这次的结果重写整个报告
+
+mod report;
+mod scenarios;
+
+use blueshift_amm_tests::program_path;
+use report::Report;
+use scenarios::SCENARIOS;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const DEFAULT_THRESHOLD: u64 = 5;
+
+struct Options {
+    update: bool,
+    threshold: u64,
+    report: PathBuf,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        update: false,
+        threshold: DEFAULT_THRESHOLD,
+        report: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("compute_units.md"),
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--update" => options.update = true,
+            "--threshold" => {
+                let value = args.next().ok_or("--threshold needs a value")?;
+                options.threshold = value
+                    .parse()
+                    .map_err(|_| format!("invalid threshold {value}"))?;
+            }
+            "--report" => {
+                options.report = args.next().ok_or("--report needs a value")?.into();
+            }
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+    Ok(options)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let text = std::fs::read_to_string(&options.report).unwrap_or_default();
+    let baseline = Report::parse(&text);
+
+    let mut measurements = Vec::new();
+    let mut binary_sizes = BTreeMap::new();
+    for scenario in SCENARIOS {
+        match scenario.measure() {
+            Ok(results) => measurements.extend(results),
+            Err(error) => {
+                eprintln!("error: {}: {error}", scenario.program);
+                return ExitCode::FAILURE;
+            }
+        }
+        // measure 成功说明编译产物一定存在
+        let size = program_path(scenario.program)
+            .and_then(|path| std::fs::metadata(path).ok())
+            .map_or(0, |metadata| metadata.len());
+        binary_sizes.insert(scenario.program.to_string(), size);
+    }
+    let current = Report::from_measurements(&measurements, binary_sizes);
+
+    println!(
+        "{:<24} {:<24} {:>10} {:>10}",
+        "program", "instruction", "baseline", "current"
+    );
+    for ((program, instruction), units) in &current.compute_units {
+        let key = (program.clone(), instruction.clone());
+        let previous = baseline.compute_units.get(&key);
+        println!(
+            "{:<24} {:<24} {:>10} {:>10}",
+            program,
+            instruction,
+            previous.map_or("-".to_string(), |units| units.to_string()),
+            units
+        );
+    }
+    for (program, size) in &current.binary_sizes {
+        let previous = baseline.binary_sizes.get(program);
+        println!(
+            "{:<24} {:<24} {:>10} {:>10}",
+            program,
+            "binary size",
+            previous.map_or("-".to_string(), |size| size.to_string()),
+            size
+        );
+    }
+
+    if options.update {
+        if let Err(error) = std::fs::write(&options.report, current.to_markdown(options.threshold))
+        {
+            eprintln!("error: {}: {error}", options.report.display());
+            return ExitCode::FAILURE;
+        }
+        println!("updated {}", options.report.display());
+        return ExitCode::SUCCESS;
+    }
+    let regressions = current.regressions(&baseline, options.threshold);
+    if !regressions.is_empty() {
+        for regression in &regressions {
+            eprintln!("regression: {regression}");
+        }
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
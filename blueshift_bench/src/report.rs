@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+// 一条指令的测量结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub program: String,
+    pub instruction: String,
+    pub compute_units: u64,
+}
+
+// 检查进仓库的报告 也是下一次运行的基准
+// 计算单元表有三列（程序、指令、计算单元） 程序大小表有两列（程序、字节数）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub compute_units: BTreeMap<(String, String), u64>,
+    pub binary_sizes: BTreeMap<String, u64>,
+}
+
+impl Report {
+    pub fn parse(text: &str) -> Self {
+        let mut report = Self::default();
+        for line in text.lines() {
+            if !line.trim_start().starts_with('|') {
+                continue;
+            }
+            let cells: Vec<&str> = line
+                .trim()
+                .trim_matches('|')
+                .split('|')
+                .map(str::trim)
+                .collect();
+            // 表头和分隔行的最后一列不是数字 直接跳过
+            match cells.as_slice() {
+                [program, instruction, units] => {
+                    if let Ok(units) = units.parse() {
+                        report
+                            .compute_units
+                            .insert((program.to_string(), instruction.to_string()), units);
+                    }
+                }
+                [program, size] => {
+                    if let Ok(size) = size.parse() {
+                        report.binary_sizes.insert(program.to_string(), size);
+                    }
+                }
+                _ => {}
+            }
+        }
+        report
+    }
+
+    // 这次运行的结果 --update 时整个报告都用它重写
+    pub fn from_measurements(
+        measurements: &[Measurement],
+        binary_sizes: BTreeMap<String, u64>,
+    ) -> Self {
+        let compute_units = measurements
+            .iter()
+            .map(|m| ((m.program.clone(), m.instruction.clone()), m.compute_units))
+            .collect();
+        Self {
+            compute_units,
+            binary_sizes,
+        }
+    }
+
+    // 和基准比较 返回全部回退 基准中有但这次没有测量到的行也算回退
+    pub fn regressions(&self, baseline: &Report, threshold: u64) -> Vec<String> {
+        let mut regressions = Vec::new();
+        for ((program, instruction), units) in &self.compute_units {
+            let key = (program.clone(), instruction.clone());
+            let name = format!("{program} {instruction}");
+            regressions.extend(regression(
+                &name,
+                baseline.compute_units.get(&key).copied(),
+                *units,
+                threshold,
+            ));
+        }
+        for (program, size) in &self.binary_sizes {
+            let name = format!("{program} binary size");
+            regressions.extend(regression(
+                &name,
+                baseline.binary_sizes.get(program).copied(),
+                *size,
+                threshold,
+            ));
+        }
+        for (program, instruction) in baseline.compute_units.keys() {
+            if !self
+                .compute_units
+                .contains_key(&(program.clone(), instruction.clone()))
+            {
+                regressions.push(format!("{program} {instruction}: not measured"));
+            }
+        }
+        for program in baseline.binary_sizes.keys() {
+            if !self.binary_sizes.contains_key(program) {
+                regressions.push(format!("{program} binary size: not measured"));
+            }
+        }
+        regressions
+    }
+
+    pub fn to_markdown(&self, threshold: u64) -> String {
+        let mut out = String::new();
+        writeln!(out, "# Compute units").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "Generated by `cargo run --release -- --update` in `blueshift_bench`. \
+             The benchmark fails when an instruction or binary grows more than {threshold}% \
+             over the numbers below."
+        )
+        .unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "| program | instruction | compute units |").unwrap();
+        writeln!(out, "| --- | --- | ---: |").unwrap();
+        for ((program, instruction), units) in &self.compute_units {
+            writeln!(out, "| {program} | {instruction} | {units} |").unwrap();
+        }
+        writeln!(out).unwrap();
+        writeln!(out, "| program | binary size (bytes) |").unwrap();
+        writeln!(out, "| --- | ---: |").unwrap();
+        for (program, size) in &self.binary_sizes {
+            writeln!(out, "| {program} | {size} |").unwrap();
+        }
+        out
+    }
+}
+
+// 超过基准 threshold% 时返回说明 没有基准的指令也要失败 需要先用 --update 记录
+pub fn regression(
+    name: &str,
+    baseline: Option<u64>,
+    current: u64,
+    threshold: u64,
+) -> Option<String> {
+    let Some(baseline) = baseline else {
+        return Some(format!("{name}: no baseline, run with --update"));
+    };
+    let limit = baseline as u128 * (100 + threshold as u128) / 100;
+    (current as u128 > limit)
+        .then(|| format!("{name}: {current} exceeds baseline {baseline} by more than {threshold}%"))
+}
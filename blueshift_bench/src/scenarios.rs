@@ -0,0 +1,709 @@
+use crate::report::Measurement;
+use blueshift_amm_client::{
+    find_associated_token_address, find_config_address, find_mint_lp_address,
+    find_program_data_address, AccountMeta, ClientError, CollectCreationFees, CreateSettings,
+    Deposit, Instruction, OpenPosition, QuotePosition, Swap, UpdateSettings, Withdraw,
+    ASSOCIATED_TOKEN_PROGRAM_ID, BPF_LOADER_UPGRADEABLE_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+use blueshift_amm_tests::{new_address, pack_oracle_price, pack_program_data, PoolFixture, Svm};
+use solana_address::{address, Address};
+
+// 三个原生程序都使用同一个程序地址
+pub const PROGRAM_ID: Address = address!("22222222222222222222222222222222222222222222");
+
+// 每个程序的一组指令 按执行顺序排列 后面的指令依赖前面的状态
+pub struct Scenario {
+    pub program: &'static str,
+    pub run: fn(Svm) -> Result<Vec<(&'static str, u64)>, String>,
+}
+
+pub const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        program: "blueshift_vault",
+        run: vault,
+    },
+    Scenario {
+        program: "blueshift_escrow",
+        run: escrow,
+    },
+    Scenario {
+        program: "blueshift_native_amm",
+        run: native_amm,
+    },
+];
+
+impl Scenario {
+    // 没有编译产物时返回错误 不能在没有测量的情况下让基准测试通过
+    pub fn measure(&self) -> Result<Vec<Measurement>, String> {
+        let svm = Svm::load_program(self.program, &PROGRAM_ID).ok_or_else(|| {
+            format!(
+                "{}.so not found, run cargo build-sbf in ../{} first",
+                self.program, self.program
+            )
+        })?;
+        let results = (self.run)(svm)?;
+        Ok(results
+            .into_iter()
+            .map(|(instruction, compute_units)| Measurement {
+                program: self.program.to_string(),
+                instruction: instruction.to_string(),
+                compute_units,
+            })
+            .collect())
+    }
+}
+
+// 执行一条指令并记录消耗的计算单元 失败时整个基准测试失败
+fn run(
+    svm: &mut Svm,
+    name: &'static str,
+    instruction: &Instruction,
+) -> Result<(&'static str, u64), String> {
+    svm.process(instruction)
+        .map_err(|error| format!("{name} failed: {error:?}"))?;
+    Ok((name, svm.compute_units))
+}
+
+fn program_instruction(data: Vec<u8>, accounts: Vec<AccountMeta>) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+fn vault(mut svm: Svm) -> Result<Vec<(&'static str, u64)>, String> {
+    let owner = new_address();
+    svm.fund(&owner, 10_000_000_000);
+    let (vault, _) = Address::find_program_address(&[b"vault", owner.as_ref()], &PROGRAM_ID);
+    let accounts = vec![
+        AccountMeta::new(owner, true),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    let mut deposit = vec![0];
+    deposit.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+    Ok(vec![
+        run(
+            &mut svm,
+            "deposit",
+            &program_instruction(deposit, accounts.clone()),
+        )?,
+        run(
+            &mut svm,
+            "withdraw",
+            &program_instruction(vec![1], accounts),
+        )?,
+    ])
+}
+
+fn escrow(mut svm: Svm) -> Result<Vec<(&'static str, u64)>, String> {
+    let maker = new_address();
+    let taker = new_address();
+    let mint_a = new_address();
+    let mint_b = new_address();
+    svm.fund(&maker, 10_000_000_000);
+    svm.fund(&taker, 10_000_000_000);
+    svm.create_mint(&mint_a, &maker, 6);
+    svm.create_mint(&mint_b, &taker, 6);
+    let maker_ata_a = find_associated_token_address(&maker, &mint_a);
+    let taker_ata_b = find_associated_token_address(&taker, &mint_b);
+    svm.create_token_account(&maker_ata_a, &mint_a, &maker, 1_000_000);
+    svm.create_token_account(&taker_ata_b, &mint_b, &taker, 1_000_000);
+
+    let escrow_address = |seed: u64| {
+        Address::find_program_address(
+            &[b"escrow", maker.as_ref(), &seed.to_le_bytes()],
+            &PROGRAM_ID,
+        )
+        .0
+    };
+    let make = |seed: u64| {
+        let escrow = escrow_address(seed);
+        let mut data = vec![0];
+        data.extend_from_slice(&seed.to_le_bytes());
+        data.extend_from_slice(&500_000u64.to_le_bytes());
+        data.extend_from_slice(&100_000u64.to_le_bytes());
+        program_instruction(
+            data,
+            vec![
+                AccountMeta::new(maker.clone(), true),
+                AccountMeta::new(escrow.clone(), false),
+                AccountMeta::new_readonly(mint_a.clone(), false),
+                AccountMeta::new_readonly(mint_b.clone(), false),
+                AccountMeta::new(maker_ata_a.clone(), false),
+                AccountMeta::new(find_associated_token_address(&escrow, &mint_a), false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+            ],
+        )
+    };
+
+    // 第一个 escrow 被接受 第二个 escrow 被退款
+    let escrow = escrow_address(1);
+    let take = program_instruction(
+        vec![1],
+        vec![
+            AccountMeta::new(taker.clone(), true),
+            AccountMeta::new(maker.clone(), false),
+            AccountMeta::new(escrow.clone(), false),
+            AccountMeta::new_readonly(mint_a.clone(), false),
+            AccountMeta::new_readonly(mint_b.clone(), false),
+            AccountMeta::new(find_associated_token_address(&escrow, &mint_a), false),
+            AccountMeta::new(find_associated_token_address(&taker, &mint_a), false),
+            AccountMeta::new(taker_ata_b.clone(), false),
+            AccountMeta::new(find_associated_token_address(&maker, &mint_b), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    let escrow = escrow_address(2);
+    let refund = program_instruction(
+        vec![2],
+        vec![
+            AccountMeta::new(maker.clone(), true),
+            AccountMeta::new(escrow.clone(), false),
+            AccountMeta::new_readonly(mint_a.clone(), false),
+            AccountMeta::new(find_associated_token_address(&escrow, &mint_a), false),
+            AccountMeta::new(maker_ata_a.clone(), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        ],
+    );
+
+    let make_result = run(&mut svm, "make", &make(1))?;
+    let take_result = run(&mut svm, "take", &take)?;
+    run(&mut svm, "make", &make(2))?;
+    let refund_result = run(&mut svm, "refund", &refund)?;
+    Ok(vec![make_result, take_result, refund_result])
+}
+
+// 覆盖 AMM 的每一条指令 按依赖顺序执行
+// 管理员权限在最后转给多签 迁移和关闭池子使用另一个旧布局的池子
+fn native_amm(mut svm: Svm) -> Result<Vec<(&'static str, u64)>, String> {
+    // CreateSettings 只能由升级权限持有者调用 PoolFixture 随后会直接改写设置账户
+    let upgrade_authority = new_address();
+    svm.fund(&upgrade_authority, 10_000_000_000);
+    svm.set_account(
+        &find_program_data_address(),
+        &BPF_LOADER_UPGRADEABLE_ID,
+        pack_program_data(&upgrade_authority),
+    );
+    let create_settings = CreateSettings {
+        payer: upgrade_authority.clone(),
+        admin: upgrade_authority,
+        creation_fee: PoolFixture::CREATION_FEE,
+        fee_tiers: PoolFixture::FEE_TIERS.to_vec(),
+    };
+    let create_settings = run(
+        &mut svm,
+        "create settings",
+        &client(create_settings.instruction())?,
+    )?;
+
+    // PoolFixture 最后执行的指令就是 Initialize
+    let mut fixture = PoolFixture::new(svm, 30);
+    let mut results = vec![create_settings, ("initialize", fixture.svm.compute_units)];
+    let admin = fixture.admin.clone();
+    let user = fixture.user.clone();
+    let pool = fixture.pool.clone();
+    let user_x = fixture.user_ata(&pool.mint_x);
+    let user_y = fixture.user_ata(&pool.mint_y);
+    let user_lp = fixture.user_ata(&pool.mint_lp);
+    let svm = &mut fixture.svm;
+
+    let deposit = |amount: u64| Deposit {
+        user: user.clone(),
+        pool: pool.clone(),
+        amount,
+        max_x: 1_000_000_000,
+        max_y: 2_000_000_000,
+        expiration: i64::MAX,
+        ..Default::default()
+    };
+    let swap = |is_x: bool| Swap {
+        user: user.clone(),
+        pool: pool.clone(),
+        is_x,
+        amount: 1_000_000,
+        min: 1,
+        expiration: i64::MAX,
+        ..Default::default()
+    };
+    let withdraw = Withdraw {
+        user: user.clone(),
+        pool: pool.clone(),
+        amount: 500_000,
+        min_x: 1,
+        min_y: 1,
+        expiration: i64::MAX,
+        ..Default::default()
+    };
+    let position = OpenPosition {
+        user: user.clone(),
+        pool: pool.clone(),
+    };
+    let quote_position = QuotePosition {
+        user: user.clone(),
+        pool: pool.clone(),
+    };
+    let instructions = [
+        ("deposit (first)", deposit(1_000_000).instruction()),
+        ("deposit", deposit(500_000).instruction()),
+        ("swap x to y", swap(true).instruction()),
+        ("swap y to x", swap(false).instruction()),
+        ("withdraw", withdraw.instruction()),
+        ("open position", position.instruction()),
+        ("quote position", quote_position.instruction()),
+    ];
+    for (name, instruction) in instructions {
+        let instruction = instruction.map_err(|error| format!("{name}: {error:?}"))?;
+        results.push(run(svm, name, &instruction)?);
+    }
+
+    // 报价
+    let mut data = vec![13, 1];
+    data.extend_from_slice(&1_000_000u64.to_le_bytes());
+    let quote = program_instruction(
+        data,
+        vec![AccountMeta::new_readonly(pool.config.clone(), false)],
+    );
+    results.push(run(svm, "quote swap", &quote)?);
+    let mut data = vec![14];
+    for value in [100_000u64, 1_000_000_000, 2_000_000_000] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    let quote_accounts = vec![
+        AccountMeta::new_readonly(pool.config.clone(), false),
+        AccountMeta::new_readonly(pool.mint_lp.clone(), false),
+    ];
+    let quote = program_instruction(data, quote_accounts.clone());
+    results.push(run(svm, "quote deposit", &quote)?);
+    let mut data = vec![15];
+    data.extend_from_slice(&100_000u64.to_le_bytes());
+    results.push(run(
+        svm,
+        "quote withdraw",
+        &program_instruction(data, quote_accounts),
+    )?);
+
+    // 直接转入金库的代币由 Skim 转走 Sync 把储备同步为金库余额
+    let admin_x = find_associated_token_address(&admin, &pool.mint_x);
+    let admin_y = find_associated_token_address(&admin, &pool.mint_y);
+    svm.create_token_account(&admin_x, &pool.mint_x, &admin, 0);
+    svm.create_token_account(&admin_y, &pool.mint_y, &admin, 0);
+    let donated = svm.token_amount(&pool.vault_x) + 1_000;
+    svm.create_token_account(&pool.vault_x, &pool.mint_x, &pool.config, donated);
+    let skim = program_instruction(
+        vec![11],
+        vec![
+            AccountMeta::new_readonly(admin.clone(), true),
+            AccountMeta::new_readonly(pool.config.clone(), false),
+            AccountMeta::new(pool.vault_x.clone(), false),
+            AccountMeta::new(pool.vault_y.clone(), false),
+            AccountMeta::new(admin_x, false),
+            AccountMeta::new(admin_y, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "skim", &skim)?);
+    let sync = program_instruction(
+        vec![10],
+        vec![
+            AccountMeta::new(pool.config.clone(), false),
+            AccountMeta::new_readonly(pool.mint_lp.clone(), false),
+            AccountMeta::new_readonly(pool.vault_x.clone(), false),
+            AccountMeta::new_readonly(pool.vault_y.clone(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "sync", &sync)?);
+
+    // 只需要管理员和 config 的管理指令
+    let admin_accounts = || {
+        vec![
+            AccountMeta::new_readonly(admin.clone(), true),
+            AccountMeta::new(pool.config.clone(), false),
+        ]
+    };
+    let oracle = new_address();
+    svm.set_account(
+        &oracle,
+        &SYSTEM_PROGRAM_ID,
+        pack_oracle_price(200_000_000, 1_000, -8, 0),
+    );
+    let set_oracle = |oracle: &Address| {
+        let mut data = vec![4];
+        data.extend_from_slice(&100u16.to_le_bytes());
+        data.extend_from_slice(&60u64.to_le_bytes());
+        let mut accounts = admin_accounts();
+        accounts.extend([
+            AccountMeta::new_readonly(oracle.clone(), false),
+            AccountMeta::new_readonly(pool.mint_x.clone(), false),
+            AccountMeta::new_readonly(pool.mint_y.clone(), false),
+        ]);
+        program_instruction(data, accounts)
+    };
+    results.push(run(svm, "set oracle", &set_oracle(&oracle))?);
+    run(svm, "set oracle", &set_oracle(&SYSTEM_PROGRAM_ID))?;
+    let mut data = vec![26];
+    data.extend_from_slice(&500u16.to_le_bytes());
+    results.push(run(
+        svm,
+        "set price band",
+        &program_instruction(data, admin_accounts()),
+    )?);
+    let mut data = vec![32];
+    data.extend_from_slice(new_address().as_ref());
+    results.push(run(
+        svm,
+        "set guardian",
+        &program_instruction(data, admin_accounts()),
+    )?);
+    results.push(run(
+        svm,
+        "set state",
+        &program_instruction(vec![5, 2], admin_accounts()),
+    )?);
+    run(
+        svm,
+        "set state",
+        &program_instruction(vec![5, 1], admin_accounts()),
+    )?;
+    let set_hook = |hook: &Address| {
+        let mut accounts = admin_accounts();
+        accounts.push(AccountMeta::new_readonly(hook.clone(), false));
+        program_instruction(vec![9], accounts)
+    };
+    results.push(run(svm, "set hook", &set_hook(&TOKEN_PROGRAM_ID))?);
+    run(svm, "set hook", &set_hook(&SYSTEM_PROGRAM_ID))?;
+
+    // 许可池白名单 记录的租金由用户支付
+    results.push(run(
+        svm,
+        "set permissioned",
+        &program_instruction(vec![6, 1], admin_accounts()),
+    )?);
+    let entry = pda(&[b"allowlist", pool.config.as_ref(), user.as_ref()]);
+    let add_trader = program_instruction(
+        vec![7],
+        vec![
+            AccountMeta::new(user.clone(), true),
+            AccountMeta::new_readonly(admin.clone(), true),
+            AccountMeta::new_readonly(pool.config.clone(), false),
+            AccountMeta::new_readonly(user.clone(), false),
+            AccountMeta::new(entry.clone(), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "add trader", &add_trader)?);
+    let remove_trader = program_instruction(
+        vec![8],
+        vec![
+            AccountMeta::new(admin.clone(), true),
+            AccountMeta::new_readonly(pool.config.clone(), false),
+            AccountMeta::new(entry, false),
+        ],
+    );
+    results.push(run(svm, "remove trader", &remove_trader)?);
+    run(
+        svm,
+        "set permissioned",
+        &program_instruction(vec![6, 0], admin_accounts()),
+    )?;
+
+    // 流动性挖矿 农场的两个金库是农场的 ATA
+    let reward_mint = new_address();
+    svm.create_mint(&reward_mint, &admin, 6);
+    let farm = pda(&[b"farm", pool.config.as_ref()]);
+    let stake_vault = find_associated_token_address(&farm, &pool.mint_lp);
+    let reward_vault = find_associated_token_address(&farm, &reward_mint);
+    let admin_reward = find_associated_token_address(&admin, &reward_mint);
+    let user_reward = find_associated_token_address(&user, &reward_mint);
+    svm.create_token_account(&stake_vault, &pool.mint_lp, &farm, 0);
+    svm.create_token_account(&reward_vault, &reward_mint, &farm, 0);
+    svm.create_token_account(&admin_reward, &reward_mint, &admin, 1_000_000);
+    svm.create_token_account(&user_reward, &reward_mint, &user, 0);
+    let mut data = vec![17];
+    data.extend_from_slice(&100u64.to_le_bytes());
+    let create_farm = program_instruction(
+        data,
+        vec![
+            AccountMeta::new(admin.clone(), true),
+            AccountMeta::new_readonly(pool.config.clone(), false),
+            AccountMeta::new(farm.clone(), false),
+            AccountMeta::new_readonly(reward_mint.clone(), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "create farm", &create_farm)?);
+    let mut data = vec![18];
+    data.extend_from_slice(&1_000_000u64.to_le_bytes());
+    data.extend_from_slice(&100u64.to_le_bytes());
+    let fund_rewards = program_instruction(
+        data,
+        vec![
+            AccountMeta::new_readonly(admin.clone(), true),
+            AccountMeta::new_readonly(pool.config.clone(), false),
+            AccountMeta::new(farm.clone(), false),
+            AccountMeta::new(admin_reward, false),
+            AccountMeta::new(reward_vault.clone(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "fund rewards", &fund_rewards)?);
+    let stake_position = pda(&[b"stake", farm.as_ref(), user.as_ref()]);
+    let farm_accounts = |token_account: &Address, vault: &Address| {
+        vec![
+            AccountMeta::new(user.clone(), true),
+            AccountMeta::new(farm.clone(), false),
+            AccountMeta::new(stake_position.clone(), false),
+            AccountMeta::new(token_account.clone(), false),
+            AccountMeta::new(vault.clone(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ]
+    };
+    let mut data = vec![19];
+    data.extend_from_slice(&100_000u64.to_le_bytes());
+    let mut accounts = farm_accounts(&user_lp, &stake_vault);
+    accounts.push(AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false));
+    results.push(run(svm, "stake", &program_instruction(data, accounts))?);
+    svm.set_clock(100, 100);
+    let claim = program_instruction(vec![21], farm_accounts(&user_reward, &reward_vault));
+    results.push(run(svm, "claim", &claim)?);
+    let mut data = vec![20];
+    data.extend_from_slice(&100_000u64.to_le_bytes());
+    let unstake = program_instruction(data, farm_accounts(&user_lp, &stake_vault));
+    results.push(run(svm, "unstake", &unstake)?);
+
+    // TWAMM 卖出 X 的订单 执行到结束时间之后取出收益
+    let twamm = pda(&[b"twamm", pool.config.as_ref()]);
+    let create_twamm = program_instruction(
+        vec![22],
+        vec![
+            AccountMeta::new(admin.clone(), true),
+            AccountMeta::new(pool.config.clone(), false),
+            AccountMeta::new(twamm.clone(), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "create twamm", &create_twamm)?);
+    let order_id = 1u64;
+    let order = pda(&[
+        b"twamm_order",
+        pool.config.as_ref(),
+        user.as_ref(),
+        &order_id.to_le_bytes(),
+    ]);
+    let mut data = vec![23];
+    data.extend_from_slice(&order_id.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(&1_000_000u64.to_le_bytes());
+    data.extend_from_slice(&3_600u64.to_le_bytes());
+    let place_order = program_instruction(
+        data,
+        vec![
+            AccountMeta::new(user.clone(), true),
+            AccountMeta::new(pool.config.clone(), false),
+            AccountMeta::new(twamm.clone(), false),
+            AccountMeta::new(order.clone(), false),
+            AccountMeta::new(user_x.clone(), false),
+            AccountMeta::new(pool.vault_x.clone(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "place order", &place_order)?);
+    // 订单的结束时间向上对齐到整点
+    svm.set_clock(7_200, 200);
+    let execute = program_instruction(
+        vec![24],
+        vec![
+            AccountMeta::new(pool.config.clone(), false),
+            AccountMeta::new(twamm.clone(), false),
+        ],
+    );
+    results.push(run(svm, "execute", &execute)?);
+    let withdraw_proceeds = program_instruction(
+        vec![25],
+        vec![
+            AccountMeta::new(user.clone(), true),
+            AccountMeta::new(pool.config.clone(), false),
+            AccountMeta::new(twamm, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(user_y.clone(), false),
+            AccountMeta::new(pool.vault_y.clone(), false),
+            AccountMeta::new(user_x.clone(), false),
+            AccountMeta::new(pool.vault_x.clone(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "withdraw proceeds", &withdraw_proceeds)?);
+
+    // 全局设置
+    let update_settings = UpdateSettings {
+        admin: admin.clone(),
+        new_admin: admin.clone(),
+        creation_fee: PoolFixture::CREATION_FEE,
+        fee_tiers: PoolFixture::FEE_TIERS.to_vec(),
+    };
+    results.push(run(
+        svm,
+        "update settings",
+        &client(update_settings.instruction())?,
+    )?);
+    let collect = CollectCreationFees {
+        admin: admin.clone(),
+        destination: admin.clone(),
+    };
+    results.push(run(
+        svm,
+        "collect creation fees",
+        &client(collect.instruction())?,
+    )?);
+
+    // 旧布局的池子 迁移之后直接关闭
+    let mint_a = new_address();
+    let mint_b = new_address();
+    let legacy_seed = 7u64;
+    let (legacy, legacy_bump) = find_config_address(legacy_seed, &mint_a, &mint_b);
+    let (legacy_mint_lp, _) = find_mint_lp_address(&legacy);
+    let legacy_vault_a = find_associated_token_address(&legacy, &mint_a);
+    let legacy_vault_b = find_associated_token_address(&legacy, &mint_b);
+    svm.create_mint(&mint_a, &admin, 6);
+    svm.create_mint(&mint_b, &admin, 6);
+    svm.create_mint(&legacy_mint_lp, &legacy, 6);
+    svm.create_token_account(&legacy_vault_a, &mint_a, &legacy, 0);
+    svm.create_token_account(&legacy_vault_b, &mint_b, &legacy, 0);
+    svm.set_account(
+        &legacy,
+        &PROGRAM_ID,
+        pack_legacy_config(legacy_seed, &admin, &mint_a, &mint_b, 30, legacy_bump),
+    );
+    let migrate = program_instruction(
+        vec![16],
+        vec![
+            AccountMeta::new(admin.clone(), true),
+            AccountMeta::new(legacy.clone(), false),
+            AccountMeta::new_readonly(legacy_mint_lp.clone(), false),
+            AccountMeta::new_readonly(legacy_vault_a.clone(), false),
+            AccountMeta::new_readonly(legacy_vault_b.clone(), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "migrate", &migrate)?);
+    // 金库中直接转入的代币在关闭时转给 closer
+    let admin_a = find_associated_token_address(&admin, &mint_a);
+    let admin_b = find_associated_token_address(&admin, &mint_b);
+    svm.create_token_account(&admin_a, &mint_a, &admin, 0);
+    svm.create_token_account(&admin_b, &mint_b, &admin, 0);
+    let close_pool = program_instruction(
+        vec![12],
+        vec![
+            AccountMeta::new(admin.clone(), true),
+            AccountMeta::new(legacy, false),
+            AccountMeta::new_readonly(legacy_mint_lp, false),
+            AccountMeta::new(legacy_vault_a, false),
+            AccountMeta::new(legacy_vault_b, false),
+            AccountMeta::new(admin_a, false),
+            AccountMeta::new(admin_b, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "close pool", &close_pool)?);
+
+    // 多签 把管理员转给 2/2 多签之后 通过提案修改价格波动范围
+    let cosigner = new_address();
+    svm.fund(&cosigner, 1_000_000_000);
+    let multisig_seed = 1u64;
+    let multisig = pda(&[b"multisig", admin.as_ref(), &multisig_seed.to_le_bytes()]);
+    let mut data = vec![27];
+    data.extend_from_slice(&multisig_seed.to_le_bytes());
+    data.push(2);
+    data.extend_from_slice(admin.as_ref());
+    data.extend_from_slice(cosigner.as_ref());
+    let create_multisig = program_instruction(
+        data,
+        vec![
+            AccountMeta::new(admin.clone(), true),
+            AccountMeta::new(multisig.clone(), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+    );
+    results.push(run(svm, "create multisig", &create_multisig)?);
+    let mut data = vec![31];
+    data.extend_from_slice(multisig.as_ref());
+    results.push(run(
+        svm,
+        "set authority",
+        &program_instruction(data, admin_accounts()),
+    )?);
+    let proposal = pda(&[b"proposal", multisig.as_ref(), &0u64.to_le_bytes()]);
+    let proposal_accounts = [
+        AccountMeta::new_readonly(multisig.clone(), false),
+        AccountMeta::new(pool.config.clone(), false),
+    ];
+    let mut data = vec![28, 26];
+    data.extend_from_slice(&1_000u16.to_le_bytes());
+    let mut accounts = vec![
+        AccountMeta::new(admin.clone(), true),
+        AccountMeta::new(multisig.clone(), false),
+        AccountMeta::new(proposal.clone(), false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+    accounts.extend_from_slice(&proposal_accounts);
+    results.push(run(svm, "propose", &program_instruction(data, accounts))?);
+    let approve = program_instruction(
+        vec![29],
+        vec![
+            AccountMeta::new_readonly(cosigner, true),
+            AccountMeta::new_readonly(multisig.clone(), false),
+            AccountMeta::new(proposal.clone(), false),
+        ],
+    );
+    results.push(run(svm, "approve", &approve)?);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(multisig, false),
+        AccountMeta::new(proposal, false),
+    ];
+    accounts.extend_from_slice(&proposal_accounts);
+    results.push(run(
+        svm,
+        "execute proposal",
+        &program_instruction(vec![30], accounts),
+    )?);
+    Ok(results)
+}
+
+// 客户端构建指令失败时整个基准测试失败
+fn client(instruction: Result<Instruction, ClientError>) -> Result<Instruction, String> {
+    instruction.map_err(|error| format!("{error:?}"))
+}
+
+fn pda(seeds: &[&[u8]]) -> Address {
+    Address::find_program_address(seeds, &PROGRAM_ID).0
+}
+
+// 没有版本号的旧 config 布局 108 字节 见 blueshift_native_amm::Config::LEGACY_LEN
+fn pack_legacy_config(
+    seed: u64,
+    authority: &Address,
+    mint_x: &Address,
+    mint_y: &Address,
+    fee: u16,
+    bump: u8,
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(108);
+    data.push(1);
+    data.extend_from_slice(&seed.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(mint_x.as_ref());
+    data.extend_from_slice(mint_y.as_ref());
+    data.extend_from_slice(&fee.to_le_bytes());
+    data.push(bump);
+    data
+}
@@ -0,0 +1,215 @@
+use pinocchio::cpi::{invoke_signed, Signer};
+use pinocchio::instruction::{InstructionAccount, InstructionView};
+use pinocchio::{AccountView, ProgramResult};
+
+// 其他程序通过 CPI 调用托管程序的辅助结构 启用 cpi feature 之后可用
+// 用法和 pinocchio-token 一样 填好账户和参数之后调用 invoke / invoke_signed
+
+// 创建托管 把 amount 个 mint_a 存入金库 换取 receive 个 mint_b
+// 账户：
+//   0. `[WRITE, SIGNER]` 创建者
+//   1. `[WRITE]` 托管 PDA seeds = [b"escrow", maker, seed]
+//   2. `[]` mint_a
+//   3. `[]` mint_b
+//   4. `[WRITE]` 创建者的 mint_a 代币账户
+//   5. `[WRITE]` 托管的 mint_a 关联代币账户
+//   6. `[]` 系统程序
+//   7. `[]` SPL 代币程序
+//   8. `[]` 关联代币账户程序
+pub struct Make<'a> {
+    pub maker: &'a AccountView,
+    pub escrow: &'a AccountView,
+    pub mint_a: &'a AccountView,
+    pub mint_b: &'a AccountView,
+    pub maker_ata_a: &'a AccountView,
+    pub vault: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub associated_token_program: &'a AccountView,
+    pub seed: u64,
+    pub receive: u64,
+    pub amount: u64,
+}
+
+impl Make<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.maker.address()),
+            InstructionAccount::writable(self.escrow.address()),
+            InstructionAccount::readonly(self.mint_a.address()),
+            InstructionAccount::readonly(self.mint_b.address()),
+            InstructionAccount::writable(self.maker_ata_a.address()),
+            InstructionAccount::writable(self.vault.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+            InstructionAccount::readonly(self.associated_token_program.address()),
+        ];
+        // [0, seed, receive, amount]
+        let mut data = [0u8; 1 + 8 * 3];
+        data[0] = 0;
+        data[1..9].copy_from_slice(&self.seed.to_le_bytes());
+        data[9..17].copy_from_slice(&self.receive.to_le_bytes());
+        data[17..25].copy_from_slice(&self.amount.to_le_bytes());
+        let instruction = InstructionView {
+            program_id: &crate::ID,
+            accounts: &instruction_accounts,
+            data: &data,
+        };
+        invoke_signed(
+            &instruction,
+            &[
+                self.maker,
+                self.escrow,
+                self.mint_a,
+                self.mint_b,
+                self.maker_ata_a,
+                self.vault,
+                self.system_program,
+                self.token_program,
+                self.associated_token_program,
+            ],
+            signers,
+        )
+    }
+}
+
+// 接受托管 taker 支付 mint_b 换取金库中的 mint_a 缺少的代币账户由 taker 创建
+// 账户：
+//   0. `[WRITE, SIGNER]` 接受者
+//   1. `[WRITE]` 创建者 接收关闭账户退回的租金
+//   2. `[WRITE]` 托管
+//   3. `[]` mint_a
+//   4. `[]` mint_b
+//   5. `[WRITE]` 托管的 mint_a 关联代币账户
+//   6. `[WRITE]` 接受者的 mint_a 关联代币账户
+//   7. `[WRITE]` 接受者的 mint_b 关联代币账户
+//   8. `[WRITE]` 创建者的 mint_b 关联代币账户
+//   9. `[]` 系统程序
+//  10. `[]` SPL 代币程序
+//  11. `[]` 关联代币账户程序
+pub struct Take<'a> {
+    pub taker: &'a AccountView,
+    pub maker: &'a AccountView,
+    pub escrow: &'a AccountView,
+    pub mint_a: &'a AccountView,
+    pub mint_b: &'a AccountView,
+    pub vault: &'a AccountView,
+    pub taker_ata_a: &'a AccountView,
+    pub taker_ata_b: &'a AccountView,
+    pub maker_ata_b: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub associated_token_program: &'a AccountView,
+}
+
+impl Take<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.taker.address()),
+            InstructionAccount::writable(self.maker.address()),
+            InstructionAccount::writable(self.escrow.address()),
+            InstructionAccount::readonly(self.mint_a.address()),
+            InstructionAccount::readonly(self.mint_b.address()),
+            InstructionAccount::writable(self.vault.address()),
+            InstructionAccount::writable(self.taker_ata_a.address()),
+            InstructionAccount::writable(self.taker_ata_b.address()),
+            InstructionAccount::writable(self.maker_ata_b.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+            InstructionAccount::readonly(self.associated_token_program.address()),
+        ];
+        let instruction = InstructionView {
+            program_id: &crate::ID,
+            accounts: &instruction_accounts,
+            data: &[1],
+        };
+        invoke_signed(
+            &instruction,
+            &[
+                self.taker,
+                self.maker,
+                self.escrow,
+                self.mint_a,
+                self.mint_b,
+                self.vault,
+                self.taker_ata_a,
+                self.taker_ata_b,
+                self.maker_ata_b,
+                self.system_program,
+                self.token_program,
+                self.associated_token_program,
+            ],
+            signers,
+        )
+    }
+}
+
+// 取消托管 金库中的 mint_a 退回给创建者
+// 账户：
+//   0. `[WRITE, SIGNER]` 创建者
+//   1. `[WRITE]` 托管
+//   2. `[]` mint_a
+//   3. `[WRITE]` 托管的 mint_a 关联代币账户
+//   4. `[WRITE]` 创建者的 mint_a 关联代币账户
+//   5. `[]` 系统程序
+//   6. `[]` SPL 代币程序
+//   7. `[]` 关联代币账户程序
+pub struct Refund<'a> {
+    pub maker: &'a AccountView,
+    pub escrow: &'a AccountView,
+    pub mint_a: &'a AccountView,
+    pub vault: &'a AccountView,
+    pub maker_ata_a: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub associated_token_program: &'a AccountView,
+}
+
+impl Refund<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.maker.address()),
+            InstructionAccount::writable(self.escrow.address()),
+            InstructionAccount::readonly(self.mint_a.address()),
+            InstructionAccount::writable(self.vault.address()),
+            InstructionAccount::writable(self.maker_ata_a.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+            InstructionAccount::readonly(self.associated_token_program.address()),
+        ];
+        let instruction = InstructionView {
+            program_id: &crate::ID,
+            accounts: &instruction_accounts,
+            data: &[2],
+        };
+        invoke_signed(
+            &instruction,
+            &[
+                self.maker,
+                self.escrow,
+                self.mint_a,
+                self.vault,
+                self.maker_ata_a,
+                self.system_program,
+                self.token_program,
+                self.associated_token_program,
+            ],
+            signers,
+        )
+    }
+}
@@ -1,7 +1,9 @@
-use pinocchio::{AccountView, entrypoint, error::ProgramError, Address, ProgramResult};
+use pinocchio::{AccountView, error::ProgramError, Address, ProgramResult};
 use solana_address::declare_id;
 
-entrypoint!(process_instruction);
+// 其他程序依赖这个 crate 时启用 no-entrypoint 避免入口符号冲突
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
 
 pub mod instructions;
 pub use instructions::*;
@@ -11,10 +13,13 @@ mod errors;
 
 pub use state::*;
 
+#[cfg(feature = "cpi")]
+pub mod cpi;
+
 // 22222222222222222222222222222222222222222222
 declare_id!("22222222222222222222222222222222222222222222");
 
-fn process_instruction(
+pub fn process_instruction(
     _program_id: &Address,
     accounts: &[AccountView],
     instruction_data: &[u8],
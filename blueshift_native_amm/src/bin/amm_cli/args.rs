@@ -0,0 +1,72 @@
+use solana_address::Address;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// 解析 --key value 形式的参数 没有值的参数视为开关
+// 同一个参数可以出现多次 比如 --remaining
+pub struct Args {
+    values: HashMap<String, Vec<String>>,
+}
+
+impl Args {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut values: HashMap<String, Vec<String>> = HashMap::new();
+        let mut iter = args.iter().peekable();
+        while let Some(arg) = iter.next() {
+            let key = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unexpected argument {arg}"))?;
+            let value = match iter.peek() {
+                Some(next) if !next.starts_with("--") => iter.next().unwrap().clone(),
+                _ => String::new(),
+            };
+            values.entry(key.to_string()).or_default().push(value);
+        }
+        Ok(Self { values })
+    }
+
+    pub fn flag(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values
+            .get(key)
+            .and_then(|values| values.last())
+            .map(String::as_str)
+    }
+
+    pub fn all(&self, key: &str) -> &[String] {
+        self.values.get(key).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn required(&self, key: &str) -> Result<&str, String> {
+        self.get(key)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| format!("missing --{key}"))
+    }
+
+    pub fn parse_required<T: FromStr>(&self, key: &str) -> Result<T, String> {
+        self.required(key)?
+            .parse()
+            .map_err(|_| format!("invalid value for --{key}"))
+    }
+
+    pub fn parse_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, String> {
+        match self.get(key) {
+            Some(_) => self.parse_required(key),
+            None => Ok(default),
+        }
+    }
+
+    pub fn address(&self, key: &str) -> Result<Address, String> {
+        Address::from_str(self.required(key)?).map_err(|_| format!("invalid address for --{key}"))
+    }
+
+    pub fn optional_address(&self, key: &str) -> Result<Option<Address>, String> {
+        match self.get(key) {
+            Some(_) => self.address(key).map(Some),
+            None => Ok(None),
+        }
+    }
+}
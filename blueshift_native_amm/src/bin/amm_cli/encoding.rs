@@ -0,0 +1,188 @@
+// 命令行工具离线运行 不引入额外依赖 base64 和 JSON 输出在这里手写
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+pub fn base64_decode(text: &str) -> Result<Vec<u8>, String> {
+    let text: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    if text.len() % 4 != 0 {
+        return Err("base64 length must be a multiple of 4".into());
+    }
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let last = text.len() / 4;
+    for (index, chunk) in text.chunks(4).enumerate() {
+        let mut n = 0u32;
+        let mut padding = 0;
+        for (position, &c) in chunk.iter().enumerate() {
+            // = 只能出现在最后一组的末尾 最多两个 之后不能再有数据
+            let value = match c {
+                b'=' if index + 1 == last && position >= 2 => {
+                    padding += 1;
+                    0
+                }
+                b'=' => return Err("unexpected base64 padding".into()),
+                _ if padding > 0 => return Err("unexpected base64 padding".into()),
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return Err(format!("invalid base64 character {:?}", c as char)),
+            };
+            n = n << 6 | value as u32;
+        }
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        out.extend_from_slice(&bytes[..3 - padding]);
+    }
+    Ok(out)
+}
+
+// 按插入顺序输出的 JSON 对象
+#[derive(Default)]
+pub struct JsonObject {
+    fields: Vec<(String, String)>,
+}
+
+impl JsonObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn string(mut self, key: &str, value: impl ToString) -> Self {
+        self.fields
+            .push((key.to_string(), json_string(&value.to_string())));
+        self
+    }
+
+    // 数字和布尔值原样输出 u64 超过 2^53 时部分 JSON 解析器会丢精度 这里不做处理
+    pub fn raw(mut self, key: &str, value: impl ToString) -> Self {
+        self.fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn array(mut self, key: &str, values: Vec<String>) -> Self {
+        self.fields
+            .push((key.to_string(), format!("[{}]", values.join(", "))));
+        self
+    }
+}
+
+impl std::fmt::Display for JsonObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("{")?;
+        for (i, (key, value)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}: {}", json_string(key), value)?;
+        }
+        f.write_str("}")
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_round_trip() {
+        let data: Vec<u8> = (0..=255).collect();
+        for len in 0..data.len() {
+            let encoded = base64_encode(&data[..len]);
+            assert_eq!(base64_decode(&encoded).unwrap(), &data[..len]);
+        }
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_decode("Zm9v\nYg==").unwrap(), b"foob");
+    }
+
+    #[test]
+    fn base64_rejects_misplaced_padding() {
+        for text in [
+            "A=AA", "AA=A", "====", "A===", "AA==AAAA", "AAA=AAAA", "Zm9v=mFy",
+        ] {
+            assert!(base64_decode(text).is_err(), "{text}");
+        }
+        assert!(base64_decode("AAA").is_err());
+        assert!(base64_decode("AA*=").is_err());
+    }
+
+    // 按 JSON 的规则还原转义 只需要支持 json_string 会输出的转义
+    fn unescape(json: &str) -> String {
+        let inner = json.strip_prefix('"').unwrap().strip_suffix('"').unwrap();
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                assert!(c != '"' && (c as u32) >= 0x20, "unescaped {c:?}");
+                out.push(c);
+                continue;
+            }
+            match chars.next().unwrap() {
+                '"' => out.push('"'),
+                '\\' => out.push('\\'),
+                'n' => out.push('\n'),
+                'u' => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    out.push(char::from_u32(u32::from_str_radix(&code, 16).unwrap()).unwrap());
+                }
+                c => panic!("unknown escape {c:?}"),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn json_string_round_trip() {
+        let cases = [
+            "",
+            "plain",
+            "quote \" and backslash \\",
+            "line\nbreak\ttab\r\u{0}\u{1f}",
+            "非 ASCII 字符 🚀",
+        ];
+        for value in cases {
+            assert_eq!(unescape(&json_string(value)), value);
+        }
+        assert_eq!(json_string("a\"b\\c\n\t"), r#""a\"b\\c\n\u0009""#);
+        assert_eq!(
+            JsonObject::new().string("k\"", "v").raw("n", 1).to_string(),
+            r#"{"k\"": "v", "n": 1}"#
+        );
+    }
+}
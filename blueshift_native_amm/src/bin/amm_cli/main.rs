@@ -0,0 +1,292 @@
+mod args;
+mod encoding;
+
+use args::Args;
+use blueshift_amm_client::{
+    find_config_address, find_mint_lp_address, find_vault_address, AccountMeta, Config, Deposit,
+    Initialize, Instruction, Pool, Swap, Withdraw,
+};
+use blueshift_amm_math::{ConstantProduct, Curve, LiquidityPair};
+use encoding::{base64_decode, base64_encode, JsonObject};
+use solana_address::Address;
+use std::str::FromStr;
+
+// 离线的 AMM 运维工具 不访问网络
+// 所有命令的结果都以一行 JSON 输出到 stdout
+const USAGE: &str = "\
+usage: amm-cli <command> [--key value ...]
+
+commands:
+  pda             --seed <u64> --mint-x <address> --mint-y <address>
+  decode-config   --base64 <data> | --file <path>
+  quote-swap      --reserve-x <u64> --reserve-y <u64> --fee <bps> --amount <u64> [--min <u64>] [--y-to-x]
+  quote-deposit   --reserve-x <u64> --reserve-y <u64> --supply <u64> --amount <u64> [--max-x <u64>] [--max-y <u64>]
+  quote-withdraw  --reserve-x <u64> --reserve-y <u64> --supply <u64> --amount <u64>
+  initialize      --initializer <address> --seed <u64> --fee <bps> --mint-x <address> --mint-y <address> [--authority <address>]
+  deposit         --user <address> --seed <u64> --mint-x <address> --mint-y <address> --amount <u64> --max-x <u64> --max-y <u64> --expiration <i64>
+  withdraw        --user <address> --seed <u64> --mint-x <address> --mint-y <address> --amount <u64> --min-x <u64> --min-y <u64> --expiration <i64>
+  swap            --user <address> --seed <u64> --mint-x <address> --mint-y <address> --amount <u64> --min <u64> --expiration <i64> [--y-to-x]
+
+deposit / withdraw / swap also accept [--expiration-slot <u64>] and repeated [--remaining <address>[:w]],
+deposit / swap accept [--native]. Instruction commands print the unsigned instruction with base64 data.
+swap --native receiving SOL prints {\"instructions\": [...]}: create and initialize a temporary wSOL account, then swap;
+its address is derived from --user and [--temporary-seed <u64>] (default: current time in nanoseconds).";
+
+fn main() {
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = argv.split_first() else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+    let result = Args::parse(rest).and_then(|args| run(command, &args));
+    match result {
+        Ok(output) => println!("{output}"),
+        Err(error) => {
+            eprintln!("error: {error}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(command: &str, args: &Args) -> Result<String, String> {
+    match command {
+        "pda" => pda(args),
+        "decode-config" => decode_config(args),
+        "quote-swap" => quote_swap(args),
+        "quote-deposit" => quote_deposit(args),
+        "quote-withdraw" => quote_withdraw(args),
+        "initialize" => Initialize {
+            initializer: args.address("initializer")?,
+            seed: args.parse_required("seed")?,
+            fee: args.parse_required("fee")?,
+            mint_x: args.address("mint-x")?,
+            mint_y: args.address("mint-y")?,
+            authority: args.optional_address("authority")?,
+        }
+        .instruction()
+        .map(instruction_json)
+        .map_err(|error| error.to_string()),
+        "deposit" => Deposit {
+            user: args.address("user")?,
+            pool: pool(args)?,
+            amount: args.parse_required("amount")?,
+            max_x: args.parse_required("max-x")?,
+            max_y: args.parse_required("max-y")?,
+            expiration: args.parse_required("expiration")?,
+            native: args.flag("native"),
+            expiration_slot: args.parse_or("expiration-slot", 0)?,
+            remaining_accounts: remaining_accounts(args)?,
+        }
+        .instruction()
+        .map(instruction_json)
+        .map_err(|error| error.to_string()),
+        "withdraw" => Withdraw {
+            user: args.address("user")?,
+            pool: pool(args)?,
+            amount: args.parse_required("amount")?,
+            min_x: args.parse_required("min-x")?,
+            min_y: args.parse_required("min-y")?,
+            expiration: args.parse_required("expiration")?,
+            expiration_slot: args.parse_or("expiration-slot", 0)?,
+            remaining_accounts: remaining_accounts(args)?,
+        }
+        .instruction()
+        .map(instruction_json)
+        .map_err(|error| error.to_string()),
+        "swap" => Swap {
+            user: args.address("user")?,
+            pool: pool(args)?,
+            is_x: !args.flag("y-to-x"),
+            amount: args.parse_required("amount")?,
+            min: args.parse_required("min")?,
+            expiration: args.parse_required("expiration")?,
+            native: args.flag("native"),
+            temporary_seed: args.parse_or("temporary-seed", temporary_seed())?,
+            expiration_slot: args.parse_or("expiration-slot", 0)?,
+            remaining_accounts: remaining_accounts(args)?,
+        }
+        .instructions()
+        .map(instructions_json)
+        .map_err(|error| error.to_string()),
+        "help" | "--help" => Ok(USAGE.to_string()),
+        _ => Err(format!("unknown command {command}\n{USAGE}")),
+    }
+}
+
+fn pda(args: &Args) -> Result<String, String> {
+    let seed: u64 = args.parse_required("seed")?;
+    let mint_x = args.address("mint-x")?;
+    let mint_y = args.address("mint-y")?;
+    let (config, config_bump) = find_config_address(seed, &mint_x, &mint_y);
+    let (mint_lp, lp_bump) = find_mint_lp_address(&config);
+    Ok(JsonObject::new()
+        .string("config", &config)
+        .raw("config_bump", config_bump)
+        .string("mint_lp", &mint_lp)
+        .raw("lp_bump", lp_bump)
+        .string("vault_x", find_vault_address(&config, &mint_x))
+        .string("vault_y", find_vault_address(&config, &mint_y))
+        .to_string())
+}
+
+fn decode_config(args: &Args) -> Result<String, String> {
+    let data = match (args.get("base64"), args.get("file")) {
+        (Some(text), None) => base64_decode(text)?,
+        (None, Some(path)) => std::fs::read(path).map_err(|error| format!("{path}: {error}"))?,
+        _ => return Err("expected exactly one of --base64 or --file".into()),
+    };
+    let config = Config::from_bytes(&data).map_err(|error| error.to_string())?;
+    Ok(JsonObject::new()
+        .raw("version", config.version)
+        .string("state", format!("{:?}", config.state))
+        .raw("seed", config.seed)
+        .string("authority", &config.authority)
+        .string("guardian", &config.guardian)
+        .string("mint_x", &config.mint_x)
+        .string("mint_y", &config.mint_y)
+        .string("mint_lp", &config.mint_lp)
+        .string("vault_x", &config.vault_x)
+        .string("vault_y", &config.vault_y)
+        .raw("fee", config.fee)
+        .raw("config_bump", config.config_bump)
+        .string("oracle", &config.oracle)
+        .raw("oracle_max_deviation", config.oracle_max_deviation)
+        .raw("oracle_max_age", config.oracle_max_age)
+        .raw("decimals_x", config.decimals_x)
+        .raw("decimals_y", config.decimals_y)
+        .raw("permissioned", config.permissioned)
+        .string("hook_program", &config.hook_program)
+        .raw("reserve_x", config.reserve_x)
+        .raw("reserve_y", config.reserve_y)
+        .string("initializer", &config.initializer)
+        .raw("twamm", config.twamm)
+        .raw("twamm_escrow_x", config.twamm_escrow_x)
+        .raw("twamm_escrow_y", config.twamm_escrow_y)
+        .raw("slot_price_band", config.slot_price_band)
+        .raw("open_slot", config.open_slot)
+        .raw("open_reserve_x", config.open_reserve_x)
+        .raw("open_reserve_y", config.open_reserve_y)
+        .to_string())
+}
+
+// 报价直接使用链上程序的曲线实现 结果和链上成交一致
+fn quote_swap(args: &Args) -> Result<String, String> {
+    let curve = ConstantProduct::init(
+        args.parse_required("reserve-x")?,
+        args.parse_required("reserve-y")?,
+        args.parse_required("fee")?,
+    )
+    .map_err(|error| error.to_string())?;
+    let p = match args.flag("y-to-x") {
+        true => LiquidityPair::Y,
+        false => LiquidityPair::X,
+    };
+    let (amount_in, fee, amount_out) = curve
+        .quote_swap(p, args.parse_required("amount")?, args.parse_or("min", 0)?)
+        .map_err(|error| error.to_string())?;
+    Ok(JsonObject::new()
+        .raw("amount_in", amount_in)
+        .raw("fee", fee)
+        .raw("amount_out", amount_out)
+        .to_string())
+}
+
+fn quote_deposit(args: &Args) -> Result<String, String> {
+    let curve = ConstantProduct::new(
+        args.parse_required("reserve-x")?,
+        args.parse_required("reserve-y")?,
+        0,
+    )
+    .map_err(|error| error.to_string())?;
+    let (x, y) = curve
+        .quote_deposit(
+            args.parse_required("supply")?,
+            args.parse_required("amount")?,
+            args.parse_or("max-x", u64::MAX)?,
+            args.parse_or("max-y", u64::MAX)?,
+        )
+        .map_err(|error| error.to_string())?;
+    Ok(JsonObject::new().raw("x", x).raw("y", y).to_string())
+}
+
+fn quote_withdraw(args: &Args) -> Result<String, String> {
+    let curve = ConstantProduct::new(
+        args.parse_required("reserve-x")?,
+        args.parse_required("reserve-y")?,
+        0,
+    )
+    .map_err(|error| error.to_string())?;
+    let (x, y) = curve
+        .quote_withdraw(
+            args.parse_required("supply")?,
+            args.parse_required("amount")?,
+        )
+        .map_err(|error| error.to_string())?;
+    Ok(JsonObject::new().raw("x", x).raw("y", y).to_string())
+}
+
+fn pool(args: &Args) -> Result<Pool, String> {
+    Ok(Pool::new(
+        args.parse_required("seed")?,
+        args.address("mint-x")?,
+        args.address("mint-y")?,
+    ))
+}
+
+// --remaining <address> 只读 --remaining <address>:w 可写
+fn remaining_accounts(args: &Args) -> Result<Vec<AccountMeta>, String> {
+    args.all("remaining")
+        .iter()
+        .map(|value| {
+            let (address, writable) = match value.strip_suffix(":w") {
+                Some(address) => (address, true),
+                None => (value.as_str(), false),
+            };
+            let address = Address::from_str(address)
+                .map_err(|_| format!("invalid address for --remaining: {value}"))?;
+            Ok(match writable {
+                true => AccountMeta::new(address, false),
+                false => AccountMeta::new_readonly(address, false),
+            })
+        })
+        .collect()
+}
+
+// 只有一条指令时和其他命令的输出一致 多条时按执行顺序放在 instructions 中
+fn instructions_json(instructions: Vec<Instruction>) -> String {
+    match <[Instruction; 1]>::try_from(instructions) {
+        Ok([instruction]) => instruction_json(instruction),
+        Err(instructions) => JsonObject::new()
+            .array(
+                "instructions",
+                instructions.into_iter().map(instruction_json).collect(),
+            )
+            .to_string(),
+    }
+}
+
+// 临时 wSOL 账户的种子 每次调用都不同 避免地址被提前占用
+fn temporary_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+fn instruction_json(instruction: Instruction) -> String {
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|meta| {
+            JsonObject::new()
+                .string("pubkey", &meta.pubkey)
+                .raw("is_signer", meta.is_signer)
+                .raw("is_writable", meta.is_writable)
+                .to_string()
+        })
+        .collect();
+    JsonObject::new()
+        .string("program_id", &instruction.program_id)
+        .array("accounts", accounts)
+        .string("data", base64_encode(&instruction.data))
+        .to_string()
+}
@@ -0,0 +1,329 @@
+use super::{invoke_amm, invoke_amm_with_remaining};
+use pinocchio::cpi::Signer;
+use pinocchio::instruction::InstructionAccount;
+use pinocchio::{AccountView, Address, ProgramResult};
+
+// 管理指令 authority 必须是 config 中记录的管理员（或者多签 PDA 由调用方签名）
+
+// 设置预言机 传入系统程序表示关闭预言机校验
+// 账户：
+//   0. `[SIGNER]` 管理员
+//   1. `[WRITE]` config
+//   2. `[]` 预言机价格账户
+//   3. `[]` X 铸币
+//   4. `[]` Y 铸币
+pub struct SetOracle<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub oracle: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+    pub max_deviation: u16,
+    pub max_age: u64,
+}
+
+impl SetOracle<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::readonly(self.oracle.address()),
+            InstructionAccount::readonly(self.mint_x.address()),
+            InstructionAccount::readonly(self.mint_y.address()),
+        ];
+        // [4, max_deviation, max_age]
+        let mut data = [0u8; 1 + 2 + 8];
+        data[0] = 4;
+        data[1..3].copy_from_slice(&self.max_deviation.to_le_bytes());
+        data[3..11].copy_from_slice(&self.max_age.to_le_bytes());
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.authority,
+                self.config,
+                self.oracle,
+                self.mint_x,
+                self.mint_y,
+            ],
+            &data,
+            signers,
+        )
+    }
+}
+
+// 切换池子的状态 守护者只能暂停池子
+// 账户：
+//   0. `[SIGNER]` 管理员或者守护者
+//   1. `[WRITE]` config
+//   .. 可选账户：TWAMM 账户
+pub struct SetState<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub remaining: &'a [AccountView],
+    pub state: u8,
+}
+
+impl SetState<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.config.address()),
+        ];
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[self.authority, self.config],
+            self.remaining,
+            &[5, self.state],
+            signers,
+        )
+    }
+}
+
+// 打开或者关闭许可池
+// 账户：
+//   0. `[SIGNER]` 管理员
+//   1. `[WRITE]` config
+pub struct SetPermissioned<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub permissioned: bool,
+}
+
+impl SetPermissioned<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.config.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[self.authority, self.config],
+            &[6, self.permissioned as u8],
+            signers,
+        )
+    }
+}
+
+// 把钱包加入白名单
+// 账户：
+//   0. `[WRITE, SIGNER]` 付款方 支付白名单记录的租金
+//   1. `[SIGNER]` 管理员
+//   2. `[]` config
+//   3. `[]` 被加入白名单的钱包
+//   4. `[WRITE]` 白名单记录 PDA
+//   5. `[]` 系统程序
+pub struct AddTrader<'a> {
+    pub payer: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub trader: &'a AccountView,
+    pub entry: &'a AccountView,
+    pub system_program: &'a AccountView,
+}
+
+impl AddTrader<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.payer.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::readonly(self.config.address()),
+            InstructionAccount::readonly(self.trader.address()),
+            InstructionAccount::writable(self.entry.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.payer,
+                self.authority,
+                self.config,
+                self.trader,
+                self.entry,
+                self.system_program,
+            ],
+            &[7],
+            signers,
+        )
+    }
+}
+
+// 把钱包移出白名单 租金返还给管理员
+// 账户：
+//   0. `[WRITE, SIGNER]` 管理员
+//   1. `[]` config
+//   2. `[WRITE]` 白名单记录
+pub struct RemoveTrader<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub entry: &'a AccountView,
+}
+
+impl RemoveTrader<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.authority.address()),
+            InstructionAccount::readonly(self.config.address()),
+            InstructionAccount::writable(self.entry.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[self.authority, self.config, self.entry],
+            &[8],
+            signers,
+        )
+    }
+}
+
+// 注册 hook 程序 传入系统程序表示取消 hook
+// 账户：
+//   0. `[SIGNER]` 管理员
+//   1. `[WRITE]` config
+//   2. `[]` hook 程序
+pub struct SetHook<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub hook_program: &'a AccountView,
+}
+
+impl SetHook<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::readonly(self.hook_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[self.authority, self.config, self.hook_program],
+            &[9],
+            signers,
+        )
+    }
+}
+
+// 设置每个 slot 的价格波动范围（基点） 0 表示不限制
+// 账户：
+//   0. `[SIGNER]` 管理员
+//   1. `[WRITE]` config
+pub struct SetPriceBand<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub band: u16,
+}
+
+impl SetPriceBand<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.config.address()),
+        ];
+        let band = self.band.to_le_bytes();
+        invoke_amm(
+            &instruction_accounts,
+            &[self.authority, self.config],
+            &[26, band[0], band[1]],
+            signers,
+        )
+    }
+}
+
+// 转移管理员
+// 账户：
+//   0. `[SIGNER]` 当前的管理员
+//   1. `[WRITE]` config
+pub struct SetAuthority<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub new_authority: &'a Address,
+}
+
+impl SetAuthority<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.config.address()),
+        ];
+        let mut data = [0u8; 1 + 32];
+        data[0] = 31;
+        data[1..33].copy_from_slice(self.new_authority.as_ref());
+        invoke_amm(
+            &instruction_accounts,
+            &[self.authority, self.config],
+            &data,
+            signers,
+        )
+    }
+}
+
+// 设置守护者 全 0 地址表示取消守护者
+// 账户：
+//   0. `[SIGNER]` 管理员
+//   1. `[WRITE]` config
+pub struct SetGuardian<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub guardian: &'a Address,
+}
+
+impl SetGuardian<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable(self.config.address()),
+        ];
+        let mut data = [0u8; 1 + 32];
+        data[0] = 32;
+        data[1..33].copy_from_slice(self.guardian.as_ref());
+        invoke_amm(
+            &instruction_accounts,
+            &[self.authority, self.config],
+            &data,
+            signers,
+        )
+    }
+}
@@ -0,0 +1,262 @@
+use super::invoke_amm;
+use pinocchio::cpi::Signer;
+use pinocchio::instruction::InstructionAccount;
+use pinocchio::{AccountView, ProgramResult};
+
+// 创建池子的流动性挖矿农场
+// 账户：
+//   0. `[WRITE, SIGNER]` 管理员 支付农场账户的租金
+//   1. `[]` config
+//   2. `[WRITE]` 农场 PDA
+//   3. `[]` 奖励代币的铸币
+//   4. `[]` 系统程序
+pub struct CreateFarm<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub farm: &'a AccountView,
+    pub reward_mint: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub reward_rate: u64,
+}
+
+impl CreateFarm<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.authority.address()),
+            InstructionAccount::readonly(self.config.address()),
+            InstructionAccount::writable(self.farm.address()),
+            InstructionAccount::readonly(self.reward_mint.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        let mut data = [0u8; 1 + 8];
+        data[0] = 17;
+        data[1..9].copy_from_slice(&self.reward_rate.to_le_bytes());
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.authority,
+                self.config,
+                self.farm,
+                self.reward_mint,
+                self.system_program,
+            ],
+            &data,
+            signers,
+        )
+    }
+}
+
+// 向农场注入奖励并调整发放速度
+// 账户：
+//   0. `[SIGNER]` 管理员
+//   1. `[]` config
+//   2. `[WRITE]` 农场
+//   3. `[WRITE]` 管理员的奖励代币账户
+//   4. `[WRITE]` 农场的奖励代币账户
+//   5. `[]` SPL 代币程序
+pub struct FundRewards<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub farm: &'a AccountView,
+    pub source: &'a AccountView,
+    pub reward_vault: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub amount: u64,
+    pub reward_rate: u64,
+}
+
+impl FundRewards<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::readonly(self.config.address()),
+            InstructionAccount::writable(self.farm.address()),
+            InstructionAccount::writable(self.source.address()),
+            InstructionAccount::writable(self.reward_vault.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        let mut data = [0u8; 1 + 8 * 2];
+        data[0] = 18;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9..17].copy_from_slice(&self.reward_rate.to_le_bytes());
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.authority,
+                self.config,
+                self.farm,
+                self.source,
+                self.reward_vault,
+                self.token_program,
+            ],
+            &data,
+            signers,
+        )
+    }
+}
+
+// 质押 LP 第一次质押时创建质押记录
+// 账户：
+//   0. `[WRITE, SIGNER]` 用户
+//   1. `[WRITE]` 农场
+//   2. `[WRITE]` 质押记录 PDA
+//   3. `[WRITE]` 用户的 LP 代币账户
+//   4. `[WRITE]` 农场的 LP 代币账户
+//   5. `[]` SPL 代币程序
+//   6. `[]` 系统程序
+pub struct Stake<'a> {
+    pub user: &'a AccountView,
+    pub farm: &'a AccountView,
+    pub position: &'a AccountView,
+    pub user_lp_ata: &'a AccountView,
+    pub stake_vault: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub amount: u64,
+}
+
+impl Stake<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.user.address()),
+            InstructionAccount::writable(self.farm.address()),
+            InstructionAccount::writable(self.position.address()),
+            InstructionAccount::writable(self.user_lp_ata.address()),
+            InstructionAccount::writable(self.stake_vault.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        let mut data = [0u8; 1 + 8];
+        data[0] = 19;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.user,
+                self.farm,
+                self.position,
+                self.user_lp_ata,
+                self.stake_vault,
+                self.token_program,
+                self.system_program,
+            ],
+            &data,
+            signers,
+        )
+    }
+}
+
+// 取回质押的 LP
+// 账户：
+//   0. `[SIGNER]` 用户
+//   1. `[WRITE]` 农场
+//   2. `[WRITE]` 质押记录
+//   3. `[WRITE]` 用户的 LP 代币账户
+//   4. `[WRITE]` 农场的 LP 代币账户
+//   5. `[]` SPL 代币程序
+pub struct Unstake<'a> {
+    pub user: &'a AccountView,
+    pub farm: &'a AccountView,
+    pub position: &'a AccountView,
+    pub user_lp_ata: &'a AccountView,
+    pub stake_vault: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub amount: u64,
+}
+
+impl Unstake<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.user.address()),
+            InstructionAccount::writable(self.farm.address()),
+            InstructionAccount::writable(self.position.address()),
+            InstructionAccount::writable(self.user_lp_ata.address()),
+            InstructionAccount::writable(self.stake_vault.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        let mut data = [0u8; 1 + 8];
+        data[0] = 20;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.user,
+                self.farm,
+                self.position,
+                self.user_lp_ata,
+                self.stake_vault,
+                self.token_program,
+            ],
+            &data,
+            signers,
+        )
+    }
+}
+
+// 领取挖矿奖励
+// 账户：
+//   0. `[SIGNER]` 用户
+//   1. `[WRITE]` 农场
+//   2. `[WRITE]` 质押记录
+//   3. `[WRITE]` 用户接收奖励的代币账户
+//   4. `[WRITE]` 农场的奖励代币账户
+//   5. `[]` SPL 代币程序
+pub struct Claim<'a> {
+    pub user: &'a AccountView,
+    pub farm: &'a AccountView,
+    pub position: &'a AccountView,
+    pub user_reward_ata: &'a AccountView,
+    pub reward_vault: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl Claim<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.user.address()),
+            InstructionAccount::writable(self.farm.address()),
+            InstructionAccount::writable(self.position.address()),
+            InstructionAccount::writable(self.user_reward_ata.address()),
+            InstructionAccount::writable(self.reward_vault.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.user,
+                self.farm,
+                self.position,
+                self.user_reward_ata,
+                self.reward_vault,
+                self.token_program,
+            ],
+            &[21],
+            signers,
+        )
+    }
+}
@@ -0,0 +1,74 @@
+use pinocchio::cpi::{invoke_signed, invoke_signed_with_slice, Signer};
+use pinocchio::instruction::{InstructionAccount, InstructionView};
+use pinocchio::{AccountView, ProgramResult};
+
+// 其他程序通过 CPI 调用 AMM 的辅助结构 启用 cpi feature 之后可用
+// 用法和 pinocchio-token 一样 填好账户和参数之后调用 invoke / invoke_signed
+// 账户顺序和链上指令完全一致 可选账户放在 remaining 中 保留调用方传入时的签名和可写属性
+// 例如策略程序用自己的 PDA 作为用户存入流动性：
+// Deposit { user: vault_authority, .., amount, max_x, max_y, expiration, .. }.invoke_signed(&[signer])
+
+pub mod admin;
+pub mod farm;
+pub mod multisig;
+pub mod pool;
+pub mod quote;
+pub mod settings;
+pub mod twamm;
+
+pub use admin::*;
+pub use farm::*;
+pub use multisig::*;
+pub use pool::*;
+pub use quote::*;
+pub use settings::*;
+pub use twamm::*;
+
+// 账户数量固定的指令 账户和数据都放在栈上
+#[inline(always)]
+fn invoke_amm<const ACCOUNTS: usize>(
+    instruction_accounts: &[InstructionAccount; ACCOUNTS],
+    accounts: &[&AccountView; ACCOUNTS],
+    data: &[u8],
+    signers: &[Signer],
+) -> ProgramResult {
+    let instruction = InstructionView {
+        program_id: &crate::ID,
+        accounts: instruction_accounts,
+        data,
+    };
+    invoke_signed(&instruction, accounts, signers)
+}
+
+// 带可选账户的指令 可选账户接在固定账户后面
+#[inline(always)]
+fn invoke_amm_with_remaining(
+    instruction_accounts: &[InstructionAccount],
+    accounts: &[&AccountView],
+    remaining: &[AccountView],
+    data: &[u8],
+    signers: &[Signer],
+) -> ProgramResult {
+    let mut all_instruction_accounts = Vec::with_capacity(accounts.len() + remaining.len());
+    all_instruction_accounts.extend_from_slice(instruction_accounts);
+    all_instruction_accounts.extend(remaining.iter().map(InstructionAccount::from));
+    let mut all_accounts = Vec::with_capacity(accounts.len() + remaining.len());
+    all_accounts.extend_from_slice(accounts);
+    all_accounts.extend(remaining.iter());
+
+    let instruction = InstructionView {
+        program_id: &crate::ID,
+        accounts: &all_instruction_accounts,
+        data,
+    };
+    invoke_signed_with_slice(&instruction, &all_accounts, signers)
+}
+
+// 用户的代币账户 使用原生 SOL 支付时这个位置是系统程序 不能标记为可写
+#[inline(always)]
+fn user_token_account(account: &AccountView) -> InstructionAccount<'_> {
+    match account.address().eq(&pinocchio_system::ID) {
+        true => InstructionAccount::readonly(account.address()),
+        false => InstructionAccount::writable(account.address()),
+    }
+}
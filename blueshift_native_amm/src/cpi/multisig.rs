@@ -0,0 +1,160 @@
+use super::{invoke_amm, invoke_amm_with_remaining};
+use pinocchio::cpi::Signer;
+use pinocchio::instruction::InstructionAccount;
+use pinocchio::{AccountView, Address, ProgramResult};
+
+// 创建多签 signers 最多 MAX_SIGNERS 个
+// 账户：
+//   0. `[WRITE, SIGNER]` 创建者 支付多签账户的租金
+//   1. `[WRITE]` 多签 PDA
+//   2. `[]` 系统程序
+pub struct CreateMultisig<'a> {
+    pub creator: &'a AccountView,
+    pub multisig: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub seed: u64,
+    pub threshold: u8,
+    pub signers: &'a [Address],
+}
+
+impl CreateMultisig<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.creator.address()),
+            InstructionAccount::writable(self.multisig.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        // [27, seed, threshold, signers]
+        let mut data = Vec::with_capacity(1 + 8 + 1 + size_of_val(self.signers));
+        data.push(27);
+        data.extend_from_slice(&self.seed.to_le_bytes());
+        data.push(self.threshold);
+        for signer in self.signers {
+            data.extend_from_slice(signer.as_ref());
+        }
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[self.creator, self.multisig, self.system_program],
+            &[],
+            &data,
+            signers,
+        )
+    }
+}
+
+// 提交一个由多签执行的 AMM 指令 提交即视为批准
+// 账户：
+//   0. `[WRITE, SIGNER]` 提交提案的多签成员 支付提案账户的租金
+//   1. `[WRITE]` 多签
+//   2. `[WRITE]` 提案 PDA
+//   3. `[]` 系统程序
+//   .. 提案执行时需要的全部账户
+// instruction_data 是提案要执行的 AMM 指令数据 包括开头的指令编号
+pub struct Propose<'a> {
+    pub proposer: &'a AccountView,
+    pub multisig: &'a AccountView,
+    pub proposal: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub remaining: &'a [AccountView],
+    pub instruction_data: &'a [u8],
+}
+
+impl Propose<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.proposer.address()),
+            InstructionAccount::writable(self.multisig.address()),
+            InstructionAccount::writable(self.proposal.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        let mut data = Vec::with_capacity(1 + self.instruction_data.len());
+        data.push(28);
+        data.extend_from_slice(self.instruction_data);
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[
+                self.proposer,
+                self.multisig,
+                self.proposal,
+                self.system_program,
+            ],
+            self.remaining,
+            &data,
+            signers,
+        )
+    }
+}
+
+// 多签成员批准提案
+// 账户：
+//   0. `[SIGNER]` 多签成员
+//   1. `[]` 多签
+//   2. `[WRITE]` 提案
+pub struct Approve<'a> {
+    pub signer: &'a AccountView,
+    pub multisig: &'a AccountView,
+    pub proposal: &'a AccountView,
+}
+
+impl Approve<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.signer.address()),
+            InstructionAccount::readonly(self.multisig.address()),
+            InstructionAccount::writable(self.proposal.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[self.signer, self.multisig, self.proposal],
+            &[29],
+            signers,
+        )
+    }
+}
+
+// 执行批准数量达到门槛的提案 任何人都可以调用
+// 账户：
+//   0. `[]` 多签
+//   1. `[WRITE]` 提案
+//   .. 提案记录的全部账户 顺序和提交时一致
+pub struct ExecuteProposal<'a> {
+    pub multisig: &'a AccountView,
+    pub proposal: &'a AccountView,
+    pub remaining: &'a [AccountView],
+}
+
+impl ExecuteProposal<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly(self.multisig.address()),
+            InstructionAccount::writable(self.proposal.address()),
+        ];
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[self.multisig, self.proposal],
+            self.remaining,
+            &[30],
+            signers,
+        )
+    }
+}
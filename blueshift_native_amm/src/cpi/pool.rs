@@ -0,0 +1,537 @@
+use super::{invoke_amm, invoke_amm_with_remaining, user_token_account};
+use pinocchio::cpi::Signer;
+use pinocchio::instruction::InstructionAccount;
+use pinocchio::{AccountView, Address, ProgramResult};
+
+// 创建池子
+// 账户：
+//   0. `[WRITE, SIGNER]` 创建者 支付租金
+//   1. `[WRITE]` LP 铸币 PDA
+//   2. `[WRITE]` config PDA
+//   3. `[]` 系统程序
+//   4. `[]` SPL 代币程序
+//   5. `[WRITE]` 全局设置 接收创建费
+pub struct Initialize<'a> {
+    pub initializer: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub config: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub settings: &'a AccountView,
+    pub seed: u64,
+    pub fee: u16,
+    pub mint_x: &'a Address,
+    pub mint_y: &'a Address,
+    pub config_bump: u8,
+    pub lp_bump: u8,
+    // None 表示创建一个不可变的池子
+    pub authority: Option<&'a Address>,
+}
+
+impl Initialize<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.initializer.address()),
+            InstructionAccount::writable(self.mint_lp.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+            InstructionAccount::writable(self.settings.address()),
+        ];
+        // [0, seed, fee, mint_x, mint_y, config_bump, lp_bump, authority]
+        let mut data = [0u8; 1 + 8 + 2 + 32 + 32 + 1 + 1 + 32];
+        data[1..9].copy_from_slice(&self.seed.to_le_bytes());
+        data[9..11].copy_from_slice(&self.fee.to_le_bytes());
+        data[11..43].copy_from_slice(self.mint_x.as_ref());
+        data[43..75].copy_from_slice(self.mint_y.as_ref());
+        data[75] = self.config_bump;
+        data[76] = self.lp_bump;
+        let len = match self.authority {
+            Some(authority) => {
+                data[77..109].copy_from_slice(authority.as_ref());
+                data.len()
+            }
+            None => 77,
+        };
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.initializer,
+                self.mint_lp,
+                self.config,
+                self.system_program,
+                self.token_program,
+                self.settings,
+            ],
+            &data[..len],
+            signers,
+        )
+    }
+}
+
+// 存入 x y 铸造 amount 个 LP
+// 账户：
+//   0. `[WRITE, SIGNER]` 用户
+//   1. `[WRITE]` LP 铸币
+//   2. `[WRITE]` 金库 X
+//   3. `[WRITE]` 金库 Y
+//   4. `[WRITE]` 用户的 X 代币账户 使用原生 SOL 支付时传入系统程序
+//   5. `[WRITE]` 用户的 Y 代币账户 使用原生 SOL 支付时传入系统程序
+//   6. `[WRITE]` 用户的 LP 代币账户
+//   7. `[WRITE]` config
+//   8. `[]` SPL 代币程序
+//   .. 可选账户：TWAMM 账户、白名单记录、LP 仓位记录
+pub struct Deposit<'a> {
+    pub user: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub user_x_ata: &'a AccountView,
+    pub user_y_ata: &'a AccountView,
+    pub user_lp_ata: &'a AccountView,
+    pub config: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub remaining: &'a [AccountView],
+    pub amount: u64,
+    pub max_x: u64,
+    pub max_y: u64,
+    pub expiration: i64,
+    pub native: bool,
+    // 0 表示不限制
+    pub expiration_slot: u64,
+}
+
+impl Deposit<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.user.address()),
+            InstructionAccount::writable(self.mint_lp.address()),
+            InstructionAccount::writable(self.vault_x.address()),
+            InstructionAccount::writable(self.vault_y.address()),
+            user_token_account(self.user_x_ata),
+            user_token_account(self.user_y_ata),
+            InstructionAccount::writable(self.user_lp_ata.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        // [1, amount, max_x, max_y, expiration, native, expiration_slot]
+        let mut data = [0u8; 1 + 8 * 4 + 1 + 8];
+        data[0] = 1;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9..17].copy_from_slice(&self.max_x.to_le_bytes());
+        data[17..25].copy_from_slice(&self.max_y.to_le_bytes());
+        data[25..33].copy_from_slice(&self.expiration.to_le_bytes());
+        data[33] = self.native as u8;
+        data[34..42].copy_from_slice(&self.expiration_slot.to_le_bytes());
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[
+                self.user,
+                self.mint_lp,
+                self.vault_x,
+                self.vault_y,
+                self.user_x_ata,
+                self.user_y_ata,
+                self.user_lp_ata,
+                self.config,
+                self.token_program,
+            ],
+            self.remaining,
+            &data,
+            signers,
+        )
+    }
+}
+
+// 销毁 amount 个 LP 取回 x y
+// 账户：
+//   0. `[WRITE, SIGNER]` 用户
+//   1. `[WRITE]` LP 铸币
+//   2. `[WRITE]` 金库 X
+//   3. `[WRITE]` 金库 Y
+//   4. `[WRITE]` 用户的 X 代币账户
+//   5. `[WRITE]` 用户的 Y 代币账户
+//   6. `[WRITE]` 用户的 LP 代币账户
+//   7. `[WRITE]` config
+//   8. `[]` SPL 代币程序
+//   .. 可选账户：TWAMM 账户、白名单记录、LP 仓位记录
+pub struct Withdraw<'a> {
+    pub user: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub user_x_ata: &'a AccountView,
+    pub user_y_ata: &'a AccountView,
+    pub user_lp_ata: &'a AccountView,
+    pub config: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub remaining: &'a [AccountView],
+    pub amount: u64,
+    pub min_x: u64,
+    pub min_y: u64,
+    pub expiration: i64,
+    // 0 表示不限制
+    pub expiration_slot: u64,
+}
+
+impl Withdraw<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.user.address()),
+            InstructionAccount::writable(self.mint_lp.address()),
+            InstructionAccount::writable(self.vault_x.address()),
+            InstructionAccount::writable(self.vault_y.address()),
+            InstructionAccount::writable(self.user_x_ata.address()),
+            InstructionAccount::writable(self.user_y_ata.address()),
+            InstructionAccount::writable(self.user_lp_ata.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        // [2, amount, min_x, min_y, expiration, expiration_slot]
+        let mut data = [0u8; 1 + 8 * 5];
+        data[0] = 2;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9..17].copy_from_slice(&self.min_x.to_le_bytes());
+        data[17..25].copy_from_slice(&self.min_y.to_le_bytes());
+        data[25..33].copy_from_slice(&self.expiration.to_le_bytes());
+        data[33..41].copy_from_slice(&self.expiration_slot.to_le_bytes());
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[
+                self.user,
+                self.mint_lp,
+                self.vault_x,
+                self.vault_y,
+                self.user_x_ata,
+                self.user_y_ata,
+                self.user_lp_ata,
+                self.config,
+                self.token_program,
+            ],
+            self.remaining,
+            &data,
+            signers,
+        )
+    }
+}
+
+// 交换 is_x 为 true 时支付 x 获取 y
+// 账户：
+//   0. `[WRITE, SIGNER]` 用户
+//   1. `[WRITE]` 用户的 X 代币账户 使用原生 SOL 支付时传入系统程序
+//   2. `[WRITE]` 用户的 Y 代币账户 使用原生 SOL 支付时传入系统程序
+//   3. `[WRITE]` 金库 X
+//   4. `[WRITE]` 金库 Y
+//   5. `[WRITE]` config
+//   6. `[]` SPL 代币程序
+//   .. 可选账户：TWAMM 账户、预言机、白名单记录、hook 程序和 hook 需要的账户
+pub struct Swap<'a> {
+    pub user: &'a AccountView,
+    pub user_x_ata: &'a AccountView,
+    pub user_y_ata: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub config: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub remaining: &'a [AccountView],
+    pub is_x: bool,
+    pub amount: u64,
+    pub min: u64,
+    pub expiration: i64,
+    pub native: bool,
+    // 0 表示不限制
+    pub expiration_slot: u64,
+}
+
+impl Swap<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.user.address()),
+            user_token_account(self.user_x_ata),
+            user_token_account(self.user_y_ata),
+            InstructionAccount::writable(self.vault_x.address()),
+            InstructionAccount::writable(self.vault_y.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        // [3, is_x, amount, min, expiration, native, expiration_slot]
+        let mut data = [0u8; 1 + 1 + 8 * 3 + 1 + 8];
+        data[0] = 3;
+        data[1] = self.is_x as u8;
+        data[2..10].copy_from_slice(&self.amount.to_le_bytes());
+        data[10..18].copy_from_slice(&self.min.to_le_bytes());
+        data[18..26].copy_from_slice(&self.expiration.to_le_bytes());
+        data[26] = self.native as u8;
+        data[27..35].copy_from_slice(&self.expiration_slot.to_le_bytes());
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[
+                self.user,
+                self.user_x_ata,
+                self.user_y_ata,
+                self.vault_x,
+                self.vault_y,
+                self.config,
+                self.token_program,
+            ],
+            self.remaining,
+            &data,
+            signers,
+        )
+    }
+}
+
+// 把储备同步为金库的实际余额
+// 账户：
+//   0. `[WRITE]` config
+//   1. `[]` LP 铸币
+//   2. `[]` 金库 X
+//   3. `[]` 金库 Y
+//   4. `[]` SPL 代币程序
+pub struct Sync<'a> {
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl Sync<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::readonly(self.mint_lp.address()),
+            InstructionAccount::readonly(self.vault_x.address()),
+            InstructionAccount::readonly(self.vault_y.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.config,
+                self.mint_lp,
+                self.vault_x,
+                self.vault_y,
+                self.token_program,
+            ],
+            &[10],
+            signers,
+        )
+    }
+}
+
+// 把金库中超出储备的代币转给管理员指定的账户
+// 账户：
+//   0. `[SIGNER]` 管理员
+//   1. `[]` config
+//   2. `[WRITE]` 金库 X
+//   3. `[WRITE]` 金库 Y
+//   4. `[WRITE]` 接收多余 X 的代币账户
+//   5. `[WRITE]` 接收多余 Y 的代币账户
+//   6. `[]` SPL 代币程序
+pub struct Skim<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub destination_x: &'a AccountView,
+    pub destination_y: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl Skim<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::readonly(self.config.address()),
+            InstructionAccount::writable(self.vault_x.address()),
+            InstructionAccount::writable(self.vault_y.address()),
+            InstructionAccount::writable(self.destination_x.address()),
+            InstructionAccount::writable(self.destination_y.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.authority,
+                self.config,
+                self.vault_x,
+                self.vault_y,
+                self.destination_x,
+                self.destination_y,
+                self.token_program,
+            ],
+            &[11],
+            signers,
+        )
+    }
+}
+
+// 关闭已经没有流动性的池子 租金返还给 closer 金库中剩余的代币转入 destination
+// 账户：
+//   0. `[WRITE, SIGNER]` 管理员或者池子的创建者
+//   1. `[WRITE]` config
+//   2. `[]` LP 铸币
+//   3. `[WRITE]` 金库 X
+//   4. `[WRITE]` 金库 Y
+//   5. `[WRITE]` 接收 X 的代币账户
+//   6. `[WRITE]` 接收 Y 的代币账户
+//   7. `[]` SPL 代币程序
+pub struct ClosePool<'a> {
+    pub closer: &'a AccountView,
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub destination_x: &'a AccountView,
+    pub destination_y: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl ClosePool<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.closer.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::readonly(self.mint_lp.address()),
+            InstructionAccount::writable(self.vault_x.address()),
+            InstructionAccount::writable(self.vault_y.address()),
+            InstructionAccount::writable(self.destination_x.address()),
+            InstructionAccount::writable(self.destination_y.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.closer,
+                self.config,
+                self.mint_lp,
+                self.vault_x,
+                self.vault_y,
+                self.destination_x,
+                self.destination_y,
+                self.token_program,
+            ],
+            &[12],
+            signers,
+        )
+    }
+}
+
+// 把旧版本的 config 升级到最新的布局
+// 账户：
+//   0. `[WRITE, SIGNER]` 支付新增租金的账户
+//   1. `[WRITE]` config
+//   2. `[]` LP 铸币
+//   3. `[]` 金库 X
+//   4. `[]` 金库 Y
+//   5. `[]` 系统程序
+pub struct Migrate<'a> {
+    pub payer: &'a AccountView,
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub system_program: &'a AccountView,
+}
+
+impl Migrate<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.payer.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::readonly(self.mint_lp.address()),
+            InstructionAccount::readonly(self.vault_x.address()),
+            InstructionAccount::readonly(self.vault_y.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.payer,
+                self.config,
+                self.mint_lp,
+                self.vault_x,
+                self.vault_y,
+                self.system_program,
+            ],
+            &[16],
+            signers,
+        )
+    }
+}
+
+// 创建用户的 LP 仓位记录 之后 Deposit / Withdraw 带上它就会记录成本
+// 账户：
+//   0. `[WRITE, SIGNER]` 用户 支付仓位记录的租金
+//   1. `[]` config
+//   2. `[WRITE]` 仓位记录 PDA seeds = [b"position", config, user]
+//   3. `[]` 系统程序
+pub struct OpenPosition<'a> {
+    pub user: &'a AccountView,
+    pub config: &'a AccountView,
+    pub position: &'a AccountView,
+    pub system_program: &'a AccountView,
+}
+
+impl OpenPosition<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.user.address()),
+            InstructionAccount::readonly(self.config.address()),
+            InstructionAccount::writable(self.position.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[self.user, self.config, self.position, self.system_program],
+            &[33],
+            signers,
+        )
+    }
+}
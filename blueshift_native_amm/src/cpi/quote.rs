@@ -0,0 +1,151 @@
+use super::{invoke_amm, invoke_amm_with_remaining};
+use pinocchio::cpi::Signer;
+use pinocchio::instruction::InstructionAccount;
+use pinocchio::{AccountView, ProgramResult};
+
+// 只读的报价指令 结果通过 return data 返回 调用方用 get_return_data 读取
+// 返回数据的格式见 instructions 中对应的指令
+
+// 交换报价
+// 账户：
+//   0. `[]` config
+//   1. `[]` 可选 池子启用了 TWAMM 时的 TWAMM 账户
+pub struct QuoteSwap<'a> {
+    pub config: &'a AccountView,
+    pub remaining: &'a [AccountView],
+    pub is_x: bool,
+    pub amount: u64,
+}
+
+impl QuoteSwap<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [InstructionAccount::readonly(self.config.address())];
+        // [13, is_x, amount]
+        let mut data = [0u8; 1 + 1 + 8];
+        data[0] = 13;
+        data[1] = self.is_x as u8;
+        data[2..10].copy_from_slice(&self.amount.to_le_bytes());
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[self.config],
+            self.remaining,
+            &data,
+            signers,
+        )
+    }
+}
+
+// 存款报价
+// 账户：
+//   0. `[]` config
+//   1. `[]` LP 铸币
+//   2. `[]` 可选 池子启用了 TWAMM 时的 TWAMM 账户
+pub struct QuoteDeposit<'a> {
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub remaining: &'a [AccountView],
+    pub amount: u64,
+    pub max_x: u64,
+    pub max_y: u64,
+}
+
+impl QuoteDeposit<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly(self.config.address()),
+            InstructionAccount::readonly(self.mint_lp.address()),
+        ];
+        // [14, amount, max_x, max_y]
+        let mut data = [0u8; 1 + 8 * 3];
+        data[0] = 14;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9..17].copy_from_slice(&self.max_x.to_le_bytes());
+        data[17..25].copy_from_slice(&self.max_y.to_le_bytes());
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[self.config, self.mint_lp],
+            self.remaining,
+            &data,
+            signers,
+        )
+    }
+}
+
+// 取款报价
+// 账户：
+//   0. `[]` config
+//   1. `[]` LP 铸币
+//   2. `[]` 可选 池子启用了 TWAMM 时的 TWAMM 账户
+pub struct QuoteWithdraw<'a> {
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub remaining: &'a [AccountView],
+    pub amount: u64,
+}
+
+impl QuoteWithdraw<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly(self.config.address()),
+            InstructionAccount::readonly(self.mint_lp.address()),
+        ];
+        // [15, amount]
+        let mut data = [0u8; 1 + 8];
+        data[0] = 15;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[self.config, self.mint_lp],
+            self.remaining,
+            &data,
+            signers,
+        )
+    }
+}
+
+// 仓位盈亏
+// 账户：
+//   0. `[]` config
+//   1. `[]` LP 铸币
+//   2. `[]` LP 仓位记录
+pub struct QuotePosition<'a> {
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub position: &'a AccountView,
+}
+
+impl QuotePosition<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly(self.config.address()),
+            InstructionAccount::readonly(self.mint_lp.address()),
+            InstructionAccount::readonly(self.position.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[self.config, self.mint_lp, self.position],
+            &[34],
+            signers,
+        )
+    }
+}
@@ -0,0 +1,130 @@
+use super::{invoke_amm, invoke_amm_with_remaining};
+use pinocchio::cpi::Signer;
+use pinocchio::instruction::InstructionAccount;
+use pinocchio::{AccountView, Address, ProgramResult};
+
+// 创建全局设置 只有程序的升级权限持有者可以调用
+// 账户：
+//   0. `[WRITE, SIGNER]` 升级权限持有者 支付设置账户的租金
+//   1. `[WRITE]` 设置 PDA seeds = [b"settings"]
+//   2. `[]` 程序的 ProgramData 账户
+//   3. `[]` 系统程序
+pub struct CreateSettings<'a> {
+    pub payer: &'a AccountView,
+    pub settings: &'a AccountView,
+    pub program_data: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub admin: &'a Address,
+    pub creation_fee: u64,
+    pub fee_tiers: &'a [u16],
+}
+
+impl CreateSettings<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.payer.address()),
+            InstructionAccount::writable(self.settings.address()),
+            InstructionAccount::readonly(self.program_data.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[
+                self.payer,
+                self.settings,
+                self.program_data,
+                self.system_program,
+            ],
+            &[],
+            &settings_data(35, self.admin, self.creation_fee, self.fee_tiers),
+            signers,
+        )
+    }
+}
+
+// 整体替换管理员、创建费和费率档位
+// 账户：
+//   0. `[SIGNER]` 协议管理员
+//   1. `[WRITE]` 设置
+pub struct UpdateSettings<'a> {
+    pub admin: &'a AccountView,
+    pub settings: &'a AccountView,
+    pub new_admin: &'a Address,
+    pub creation_fee: u64,
+    pub fee_tiers: &'a [u16],
+}
+
+impl UpdateSettings<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.admin.address()),
+            InstructionAccount::writable(self.settings.address()),
+        ];
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[self.admin, self.settings],
+            &[],
+            &settings_data(36, self.new_admin, self.creation_fee, self.fee_tiers),
+            signers,
+        )
+    }
+}
+
+// 取出设置账户中累积的创建费
+// 账户：
+//   0. `[SIGNER]` 协议管理员
+//   1. `[WRITE]` 设置
+//   2. `[WRITE]` 接收创建费的账户
+pub struct CollectCreationFees<'a> {
+    pub admin: &'a AccountView,
+    pub settings: &'a AccountView,
+    pub destination: &'a AccountView,
+}
+
+impl CollectCreationFees<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::readonly_signer(self.admin.address()),
+            InstructionAccount::writable(self.settings.address()),
+            InstructionAccount::writable(self.destination.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[self.admin, self.settings, self.destination],
+            &[37],
+            signers,
+        )
+    }
+}
+
+// [discriminator, admin, creation_fee, fee_tiers]
+fn settings_data(
+    discriminator: u8,
+    admin: &Address,
+    creation_fee: u64,
+    fee_tiers: &[u16],
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + 32 + 8 + size_of_val(fee_tiers));
+    data.push(discriminator);
+    data.extend_from_slice(admin.as_ref());
+    data.extend_from_slice(&creation_fee.to_le_bytes());
+    for tier in fee_tiers {
+        data.extend_from_slice(&tier.to_le_bytes());
+    }
+    data
+}
@@ -0,0 +1,198 @@
+use super::{invoke_amm, invoke_amm_with_remaining};
+use pinocchio::cpi::Signer;
+use pinocchio::instruction::InstructionAccount;
+use pinocchio::{AccountView, ProgramResult};
+
+// 为池子开启 TWAMM
+// 账户：
+//   0. `[WRITE, SIGNER]` 管理员 支付 TWAMM 账户的租金
+//   1. `[WRITE]` config
+//   2. `[WRITE]` TWAMM PDA
+//   3. `[]` 系统程序
+pub struct CreateTwamm<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub twamm: &'a AccountView,
+    pub system_program: &'a AccountView,
+}
+
+impl CreateTwamm<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.authority.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::writable(self.twamm.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[self.authority, self.config, self.twamm, self.system_program],
+            &[22],
+            signers,
+        )
+    }
+}
+
+// 提交一个在 duration 秒内均匀卖出 amount 的订单
+// 账户：
+//   0. `[WRITE, SIGNER]` 用户 支付订单账户和 TWAMM 账户扩容的租金
+//   1. `[WRITE]` config
+//   2. `[WRITE]` TWAMM 账户
+//   3. `[WRITE]` 订单 PDA
+//   4. `[WRITE]` 用户卖出代币的代币账户
+//   5. `[WRITE]` 卖出代币对应的金库
+//   6. `[]` SPL 代币程序
+//   7. `[]` 系统程序
+//   .. 可选账户：白名单记录
+pub struct PlaceOrder<'a> {
+    pub user: &'a AccountView,
+    pub config: &'a AccountView,
+    pub twamm: &'a AccountView,
+    pub order: &'a AccountView,
+    pub user_ata: &'a AccountView,
+    pub vault: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub remaining: &'a [AccountView],
+    pub order_id: u64,
+    pub is_x: bool,
+    pub amount: u64,
+    pub duration: u64,
+}
+
+impl PlaceOrder<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.user.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::writable(self.twamm.address()),
+            InstructionAccount::writable(self.order.address()),
+            InstructionAccount::writable(self.user_ata.address()),
+            InstructionAccount::writable(self.vault.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+        // [23, order_id, is_x, amount, duration]
+        let mut data = [0u8; 1 + 8 + 1 + 8 + 8];
+        data[0] = 23;
+        data[1..9].copy_from_slice(&self.order_id.to_le_bytes());
+        data[9] = self.is_x as u8;
+        data[10..18].copy_from_slice(&self.amount.to_le_bytes());
+        data[18..26].copy_from_slice(&self.duration.to_le_bytes());
+        invoke_amm_with_remaining(
+            &instruction_accounts,
+            &[
+                self.user,
+                self.config,
+                self.twamm,
+                self.order,
+                self.user_ata,
+                self.vault,
+                self.token_program,
+                self.system_program,
+            ],
+            self.remaining,
+            &data,
+            signers,
+        )
+    }
+}
+
+// 把 TWAMM 订单执行到当前时间 任何人都可以调用
+// 账户：
+//   0. `[WRITE]` config
+//   1. `[WRITE]` TWAMM 账户
+pub struct Execute<'a> {
+    pub config: &'a AccountView,
+    pub twamm: &'a AccountView,
+}
+
+impl Execute<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::writable(self.twamm.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[self.config, self.twamm],
+            &[24],
+            signers,
+        )
+    }
+}
+
+// 取出订单的收益 订单结束之后关闭订单账户
+// 账户：
+//   0. `[WRITE, SIGNER]` 订单的所有者
+//   1. `[WRITE]` config
+//   2. `[WRITE]` TWAMM 账户
+//   3. `[WRITE]` 订单
+//   4. `[WRITE]` 用户接收收益的代币账户
+//   5. `[WRITE]` 收益代币对应的金库
+//   6. `[WRITE]` 用户卖出代币的代币账户 接收暂停期间没有卖出的部分
+//   7. `[WRITE]` 卖出代币对应的金库
+//   8. `[]` SPL 代币程序
+pub struct WithdrawProceeds<'a> {
+    pub user: &'a AccountView,
+    pub config: &'a AccountView,
+    pub twamm: &'a AccountView,
+    pub order: &'a AccountView,
+    pub user_ata: &'a AccountView,
+    pub vault: &'a AccountView,
+    pub user_sell_ata: &'a AccountView,
+    pub sell_vault: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl WithdrawProceeds<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable_signer(self.user.address()),
+            InstructionAccount::writable(self.config.address()),
+            InstructionAccount::writable(self.twamm.address()),
+            InstructionAccount::writable(self.order.address()),
+            InstructionAccount::writable(self.user_ata.address()),
+            InstructionAccount::writable(self.vault.address()),
+            InstructionAccount::writable(self.user_sell_ata.address()),
+            InstructionAccount::writable(self.sell_vault.address()),
+            InstructionAccount::readonly(self.token_program.address()),
+        ];
+        invoke_amm(
+            &instruction_accounts,
+            &[
+                self.user,
+                self.config,
+                self.twamm,
+                self.order,
+                self.user_ata,
+                self.vault,
+                self.user_sell_ata,
+                self.sell_vault,
+                self.token_program,
+            ],
+            &[25],
+            signers,
+        )
+    }
+}
@@ -0,0 +1,66 @@
+use pinocchio::cpi::invoke_with_slice;
+use pinocchio::instruction::{InstructionAccount, InstructionView};
+use pinocchio::{AccountView, ProgramResult};
+
+// 交易前后传给 hook 程序的阶段标识
+#[repr(u8)]
+pub enum HookPhase {
+    PreSwap = 0u8,
+    PostSwap = 1u8,
+}
+
+// hook 程序收到的指令数据
+// [phase: u8, is_x: u8, amount_in: u64, amount_out: u64, fee: u64]
+pub const HOOK_DATA_LEN: usize = 2 + size_of::<u64>() * 3;
+
+// 构建传给 hook 程序的指令数据
+pub fn swap_hook_data(
+    phase: HookPhase,
+    is_x: bool,
+    amount_in: u64,
+    amount_out: u64,
+    fee: u64,
+) -> [u8; HOOK_DATA_LEN] {
+    let mut data = [0u8; HOOK_DATA_LEN];
+    data[0] = phase as u8;
+    data[1] = is_x as u8;
+    data[2..10].copy_from_slice(&amount_in.to_le_bytes());
+    data[10..18].copy_from_slice(&amount_out.to_le_bytes());
+    data[18..26].copy_from_slice(&fee.to_le_bytes());
+    data
+}
+
+// CPI 调用 hook 程序
+// 传给 hook 的账户依次为：config（只读）、user（只读）、剩余的 hook 账户
+// hook 程序由管理员选择 不能把任何签名交给它 所有账户都去掉签名标记
+// config 是金库的所有者 user 是用户代币账户的所有者 带着签名的话 hook 可以直接转走其中的代币
+// hook 返回错误时整笔交易回滚 也就是否决了这次交易
+pub fn invoke_swap_hook(
+    hook_program: &AccountView,
+    config: &AccountView,
+    user: &AccountView,
+    hook_accounts: &[AccountView],
+    data: &[u8],
+) -> ProgramResult {
+    let mut instruction_accounts = Vec::with_capacity(hook_accounts.len() + 2);
+    instruction_accounts.push(InstructionAccount::readonly(config.address()));
+    instruction_accounts.push(InstructionAccount::readonly(user.address()));
+    let mut account_views = Vec::with_capacity(hook_accounts.len() + 2);
+    account_views.push(config);
+    account_views.push(user);
+    for account in hook_accounts {
+        instruction_accounts.push(InstructionAccount::new(
+            account.address(),
+            account.is_writable(),
+            false,
+        ));
+        account_views.push(account);
+    }
+
+    let instruction = InstructionView {
+        program_id: hook_program.address(),
+        data,
+        accounts: &instruction_accounts,
+    };
+    invoke_with_slice(&instruction, &account_views)
+}
@@ -0,0 +1,96 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount, ProgramAccountInit,
+    SignerAccount,
+};
+use crate::{AllowlistEntry, Config};
+use pinocchio::{cpi::Seed, error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct AddTraderAccounts<'a> {
+    // 支付白名单记录的租金 必须签名。
+    // 和管理员分开 管理员是多签时多签 PDA 带有数据 不能作为 CreateAccount 的付款方
+    pub payer: &'a AccountView,
+    // AMM 的管理员 必须签名。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 被加入白名单的钱包 不需要签名。
+    pub trader: &'a AccountView,
+    // 白名单记录 PDA seeds = [b"allowlist", config, trader]
+    pub entry: &'a AccountView,
+    // 系统程序 创建白名单记录需要。
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for AddTraderAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [payer, authority, config, trader, entry, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(payer)?;
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            payer,
+            authority,
+            config,
+            trader,
+            entry,
+            system_program,
+        })
+    }
+}
+
+pub struct AddTrader<'a> {
+    pub accounts: AddTraderAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for AddTrader<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = AddTraderAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AddTrader<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+
+        let (entry_address, bump) = Address::find_program_address(
+            &[
+                b"allowlist",
+                self.accounts.config.address().as_ref(),
+                self.accounts.trader.address().as_ref(),
+            ],
+            &crate::ID,
+        );
+        if entry_address.ne(self.accounts.entry.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let bump_binding = [bump];
+        let entry_seeds = [
+            Seed::from(b"allowlist"),
+            Seed::from(self.accounts.config.address().as_ref()),
+            Seed::from(self.accounts.trader.address().as_ref()),
+            Seed::from(&bump_binding),
+        ];
+        // 账户已经存在的话 CreateAccount 会失败 所以不会重复添加
+        ProgramAccount::init::<AllowlistEntry>(
+            self.accounts.payer,
+            self.accounts.entry,
+            &entry_seeds,
+            AllowlistEntry::LEN,
+        )?;
+
+        let mut entry = AllowlistEntry::load_mut(self.accounts.entry)?;
+        entry.set_inner(
+            self.accounts.config.address().clone(),
+            self.accounts.trader.address().clone(),
+            bump_binding,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use crate::instructions::helper::{AccountCheck, SignerAccount};
+use crate::{Multisig, Proposal};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+pub struct ApproveAccounts<'a> {
+    // 批准提案的多签成员 必须签名。
+    pub signer: &'a AccountView,
+    // 多签账户。
+    pub multisig: &'a AccountView,
+    // 需要批准的提案。
+    pub proposal: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ApproveAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [signer, multisig, proposal] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(signer)?;
+        Ok(Self {
+            signer,
+            multisig,
+            proposal,
+        })
+    }
+}
+
+pub struct Approve<'a> {
+    pub accounts: ApproveAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for Approve<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ApproveAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Approve<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let multisig = Multisig::load(self.accounts.multisig)?;
+        let signer_index = multisig.signer_index(self.accounts.signer.address())?;
+        let mut proposal = Proposal::load_mut(self.accounts.proposal)?;
+        if proposal.multisig().ne(self.accounts.multisig.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        proposal.approve(signer_index)
+    }
+}
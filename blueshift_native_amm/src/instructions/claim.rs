@@ -0,0 +1,85 @@
+use crate::instructions::helper::{AccountCheck, SignerAccount};
+use crate::{Farm, StakePosition};
+use pinocchio::cpi::Signer;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+use pinocchio_token::instructions::Transfer;
+
+pub struct ClaimAccounts<'a> {
+    // 领取奖励的用户 必须签名。
+    pub user: &'a AccountView,
+    // 池子的农场账户。
+    pub farm: &'a AccountView,
+    // 用户的质押记录。
+    pub position: &'a AccountView,
+    // 用户接收奖励的代币账户。
+    pub user_reward_ata: &'a AccountView,
+    // 农场持有的奖励代币账户。
+    pub reward_vault: &'a AccountView,
+    // SPL 代币程序账户。
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClaimAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [user, farm, position, user_reward_ata, reward_vault, token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(user)?;
+        Ok(Self {
+            user,
+            farm,
+            position,
+            user_reward_ata,
+            reward_vault,
+            token_program,
+        })
+    }
+}
+
+pub struct Claim<'a> {
+    pub accounts: ClaimAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for Claim<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ClaimAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Claim<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut farm = Farm::load_mut(self.accounts.farm)?;
+        if farm.reward_vault().ne(self.accounts.reward_vault.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut position = StakePosition::load_mut(self.accounts.position)?;
+        position.check_owner(self.accounts.farm, self.accounts.user)?;
+
+        farm.update(Clock::get()?.unix_timestamp)?;
+        position.settle(&farm)?;
+        let amount = position.take_pending();
+        let config = farm.config().clone();
+        let bump = farm.bump();
+        drop(position);
+        drop(farm);
+
+        if amount == 0 {
+            return Ok(());
+        }
+        // 农场是奖励金库的所有者 需要用农场的 seeds 签名
+        let farm_seeds = Farm::signer_seeds(&config, &bump);
+        Transfer {
+            from: self.accounts.reward_vault,
+            to: self.accounts.user_reward_ata,
+            authority: self.accounts.farm,
+            amount,
+        }
+        .invoke_signed(&[Signer::from(&farm_seeds)])
+    }
+}
@@ -0,0 +1,152 @@
+use crate::instructions::helper::{
+    AccountCheck, AccountClose, MintInterface, ProgramAccount, SignerAccount,
+};
+use crate::Config;
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+use pinocchio_token::state::{Mint, TokenAccount};
+
+pub struct ClosePoolAccounts<'a> {
+    // 关闭池子的账户 必须是管理员或者池子的创建者 同时接收返还的租金。
+    pub closer: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 代表池流动性的铸币账户。
+    pub mint_lp: &'a AccountView,
+    // 存储所有存入池中的 X 代币的代币账户。
+    pub vault_x: &'a AccountView,
+    // 存储所有存入池中的 Y 代币的代币账户。
+    pub vault_y: &'a AccountView,
+    // 接收金库中剩余 X 代币的代币账户。
+    pub destination_x: &'a AccountView,
+    // 接收金库中剩余 Y 代币的代币账户。
+    pub destination_y: &'a AccountView,
+    // SPL 代币程序账户。
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClosePoolAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [closer, config, mint_lp, vault_x, vault_y, destination_x, destination_y, token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(closer)?;
+        ProgramAccount::check(config)?;
+        MintInterface::check(mint_lp)?;
+        Ok(Self {
+            closer,
+            config,
+            mint_lp,
+            vault_x,
+            vault_y,
+            destination_x,
+            destination_y,
+            token_program,
+        })
+    }
+}
+
+pub struct ClosePool<'a> {
+    pub accounts: ClosePoolAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClosePool<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ClosePoolAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ClosePool<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+        // 只有管理员或者池子的创建者可以关闭池子
+        let closer = self.accounts.closer.address();
+        let is_authority = config.has_authority().is_some_and(|a| a.eq(closer));
+        if !is_authority && config.initializer().ne(closer) {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+
+        // 所有 LP 都已经销毁 储备和 TWAMM 托管也都清空才可以关闭
+        {
+            let mint_lp = Mint::from_account_view(self.accounts.mint_lp)?;
+            if self.accounts.mint_lp.address().ne(config.mint_lp()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if mint_lp.supply() != 0 {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        // 金库地址在 Initialize 时已经记录在 config 中 直接比较即可
+        if self.accounts.vault_x.address().ne(config.vault_x())
+            || self.accounts.vault_y.address().ne(config.vault_y())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if config.reserve_x() != 0
+            || config.reserve_y() != 0
+            || config.twamm_escrow_x() != 0
+            || config.twamm_escrow_y() != 0
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        // 这时金库中剩下的都是直接转入的代币 和 Skim 一样转给 closer
+        // 否则任何人向金库转入 1 个代币就能阻止关闭
+        let excess_x = TokenAccount::from_account_view(self.accounts.vault_x)?.amount();
+        let excess_y = TokenAccount::from_account_view(self.accounts.vault_y)?.amount();
+
+        // 构建config签名 关闭金库
+        let seed_array = config.seed().to_le_bytes();
+        let bump = config.config_bump();
+        let config_seeds = [
+            Seed::from(b"config"),
+            Seed::from(&seed_array),
+            Seed::from(config.mint_x().as_ref()),
+            Seed::from(config.mint_y().as_ref()),
+            Seed::from(&bump),
+        ];
+        let config_signer = [Signer::from(&config_seeds)];
+        if excess_x > 0 {
+            Transfer {
+                from: self.accounts.vault_x,
+                to: self.accounts.destination_x,
+                authority: self.accounts.config,
+                amount: excess_x,
+            }
+            .invoke_signed(&config_signer)?;
+        }
+        if excess_y > 0 {
+            Transfer {
+                from: self.accounts.vault_y,
+                to: self.accounts.destination_y,
+                authority: self.accounts.config,
+                amount: excess_y,
+            }
+            .invoke_signed(&config_signer)?;
+        }
+        CloseAccount {
+            account: self.accounts.vault_x,
+            destination: self.accounts.closer,
+            authority: self.accounts.config,
+        }
+        .invoke_signed(&config_signer)?;
+        CloseAccount {
+            account: self.accounts.vault_y,
+            destination: self.accounts.closer,
+            authority: self.accounts.config,
+        }
+        .invoke_signed(&config_signer)?;
+
+        // SPL Token 的铸币账户没办法关闭 LP mint 会留在链上
+        // 最后关闭 config
+        drop(config);
+        ProgramAccount::close(self.accounts.config, self.accounts.closer)
+    }
+}
@@ -0,0 +1,61 @@
+use crate::Settings;
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+pub struct CollectCreationFeesAccounts<'a> {
+    // 协议管理员 必须签名。
+    pub admin: &'a AccountView,
+    // 全局设置账户 创建费保存在这里。
+    pub settings: &'a AccountView,
+    // 接收创建费的账户。
+    pub destination: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CollectCreationFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [admin, settings, destination] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        Ok(Self {
+            admin,
+            settings,
+            destination,
+        })
+    }
+}
+
+pub struct CollectCreationFees<'a> {
+    pub accounts: CollectCreationFeesAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CollectCreationFees<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = CollectCreationFeesAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CollectCreationFees<'a> {
+    // 把设置账户中超过租金豁免的 lamports 全部转给 destination
+    pub fn process(&self) -> ProgramResult {
+        Settings::check(self.accounts.settings)?.check_admin(self.accounts.admin)?;
+        let rent = Rent::get()?.try_minimum_balance(Settings::LEN)?;
+        let fees = self.accounts.settings.lamports().saturating_sub(rent);
+        if fees == 0 {
+            return Ok(());
+        }
+        let destination = self
+            .accounts
+            .destination
+            .lamports()
+            .checked_add(fees)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.accounts.settings.set_lamports(rent);
+        self.accounts.destination.set_lamports(destination);
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, MintInterface, ProgramAccount,
+    ProgramAccountInit,
+};
+use crate::{Config, Farm};
+use pinocchio::cpi::Seed;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct CreateFarmAccounts<'a> {
+    // AMM 的管理员 必须签名 同时支付农场账户的租金。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 农场 PDA seeds = [b"farm", config]
+    pub farm: &'a AccountView,
+    // 奖励代币的铸币账户。
+    pub reward_mint: &'a AccountView,
+    // 系统程序 创建农场账户需要。
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CreateFarmAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, farm, reward_mint, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        MintInterface::check(reward_mint)?;
+        Ok(Self {
+            authority,
+            config,
+            farm,
+            reward_mint,
+            system_program,
+        })
+    }
+}
+
+pub struct CreateFarmInstructionData {
+    // 每秒释放的奖励数量
+    pub reward_rate: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for CreateFarmInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let reward_rate = u64::from_le_bytes(data.try_into().unwrap());
+        Ok(Self { reward_rate })
+    }
+}
+
+pub struct CreateFarm<'a> {
+    pub accounts: CreateFarmAccounts<'a>,
+    pub instruction_data: CreateFarmInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for CreateFarm<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = CreateFarmAccounts::try_from(accounts)?;
+        let instruction_data = CreateFarmInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> CreateFarm<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+
+        let (farm_address, bump) = Address::find_program_address(
+            &[b"farm", self.accounts.config.address().as_ref()],
+            &crate::ID,
+        );
+        if farm_address.ne(self.accounts.farm.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let bump_binding = [bump];
+        let farm_seeds = [
+            Seed::from(b"farm"),
+            Seed::from(self.accounts.config.address().as_ref()),
+            Seed::from(&bump_binding),
+        ];
+        // 账户已经存在的话 CreateAccount 会失败 所以每个池子只有一个农场
+        ProgramAccount::init::<Farm>(
+            self.accounts.authority,
+            self.accounts.farm,
+            &farm_seeds,
+            Farm::LEN,
+        )?;
+
+        // 质押的 LP 和奖励代币都放在农场持有的 ATA 中 这里推导一次记录下来
+        let (stake_vault, _) = Address::find_program_address(
+            &[
+                farm_address.as_ref(),
+                pinocchio_token::ID.as_ref(),
+                config.mint_lp().as_ref(),
+            ],
+            &pinocchio_associated_token_account::ID,
+        );
+        let (reward_vault, _) = Address::find_program_address(
+            &[
+                farm_address.as_ref(),
+                pinocchio_token::ID.as_ref(),
+                self.accounts.reward_mint.address().as_ref(),
+            ],
+            &pinocchio_associated_token_account::ID,
+        );
+
+        let mut farm = Farm::load_mut(self.accounts.farm)?;
+        farm.set_inner(
+            self.accounts.config.address().clone(),
+            config.mint_lp().clone(),
+            self.accounts.reward_mint.address().clone(),
+            stake_vault,
+            reward_vault,
+            bump_binding,
+        );
+        farm.set_reward_rate(self.instruction_data.reward_rate);
+        farm.set_last_update(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use crate::instructions::helper::{
+    AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount,
+};
+use crate::{Multisig, MAX_SIGNERS};
+use pinocchio::cpi::Seed;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct CreateMultisigAccounts<'a> {
+    // 多签的创建者 必须签名 同时支付多签账户的租金。创建者不一定是成员。
+    pub creator: &'a AccountView,
+    // 多签账户 PDA seeds = [b"multisig", creator, seed]
+    pub multisig: &'a AccountView,
+    // 系统程序 创建多签账户需要。
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CreateMultisigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [creator, multisig, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(creator)?;
+        Ok(Self {
+            creator,
+            multisig,
+            system_program,
+        })
+    }
+}
+
+pub struct CreateMultisigInstructionData {
+    // 用于推导多签 PDA 的随机数 同一个创建者可以创建多个多签
+    pub seed: u64,
+    // 执行提案需要的最少批准数量
+    pub threshold: u8,
+    // 多签成员
+    pub signers: [Address; MAX_SIGNERS],
+    pub signer_count: usize,
+}
+
+impl<'a> TryFrom<&'a [u8]> for CreateMultisigInstructionData {
+    type Error = ProgramError;
+
+    // [seed: u64, threshold: u8, signers: [Address; n]]
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        const HEADER_LEN: usize = size_of::<u64>() + size_of::<u8>();
+        if data.len() < HEADER_LEN || !(data.len() - HEADER_LEN).is_multiple_of(size_of::<Address>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let threshold = data[8];
+        let signer_count = (data.len() - HEADER_LEN) / size_of::<Address>();
+        if signer_count > MAX_SIGNERS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut signers: [Address; MAX_SIGNERS] = core::array::from_fn(|_| Address::default());
+        for (signer, bytes) in signers
+            .iter_mut()
+            .zip(data[HEADER_LEN..].chunks_exact(size_of::<Address>()))
+        {
+            let bytes: [u8; 32] = bytes.try_into().unwrap();
+            *signer = Address::from(bytes);
+        }
+        Ok(Self {
+            seed,
+            threshold,
+            signers,
+            signer_count,
+        })
+    }
+}
+
+pub struct CreateMultisig<'a> {
+    pub accounts: CreateMultisigAccounts<'a>,
+    pub instruction_data: CreateMultisigInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for CreateMultisig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = CreateMultisigAccounts::try_from(accounts)?;
+        let instruction_data = CreateMultisigInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> CreateMultisig<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let seed_binding = self.instruction_data.seed.to_le_bytes();
+        let (multisig_address, bump) = Address::find_program_address(
+            &[
+                b"multisig",
+                self.accounts.creator.address().as_ref(),
+                &seed_binding,
+            ],
+            &crate::ID,
+        );
+        if multisig_address.ne(self.accounts.multisig.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let bump_binding = [bump];
+        let multisig_seeds = [
+            Seed::from(b"multisig"),
+            Seed::from(self.accounts.creator.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        ProgramAccount::init::<Multisig>(
+            self.accounts.creator,
+            self.accounts.multisig,
+            &multisig_seeds,
+            Multisig::LEN,
+        )?;
+        Multisig::load_mut(self.accounts.multisig)?.set_inner(
+            self.accounts.creator.address().clone(),
+            self.instruction_data.seed,
+            self.instruction_data.threshold,
+            &self.instruction_data.signers[..self.instruction_data.signer_count],
+            bump_binding,
+        )
+    }
+}
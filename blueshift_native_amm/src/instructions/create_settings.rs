@@ -0,0 +1,132 @@
+use crate::instructions::helper::{ProgramAccount, ProgramAccountInit};
+use crate::{check_upgrade_authority, Settings, MAX_FEE_TIERS};
+use pinocchio::cpi::Seed;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct CreateSettingsAccounts<'a> {
+    // 程序的升级权限持有者 必须签名 同时支付设置账户的租金。
+    pub payer: &'a AccountView,
+    // 全局设置 PDA seeds = [b"settings"]
+    pub settings: &'a AccountView,
+    // 程序的 ProgramData 账户 用来确认升级权限。
+    pub program_data: &'a AccountView,
+    // 系统程序 创建设置账户需要。
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CreateSettingsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [payer, settings, program_data, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        check_upgrade_authority(program_data, payer)?;
+        Ok(Self {
+            payer,
+            settings,
+            program_data,
+            system_program,
+        })
+    }
+}
+
+// CreateSettings 和 UpdateSettings 共用的指令数据
+pub struct SettingsInstructionData {
+    // 协议管理员 之后由它调用 UpdateSettings / CollectCreationFees
+    pub admin: Address,
+    // 创建池子需要支付的 lamports 0 表示免费
+    pub creation_fee: u64,
+    // 允许的费率档位 以基点表示
+    pub fee_tiers: [u16; MAX_FEE_TIERS],
+    pub fee_tier_count: usize,
+}
+
+impl SettingsInstructionData {
+    #[inline(always)]
+    pub fn fee_tiers(&self) -> &[u16] {
+        &self.fee_tiers[..self.fee_tier_count]
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for SettingsInstructionData {
+    type Error = ProgramError;
+
+    // [admin: Address, creation_fee: u64, fee_tiers: [u16; n]]
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        const HEADER_LEN: usize = size_of::<Address>() + size_of::<u64>();
+        if data.len() < HEADER_LEN || !(data.len() - HEADER_LEN).is_multiple_of(size_of::<u16>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let admin: [u8; 32] = data[0..32].try_into().unwrap();
+        let creation_fee = u64::from_le_bytes(data[32..40].try_into().unwrap());
+        let fee_tier_count = (data.len() - HEADER_LEN) / size_of::<u16>();
+        // 至少要有一个档位 否则就没有人可以创建池子了
+        if fee_tier_count == 0 || fee_tier_count > MAX_FEE_TIERS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut fee_tiers = [0u16; MAX_FEE_TIERS];
+        for (index, bytes) in data[HEADER_LEN..]
+            .chunks_exact(size_of::<u16>())
+            .enumerate()
+        {
+            let tier = u16::from_le_bytes(bytes.try_into().unwrap());
+            // 和 Config::set_fee 的范围一致 同一个档位不能出现两次
+            if tier >= 10_000 || fee_tiers[..index].contains(&tier) {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            fee_tiers[index] = tier;
+        }
+        Ok(Self {
+            admin: Address::from(admin),
+            creation_fee,
+            fee_tiers,
+            fee_tier_count,
+        })
+    }
+}
+
+pub struct CreateSettings<'a> {
+    pub accounts: CreateSettingsAccounts<'a>,
+    pub instruction_data: SettingsInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for CreateSettings<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = CreateSettingsAccounts::try_from(accounts)?;
+        let instruction_data = SettingsInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> CreateSettings<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let (settings_address, bump) = Address::find_program_address(&[b"settings"], &crate::ID);
+        if settings_address.ne(self.accounts.settings.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let bump_binding = [bump];
+        let settings_seeds = [Seed::from(b"settings"), Seed::from(&bump_binding)];
+        // 账户已经存在的话 CreateAccount 会失败 所以只能创建一次
+        ProgramAccount::init::<Settings>(
+            self.accounts.payer,
+            self.accounts.settings,
+            &settings_seeds,
+            Settings::LEN,
+        )?;
+
+        let mut settings = Settings::load_mut(self.accounts.settings)?;
+        settings.set_bump(bump_binding);
+        settings.set_params(
+            self.instruction_data.admin.clone(),
+            self.instruction_data.creation_fee,
+            self.instruction_data.fee_tiers(),
+        );
+        Ok(())
+    }
+}
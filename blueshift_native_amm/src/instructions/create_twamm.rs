@@ -0,0 +1,84 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount, ProgramAccountInit,
+};
+use crate::{Config, Twamm};
+use pinocchio::cpi::Seed;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct CreateTwammAccounts<'a> {
+    // AMM 的管理员 必须签名 同时支付 TWAMM 账户的租金。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // TWAMM 账户 PDA seeds = [b"twamm", config]
+    pub twamm: &'a AccountView,
+    // 系统程序 创建 TWAMM 账户需要。
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CreateTwammAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, twamm, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            authority,
+            config,
+            twamm,
+            system_program,
+        })
+    }
+}
+
+pub struct CreateTwamm<'a> {
+    pub accounts: CreateTwammAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CreateTwamm<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = CreateTwammAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CreateTwamm<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+
+        let (twamm_address, bump) = Address::find_program_address(
+            &[b"twamm", self.accounts.config.address().as_ref()],
+            &crate::ID,
+        );
+        if twamm_address.ne(self.accounts.twamm.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let bump_binding = [bump];
+        let twamm_seeds = [
+            Seed::from(b"twamm"),
+            Seed::from(self.accounts.config.address().as_ref()),
+            Seed::from(&bump_binding),
+        ];
+        // 账户已经存在的话 CreateAccount 会失败 所以每个池子只有一个 TWAMM 账户
+        ProgramAccount::init::<Twamm>(
+            self.accounts.authority,
+            self.accounts.twamm,
+            &twamm_seeds,
+            Twamm::LEN,
+        )?;
+        Twamm::load_mut(self.accounts.twamm)?.set_inner(
+            self.accounts.config.address().clone(),
+            Clock::get()?.unix_timestamp,
+            bump_binding,
+        );
+
+        // 之后改变储备的指令都需要先结算 TWAMM 订单
+        config.set_twamm(true)
+    }
+}
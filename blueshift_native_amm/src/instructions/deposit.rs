@@ -1,16 +1,18 @@
 use crate::instructions::helper::{
-    AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit,
-    MintInterface, ProgramAccount, SignerAccount,
+    AccountCheck, Deadline, MintInterface, ProgramAccount, SignerAccount, TokenAccount,
+    TokenAccountOwnerCheck,
 };
-use crate::{AmmState, Config};
-use pinocchio::cpi::{Seed, Signer};
-use pinocchio::{
-    error::ProgramError,
-    sysvars::{clock::Clock, Sysvar},
-    AccountView, Address, ProgramResult,
+use crate::math::curve_error;
+use crate::twamm::settle;
+use blueshift_amm_math::{ConstantProduct, Curve};
+use crate::{
+    check_lamport_source, wrap, AllowlistEntry, AmmState, Config, LpPosition, NATIVE_MINT,
 };
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
 use pinocchio_token::instructions::{MintTo, Transfer};
-use pinocchio_token::state::{Mint, TokenAccount};
+use pinocchio_token::state::Mint;
 
 pub struct DepositAccounts<'a> {
     // 将代币存入 AMM 流动性的用户。
@@ -22,6 +24,7 @@ pub struct DepositAccounts<'a> {
     // 存储所有存入池中的 Y 代币的代币账户。
     pub vault_y: &'a AccountView,
     // 用户的 X 代币关联账户。这是用户的 X 代币将从中转移到池中的源账户。
+    // 使用原生 SOL 支付时 wSOL 一侧传入系统程序
     pub user_x_ata: &'a AccountView,
     // 用户的 Y 代币关联账户。这是用户的 Y 代币将从中转移到池中的源账户。
     pub user_y_ata: &'a AccountView,
@@ -31,13 +34,18 @@ pub struct DepositAccounts<'a> {
     pub config: &'a AccountView,
     // SPL 代币程序账户。
     pub token_program: &'a AccountView,
+    // 可选账户 按顺序排列：
+    // 1. 池子启用了 TWAMM 时池子的 TWAMM 账户
+    // 2. 许可池中用户的白名单记录
+    // 3. 用户的 LP 仓位记录 传入时记录这次操作的成本 见 OpenPosition
+    pub remaining: &'a [AccountView],
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for DepositAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program] =
+        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program, remaining @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
@@ -55,6 +63,7 @@ impl<'a> TryFrom<&'a [AccountView]> for DepositAccounts<'a> {
             user_lp_ata,
             config,
             token_program,
+            remaining,
         })
     }
 }
@@ -68,6 +77,10 @@ pub struct DepositInstructionData {
     pub max_y: u64,
     // 此订单的过期时间。确保交易必须在一定时间内完成非常重要。
     pub expiration: i64,
+    // 可选 为 1 时池子中 wSOL 一侧直接使用原生 SOL 支付
+    pub native: bool,
+    // 可选 订单过期的 slot 和 expiration 同时生效 0 表示不限制
+    pub expiration_slot: u64,
 }
 
 impl<'a> TryFrom<&'a [u8]> for DepositInstructionData {
@@ -75,13 +88,23 @@ impl<'a> TryFrom<&'a [u8]> for DepositInstructionData {
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         // u64 和 i64 的长度是一样的
-        if data.len() != size_of::<u64>() * 4 {
+        const DEPOSIT_DATA_LEN: usize = size_of::<u64>() * 4;
+        const DEPOSIT_DATA_LEN_WITH_NATIVE: usize = DEPOSIT_DATA_LEN + size_of::<bool>();
+        const DEPOSIT_DATA_LEN_WITH_SLOT: usize = DEPOSIT_DATA_LEN_WITH_NATIVE + size_of::<u64>();
+        if !matches!(
+            data.len(),
+            DEPOSIT_DATA_LEN | DEPOSIT_DATA_LEN_WITH_NATIVE | DEPOSIT_DATA_LEN_WITH_SLOT
+        ) {
             return Err(ProgramError::InvalidInstructionData);
         }
         let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
         let max_x = u64::from_le_bytes(data[8..16].try_into().unwrap());
         let max_y = u64::from_le_bytes(data[16..24].try_into().unwrap());
         let expiration = i64::from_le_bytes(data[24..32].try_into().unwrap());
+        let native = data.get(32).is_some_and(|flag| *flag != 0);
+        let expiration_slot = data
+            .get(33..41)
+            .map_or(0, |slot| u64::from_le_bytes(slot.try_into().unwrap()));
         // Instruction Checks
         if amount <= 0 {
             return Err(ProgramError::InvalidInstructionData);
@@ -92,15 +115,13 @@ impl<'a> TryFrom<&'a [u8]> for DepositInstructionData {
         if max_y <= 0 {
             return Err(ProgramError::InvalidInstructionData);
         }
-        if Clock::get()?.unix_timestamp > expiration {
-            // 超时
-            return Err(ProgramError::InvalidInstructionData);
-        }
         Ok(Self {
             amount,
             max_x,
             max_y,
             expiration,
+            native,
+            expiration_slot,
         })
     }
 }
@@ -126,93 +147,112 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Deposit<'a> {
 
 impl<'a> Deposit<'a> {
     pub fn process(&self) -> ProgramResult {
+        // 超时 放在 process 中检查 指令数据的解析不依赖 Clock
+        Deadline::check(
+            self.instruction_data.expiration,
+            self.instruction_data.expiration_slot,
+        )?;
+        // 先把 TWAMM 订单执行到现在 再按照最新的储备计算
+        let mut remaining = self.accounts.remaining.iter();
+        settle(self.accounts.config, &mut remaining)?;
         // 校验用户的ata账户地址有没有问题
         let config = Config::load(self.accounts.config)?;
         if config.state() != (AmmState::Initialized as u8) {
             return Err(ProgramError::InvalidArgument);
         }
-        AssociatedTokenAccount::check(
-            self.accounts.user_x_ata,
-            self.accounts.user,
-            config.mint_x(),
-            self.accounts.token_program,
-        )?;
-        AssociatedTokenAccount::check(
-            self.accounts.user_y_ata,
-            self.accounts.user,
-            config.mint_y(),
-            self.accounts.token_program,
-        )?;
-        AssociatedTokenAccount::check(
+        // 许可池只允许白名单中的钱包提供流动性
+        if config.is_permissioned() {
+            let entry = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            AllowlistEntry::check(entry, self.accounts.config, self.accounts.user)?;
+        }
+        // LP 铸币必须是这个池子的
+        if self.accounts.mint_lp.address().ne(config.mint_lp()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let native_x = self.instruction_data.native && config.mint_x().eq(&NATIVE_MINT);
+        let native_y = self.instruction_data.native && config.mint_y().eq(&NATIVE_MINT);
+        if self.instruction_data.native && !native_x && !native_y {
+            return Err(ProgramError::InvalidArgument);
+        }
+        // 直接支付 lamports 的一侧不需要用户的 wSOL 账户
+        match native_x {
+            true => check_lamport_source(self.accounts.user_x_ata)?,
+            false => TokenAccount::check_owner(
+                self.accounts.user_x_ata,
+                self.accounts.user,
+                config.mint_x(),
+            )?,
+        }
+        match native_y {
+            true => check_lamport_source(self.accounts.user_y_ata)?,
+            false => TokenAccount::check_owner(
+                self.accounts.user_y_ata,
+                self.accounts.user,
+                config.mint_y(),
+            )?,
+        }
+        TokenAccount::check_owner(
             self.accounts.user_lp_ata,
             self.accounts.user,
             self.accounts.mint_lp.address(),
-            self.accounts.token_program,
         )?;
 
-        // 校验 金库的地址有没有为题
-        // 其实 vault并不需要被初始化 因为这个地址是被计算出来的 只要计算出来 就可以标记里面有多少钱
-        // Check if the vault_x is valid
-        let (vault_x, _) = Address::find_program_address(
-            &[
-                self.accounts.config.address().as_ref(),
-                self.accounts.token_program.address().as_ref(),
-                config.mint_x().as_ref(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-        if vault_x.ne(self.accounts.vault_x.address()) {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        let (vault_y, _) = Address::find_program_address(
-            &[
-                self.accounts.config.address().as_ref(),
-                self.accounts.token_program.address().as_ref(),
-                config.mint_y().as_ref(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-        if vault_y.ne(self.accounts.vault_y.address()) {
+        // 金库地址在 Initialize 时已经记录在 config 中 直接比较即可
+        if self.accounts.vault_x.address().ne(config.vault_x())
+            || self.accounts.vault_y.address().ne(config.vault_y())
+        {
             return Err(ProgramError::InvalidAccountData);
         }
         // 把账户从 AccountView 转化成功能账户
         let mint_lp = unsafe { Mint::from_account_view_unchecked(self.accounts.mint_lp)? };
-        let vault_x = unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_y)? };
+        // 使用池子记录的储备 而不是金库的实际余额
+        let reserve_x = config.reserve_x();
+        let reserve_y = config.reserve_y();
 
         // 计算用户需要支付的 x y 代币的数量
-        let (x, y) = match mint_lp.supply() == 0 && vault_x.amount() == 0 && vault_y.amount() == 0 {
-            // 这里就是当vault_x 和 vault_y 和 lp_token供应量全部都是0 也就是首次注入流动性
-            // 在这个还没有暴露的情况下 默认可以认为这就是定下 x * y 的值 也可以认为初始定下了 x y 和 lp代币的兑换比率
-            true => (self.instruction_data.max_x, self.instruction_data.max_y),
-            false => {
-                let amounts = Self::xy_deposit_amounts_from_l(
-                    vault_x.amount(),
-                    vault_y.amount(),
-                    mint_lp.supply(),
-                    self.instruction_data.amount,
-                    6,
-                )
-                .map_err(|_| ProgramError::InvalidArgument)?;
-                (amounts.0, amounts.1)
+        let (x, y) = Self::quote(
+            reserve_x,
+            reserve_y,
+            mint_lp.supply(),
+            self.instruction_data.amount,
+            self.instruction_data.max_x,
+            self.instruction_data.max_y,
+        )?;
+        // Check for slippage
+        if x > self.instruction_data.max_x || y > self.instruction_data.max_y {
+            return Err(ProgramError::InvalidArgument);
+        }
+        // 先更新储备再转账 转账的时候 config 不能处于可变借用状态
+        let new_reserve_x = reserve_x
+            .checked_add(x)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let new_reserve_y = reserve_y
+            .checked_add(y)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        drop(config);
+        Config::update_reserves(self.accounts.config, new_reserve_x, new_reserve_y)?;
+        let config = Config::load(self.accounts.config)?;
+        // 将用户的代币账户中的金额转移到金库 原生 SOL 直接转 lamports 到 wSOL 金库
+        match native_x {
+            true => wrap(self.accounts.user, self.accounts.vault_x, x)?,
+            false => Transfer {
+                from: self.accounts.user_x_ata,
+                to: self.accounts.vault_x,
+                authority: self.accounts.user,
+                amount: x,
             }
-        };
-        // 将用户的代币账户中的金额转移到金库
-        Transfer {
-            from: self.accounts.user_x_ata,
-            to: self.accounts.vault_x,
-            authority: self.accounts.user,
-            amount: x,
+            .invoke()?,
         }
-        .invoke()?;
-
-        Transfer {
-            from: self.accounts.user_y_ata,
-            to: self.accounts.vault_y,
-            authority: self.accounts.user,
-            amount: y,
+        match native_y {
+            true => wrap(self.accounts.user, self.accounts.vault_y, y)?,
+            false => Transfer {
+                from: self.accounts.user_y_ata,
+                to: self.accounts.vault_y,
+                authority: self.accounts.user,
+                amount: y,
+            }
+            .invoke()?,
         }
-        .invoke()?;
 
         // 向用户的代币账户铸造相应数量的 LP 代币
         // 构建config签名
@@ -237,48 +277,32 @@ impl<'a> Deposit<'a> {
         }
         .invoke_signed(&config_signer)?;
 
+        // 传入了仓位记录时 把这次存入的代币计入成本
+        if let Some(position) = remaining.next() {
+            let mut position = LpPosition::load_mut(position)?;
+            position.check_owner(self.accounts.config, self.accounts.user)?;
+            position.record_deposit(
+                x,
+                y,
+                self.instruction_data.amount,
+                Clock::get()?.unix_timestamp,
+            )?;
+        }
         Ok(())
     }
 
-    // 这个是根据git上直接搬过来的 错误类型改了一下
-    // x: 库存的x代币数量
-    // y: 库存的y代币数量
-    // l: 流动性代币lp的流通量
-    // a: 用户希望接收的 LP 代币数量
-    // precision: 精度
-    fn xy_deposit_amounts_from_l(
-        x: u64,
-        y: u64,
-        l: u64,
-        a: u64,
-        precision: u32,
+    // 根据储备计算存入 amount 个 LP 需要支付的 x y 数量 Deposit 和 QuoteDeposit 共用
+    pub fn quote(
+        reserve_x: u64,
+        reserve_y: u64,
+        supply: u64,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
     ) -> Result<(u64, u64), ProgramError> {
-        // ((已经流通的lp数量 + 用户希望接收的lp数量) * 精度) / 已经流动的lp数量
-        // 这里计算得到的是 用户如果想要拿到希望数量的lp所需要支付的对应x和y代币占库存的占比
-        // 由于分母比分子大 所以这里的ratio必定大于1
-        let ratio = (l as u128)
-            .checked_add(a as u128)
-            .ok_or(ProgramError::InvalidInstructionData)?
-            .checked_mul(precision as u128)
-            .ok_or(ProgramError::InvalidInstructionData)?
-            .checked_div(l as u128)
-            .ok_or(ProgramError::InvalidInstructionData)?;
-        // ((库存代币x数量 * 比例) / 精度) - 库存x代币的数量 = 用户需要支付的x代币数量
-        let deposit_x = (x as u128)
-            .checked_mul(ratio)
-            .ok_or(ProgramError::InvalidInstructionData)?
-            .checked_div(precision as u128)
-            .ok_or(ProgramError::InvalidInstructionData)?
-            .checked_sub(x as u128)
-            .ok_or(ProgramError::InvalidInstructionData)? as u64;
-        // ((库存代币y数量 * 比例) / 精度) - 库存y代币的数量 = 用户需要支付的y代币数量
-        let deposit_y = (y as u128)
-            .checked_mul(ratio)
-            .ok_or(ProgramError::InvalidInstructionData)?
-            .checked_div(precision as u128)
-            .ok_or(ProgramError::InvalidInstructionData)?
-            .checked_sub(y as u128)
-            .ok_or(ProgramError::InvalidInstructionData)? as u64;
-        Ok((deposit_x, deposit_y))
+        // 存款报价和费率无关
+        ConstantProduct::new(reserve_x, reserve_y, 0)
+            .and_then(|curve| curve.quote_deposit(supply, amount, max_x, max_y))
+            .map_err(curve_error)
     }
 }
@@ -0,0 +1,42 @@
+use crate::instructions::helper::{AccountCheck, ProgramAccount};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+pub struct ExecuteAccounts<'a> {
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 池子的 TWAMM 账户。
+    pub twamm: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ExecuteAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config, twamm] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self { config, twamm })
+    }
+}
+
+pub struct Execute<'a> {
+    pub accounts: ExecuteAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for Execute<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ExecuteAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Execute<'a> {
+    // 任何人都可以调用 把池子的 TWAMM 订单执行到当前时间
+    pub fn process(&self) -> ProgramResult {
+        let twamm = core::slice::from_ref(self.accounts.twamm);
+        crate::twamm::settle(self.accounts.config, &mut twamm.iter())
+    }
+}
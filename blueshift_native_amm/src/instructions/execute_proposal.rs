@@ -0,0 +1,77 @@
+use crate::{invoke_proposal, Multisig, Proposal, MAX_PROPOSAL_ACCOUNTS, MAX_PROPOSAL_DATA};
+use pinocchio::cpi::Signer;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct ExecuteProposalAccounts<'a> {
+    // 多签账户 执行时作为 AMM 管理指令的管理员签名。
+    pub multisig: &'a AccountView,
+    // 已经收集到足够批准的提案。
+    pub proposal: &'a AccountView,
+    // 提案中记录的全部账户 顺序必须一致
+    pub remaining: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ExecuteProposalAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [multisig, proposal, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        Ok(Self {
+            multisig,
+            proposal,
+            remaining,
+        })
+    }
+}
+
+pub struct ExecuteProposal<'a> {
+    pub accounts: ExecuteProposalAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ExecuteProposal<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ExecuteProposalAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ExecuteProposal<'a> {
+    // 任何人都可以执行已经达到门槛的提案
+    pub fn process(&self) -> ProgramResult {
+        let multisig = Multisig::load(self.accounts.multisig)?;
+        let creator = multisig.creator().clone();
+        let seed_binding = multisig.seed().to_le_bytes();
+        let bump_binding = multisig.bump();
+        let threshold = multisig.threshold();
+        drop(multisig);
+
+        // 先标记为已执行 再把指令数据和账户拷贝出来 CPI 的时候提案不能处于借用状态
+        let mut data = [0u8; MAX_PROPOSAL_DATA];
+        let mut expected: [Address; MAX_PROPOSAL_ACCOUNTS] =
+            core::array::from_fn(|_| Address::default());
+        let (data_len, account_count) = {
+            let mut proposal = Proposal::load_mut(self.accounts.proposal)?;
+            if proposal.multisig().ne(self.accounts.multisig.address()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            proposal.mark_executed(threshold)?;
+            data[..proposal.data().len()].copy_from_slice(proposal.data());
+            expected[..proposal.accounts().len()].clone_from_slice(proposal.accounts());
+            (proposal.data().len(), proposal.accounts().len())
+        };
+
+        let multisig_seeds = Multisig::signer_seeds(&creator, &seed_binding, &bump_binding);
+        let multisig_signer = [Signer::from(&multisig_seeds)];
+        invoke_proposal(
+            self.accounts.multisig,
+            &expected[..account_count],
+            &data[..data_len],
+            self.accounts.remaining,
+            &multisig_signer,
+        )
+    }
+}
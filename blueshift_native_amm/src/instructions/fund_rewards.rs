@@ -0,0 +1,112 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount,
+};
+use crate::{Config, Farm};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+use pinocchio_token::instructions::Transfer;
+
+pub struct FundRewardsAccounts<'a> {
+    // AMM 的管理员 必须签名。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 池子的农场账户。
+    pub farm: &'a AccountView,
+    // 管理员的奖励代币账户 奖励从这里转出。
+    pub source: &'a AccountView,
+    // 农场持有的奖励代币账户。
+    pub reward_vault: &'a AccountView,
+    // SPL 代币程序账户。
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for FundRewardsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, farm, source, reward_vault, token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            authority,
+            config,
+            farm,
+            source,
+            reward_vault,
+            token_program,
+        })
+    }
+}
+
+pub struct FundRewardsInstructionData {
+    // 注入的奖励数量 可以为 0 只调整释放速度
+    pub amount: u64,
+    // 新的每秒释放数量
+    pub reward_rate: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for FundRewardsInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let reward_rate = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        Ok(Self {
+            amount,
+            reward_rate,
+        })
+    }
+}
+
+pub struct FundRewards<'a> {
+    pub accounts: FundRewardsAccounts<'a>,
+    pub instruction_data: FundRewardsInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for FundRewards<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = FundRewardsAccounts::try_from(accounts)?;
+        let instruction_data = FundRewardsInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> FundRewards<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+
+        let mut farm = Farm::load_mut(self.accounts.farm)?;
+        if farm.config().ne(self.accounts.config.address())
+            || farm.reward_vault().ne(self.accounts.reward_vault.address())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // 先按照旧的速度结算到现在 再修改速度
+        farm.update(Clock::get()?.unix_timestamp)?;
+        farm.add_rewards(self.instruction_data.amount)?;
+        farm.set_reward_rate(self.instruction_data.reward_rate);
+        drop(farm);
+
+        if self.instruction_data.amount > 0 {
+            Transfer {
+                from: self.accounts.source,
+                to: self.accounts.reward_vault,
+                authority: self.accounts.authority,
+                amount: self.instruction_data.amount,
+            }
+            .invoke()?;
+        }
+        Ok(())
+    }
+}
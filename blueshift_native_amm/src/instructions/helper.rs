@@ -1,12 +1,13 @@
 use pinocchio::{
     cpi::{Seed, Signer},
     error::ProgramError,
-    sysvars::{rent::Rent, Sysvar},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     AccountView, Address, ProgramResult,
 };
-use pinocchio_associated_token_account::instructions::Create;
 use pinocchio_system::instructions::CreateAccount;
 
+use crate::state::{AmmState, Config};
+
 pub trait AccountCheck {
     fn check(account: &AccountView) -> Result<(), ProgramError>;
 }
@@ -22,6 +23,59 @@ impl AccountCheck for SignerAccount {
     }
 }
 
+pub trait ConfigAuthorityCheck {
+    fn check(authority: &AccountView, config: &Config) -> Result<(), ProgramError>;
+}
+
+// 校验签名者是不是 config 中记录的管理员
+// 没有设置管理员的池子是不可变的 任何管理指令都会失败
+pub struct ConfigAuthority;
+
+impl ConfigAuthorityCheck for ConfigAuthority {
+    fn check(authority: &AccountView, config: &Config) -> Result<(), ProgramError> {
+        SignerAccount::check(authority)?;
+        match config.has_authority() {
+            Some(address) if address.eq(authority.address()) => Ok(()),
+            _ => Err(ProgramError::IncorrectAuthority),
+        }
+    }
+}
+
+// 校验签名者能不能把池子切换到 state
+// 管理员可以切换到任何状态 守护者只能暂停池子（Disabled / WithdrawOnly） 不能恢复交易
+pub struct ConfigGuardian;
+
+impl ConfigGuardian {
+    pub fn check(signer: &AccountView, config: &Config, state: u8) -> Result<(), ProgramError> {
+        if ConfigAuthority::check(signer, config).is_ok() {
+            return Ok(());
+        }
+        SignerAccount::check(signer)?;
+        let pause = state == AmmState::Disabled as u8 || state == AmmState::WithdrawOnly as u8;
+        match config.has_guardian() {
+            Some(address) if pause && address.eq(signer.address()) => Ok(()),
+            _ => Err(ProgramError::IncorrectAuthority),
+        }
+    }
+}
+
+// 校验订单有没有过期 unix 时间戳和 slot 两种截止时间可以同时使用
+// expiration_slot 为 0 表示不限制 slot
+pub struct Deadline;
+
+impl Deadline {
+    pub fn check(expiration: i64, expiration_slot: u64) -> Result<(), ProgramError> {
+        let clock = Clock::get()?;
+        if clock.unix_timestamp > expiration {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if expiration_slot != 0 && clock.slot > expiration_slot {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(())
+    }
+}
+
 pub struct MintInterface;
 
 impl AccountCheck for MintInterface {
@@ -50,94 +104,31 @@ impl AccountCheck for TokenAccount {
     }
 }
 
-pub trait AssociatedTokenAccountCheck {
-    fn check(
+// 用户的代币账户只校验持有者和铸币 不要求是关联账户 用户可以使用任意的代币账户
+pub trait TokenAccountOwnerCheck {
+    fn check_owner(
         account: &AccountView,
-        authority: &AccountView,
+        owner: &AccountView,
         mint: &Address,
-        token_program: &AccountView,
     ) -> Result<(), ProgramError>;
 }
-pub struct AssociatedTokenAccount;
 
-pub trait AssociatedTokenAccountInit {
-    fn init(
+impl TokenAccountOwnerCheck for TokenAccount {
+    fn check_owner(
         account: &AccountView,
-        mint: &AccountView,
-        payer: &AccountView,
         owner: &AccountView,
-        system_program: &AccountView,
-        token_program: &AccountView,
-    ) -> ProgramResult;
-
-    fn init_if_needed(
-        account: &AccountView,
-        mint: &AccountView,
-        payer: &AccountView,
-        owner: &AccountView,
-        system_program: &AccountView,
-        token_program: &AccountView,
-    ) -> ProgramResult;
-}
-
-impl AssociatedTokenAccountCheck for AssociatedTokenAccount {
-    fn check(
-        account: &AccountView,
-        authority: &AccountView,
         mint: &Address,
-        token_program: &AccountView,
     ) -> Result<(), ProgramError> {
         TokenAccount::check(account)?;
-        let (address, _) = Address::find_program_address(
-            &[
-                authority.address().as_ref(),
-                token_program.address().as_ref(),
-                mint.as_ref(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-        if address.ne(account.address()) {
+        // from_account_view 同时确认账户属于代币程序
+        let token_account = pinocchio_token::state::TokenAccount::from_account_view(account)?;
+        if token_account.owner().ne(owner.address()) || token_account.mint().ne(mint) {
             return Err(ProgramError::InvalidAccountData);
         }
         Ok(())
     }
 }
 
-impl AssociatedTokenAccountInit for AssociatedTokenAccount {
-    fn init(
-        account: &AccountView,
-        mint: &AccountView,
-        payer: &AccountView,
-        owner: &AccountView,
-        system_program: &AccountView,
-        token_program: &AccountView,
-    ) -> ProgramResult {
-        Create {
-            funding_account: payer,
-            account,
-            wallet: owner,
-            mint,
-            system_program,
-            token_program,
-        }
-        .invoke()
-    }
-
-    fn init_if_needed(
-        account: &AccountView,
-        mint: &AccountView,
-        payer: &AccountView,
-        owner: &AccountView,
-        system_program: &AccountView,
-        token_program: &AccountView,
-    ) -> ProgramResult {
-        match Self::check(account, payer, mint.address(), token_program) {
-            Ok(_) => Ok(()),
-            Err(_) => Self::init(account, mint, payer, owner, system_program, token_program),
-        }
-    }
-}
-
 pub struct ProgramAccount;
 
 pub trait ProgramAccountInit {
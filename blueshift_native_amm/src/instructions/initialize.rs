@@ -1,6 +1,6 @@
 use crate::{
     instructions::helper::{AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount},
-    Config,
+    Config, Settings,
 };
 use pinocchio::{
     cpi::{Seed, Signer},
@@ -8,7 +8,7 @@ use pinocchio::{
     sysvars::{rent::Rent, Sysvar},
     AccountView, Address, ProgramResult,
 };
-use pinocchio_system::instructions::CreateAccount;
+use pinocchio_system::instructions::{CreateAccount, Transfer};
 use pinocchio_token::{instructions::InitializeMint2, state::Mint};
 use std::mem::MaybeUninit;
 
@@ -19,13 +19,15 @@ pub struct InitializeAccounts<'a> {
     pub mint_lp: &'a AccountView,
     // 正在初始化的配置账户。
     pub config: &'a AccountView,
+    // 全局设置账户 规定允许的费率档位 同时接收创建费。
+    pub settings: &'a AccountView,
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [initializer, mint_lp, config, _, _] = accounts else {
+        let [initializer, mint_lp, config, _, _, settings] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
@@ -34,6 +36,7 @@ impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
             initializer,
             mint_lp,
             config,
+            settings,
         })
     }
 }
@@ -108,6 +111,23 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Initialize<'a> {
 
 impl<'a> Initialize<'a> {
     pub fn process(&self) -> ProgramResult {
+        // 只能使用协议管理员规定的费率档位
+        let settings = Settings::check(self.accounts.settings)?;
+        if !settings.is_allowed_fee(self.instruction_data.fee) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let creation_fee = settings.creation_fee();
+        drop(settings);
+        // 创建费转入设置账户 由管理员通过 CollectCreationFees 取出
+        if creation_fee > 0 {
+            Transfer {
+                from: self.accounts.initializer,
+                to: self.accounts.settings,
+                lamports: creation_fee,
+            }
+            .invoke()?;
+        }
+
         let seed_binding = self.instruction_data.seed.to_le_bytes();
         let config_seeds = [
             Seed::from(b"config"),
@@ -139,7 +159,7 @@ impl<'a> Initialize<'a> {
         }
         .invoke_signed(&config_signer)?;
         // Populate the escrow account
-        let mut config = Config::load_mut(self.accounts.config)?;
+        let mut config = Config::init(self.accounts.config)?;
 
         // 填充config数据
         config.set_inner(
@@ -150,6 +170,27 @@ impl<'a> Initialize<'a> {
             self.instruction_data.fee,
             self.instruction_data.config_bump,
         )?;
+        config.set_initializer(self.accounts.initializer.address().clone())?;
+        // 金库是 config 持有的 ATA 在这里推导一次并记录下来
+        // 之后的 Swap / Deposit / Withdraw 只需要比较地址 不用每次都调用 find_program_address
+        let (vault_x, _) = Address::find_program_address(
+            &[
+                self.accounts.config.address().as_ref(),
+                pinocchio_token::ID.as_ref(),
+                &self.instruction_data.mint_x,
+            ],
+            &pinocchio_associated_token_account::ID,
+        );
+        let (vault_y, _) = Address::find_program_address(
+            &[
+                self.accounts.config.address().as_ref(),
+                pinocchio_token::ID.as_ref(),
+                &self.instruction_data.mint_y,
+            ],
+            &pinocchio_associated_token_account::ID,
+        );
+        config.set_vaults(vault_x, vault_y)?;
+        config.set_mint_lp(self.accounts.mint_lp.address().clone())?;
         // 创建 mint_lp
         let lp_lamports = Rent::get()?.try_minimum_balance(Mint::LEN)?;
 
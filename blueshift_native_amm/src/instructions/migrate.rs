@@ -0,0 +1,170 @@
+use crate::instructions::helper::{AccountCheck, MintInterface, SignerAccount};
+use crate::Config;
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_system::instructions::Transfer;
+use pinocchio_token::state::{Mint, TokenAccount};
+
+pub struct MigrateAccounts<'a> {
+    // 支付新增租金的账户 必须签名。
+    pub payer: &'a AccountView,
+    // 需要升级布局的配置账户。
+    pub config: &'a AccountView,
+    // 代表池流动性的铸币账户。
+    pub mint_lp: &'a AccountView,
+    // 存储所有存入池中的 X 代币的代币账户。旧布局没有记录储备时用来恢复储备。
+    pub vault_x: &'a AccountView,
+    // 存储所有存入池中的 Y 代币的代币账户。
+    pub vault_y: &'a AccountView,
+    // 系统程序账户。
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for MigrateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [payer, config, mint_lp, vault_x, vault_y, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(payer)?;
+        MintInterface::check(mint_lp)?;
+        if !config.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Self {
+            payer,
+            config,
+            mint_lp,
+            vault_x,
+            vault_y,
+            system_program,
+        })
+    }
+}
+
+pub struct Migrate<'a> {
+    pub accounts: MigrateAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for Migrate<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = MigrateAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Migrate<'a> {
+    // 任何人都可以调用 把旧布局的 config 升级到当前版本 config 和 LP 铸币的地址都会重新推导校验 升级本身不会改变池子的任何参数
+    pub fn process(&self) -> ProgramResult {
+        let data_len = self.accounts.config.data_len();
+
+        // 已经是带版本号的布局 新字段都在 reserved 里 默认值为 0 只需要更新版本号
+        if data_len == Config::LEN {
+            let mut config = Config::load_mut(self.accounts.config)?;
+            return config.set_version(Config::VERSION);
+        }
+        // 只接受真正的旧布局 长度落在同一范围的其他账户（农场、仓位记录等）不能被改写成 config
+        if data_len != Config::LEGACY_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 先把旧数据拷贝出来 扩容之后整体向后挪一个字节 给 version 让出位置
+        let mut legacy = [0u8; Config::LEGACY_LEN];
+        legacy.copy_from_slice(&self.accounts.config.try_borrow()?);
+        Self::check_legacy_address(self.accounts.config, &legacy)?;
+
+        // 补足扩容之后需要的租金
+        let lamports = Rent::get()?.try_minimum_balance(Config::LEN)?;
+        let top_up = lamports.saturating_sub(self.accounts.config.lamports());
+        if top_up > 0 {
+            Transfer {
+                from: self.accounts.payer,
+                to: self.accounts.config,
+                lamports: top_up,
+            }
+            .invoke()?;
+        }
+        self.accounts.config.resize(Config::LEN)?;
+        {
+            let mut data = self.accounts.config.try_borrow_mut()?;
+            data[0] = Config::VERSION;
+            data[1..1 + Config::LEGACY_LEN].copy_from_slice(&legacy);
+        }
+
+        let mut config = Config::load_mut(self.accounts.config)?;
+        // 旧布局没有缓存金库和 LP 铸币的地址 这里推导一次补上
+        // 任何人都可以创建一个铸币权限为 config 的铸币 所以 LP 铸币必须是推导出的 PDA
+        let (mint_lp, _) = Address::find_program_address(
+            &[b"mint_lp", self.accounts.config.address().as_ref()],
+            &crate::ID,
+        );
+        if self.accounts.mint_lp.address().ne(&mint_lp)
+            || Mint::from_account_view(self.accounts.mint_lp)?.mint_authority()
+                != Some(self.accounts.config.address())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (vault_x, _) = Address::find_program_address(
+            &[
+                self.accounts.config.address().as_ref(),
+                pinocchio_token::ID.as_ref(),
+                config.mint_x().as_ref(),
+            ],
+            &pinocchio_associated_token_account::ID,
+        );
+        let (vault_y, _) = Address::find_program_address(
+            &[
+                self.accounts.config.address().as_ref(),
+                pinocchio_token::ID.as_ref(),
+                config.mint_y().as_ref(),
+            ],
+            &pinocchio_associated_token_account::ID,
+        );
+        if self.accounts.vault_x.address().ne(&vault_x)
+            || self.accounts.vault_y.address().ne(&vault_y)
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        config.set_vaults(vault_x, vault_y)?;
+        config.set_mint_lp(self.accounts.mint_lp.address().clone())?;
+
+        // 旧布局没有记录储备 用金库当前的余额作为储备
+        // 还没有注入过流动性的池子 金库可能还没有创建 储备保持为 0
+        let reserve_x = Self::vault_amount(self.accounts.vault_x)?;
+        let reserve_y = Self::vault_amount(self.accounts.vault_y)?;
+        config.set_reserves(reserve_x, reserve_y)
+    }
+
+    // 用旧数据中的 seed、两个铸币和 bump 重新推导 config 的地址 必须和传入的账户一致
+    // 旧布局: state u8 | seed [u8; 8] | authority | mint_x | mint_y | fee [u8; 2] | config_bump u8
+    fn check_legacy_address(
+        config: &AccountView,
+        legacy: &[u8; Config::LEGACY_LEN],
+    ) -> ProgramResult {
+        let address = Address::create_program_address(
+            &[
+                b"config",
+                &legacy[1..9],
+                &legacy[41..73],
+                &legacy[73..105],
+                &legacy[107..108],
+            ],
+            &crate::ID,
+        )?;
+        if address.ne(config.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn vault_amount(vault: &AccountView) -> Result<u64, ProgramError> {
+        if vault.is_data_empty() {
+            return Ok(0);
+        }
+        Ok(TokenAccount::from_account_view(vault)?.amount())
+    }
+}
@@ -1,10 +1,78 @@
+pub mod add_trader;
+pub mod approve;
+pub mod claim;
+pub mod close_pool;
+pub mod collect_creation_fees;
+pub mod create_farm;
+pub mod create_multisig;
+pub mod create_settings;
+pub mod create_twamm;
 pub mod deposit;
+pub mod execute;
+pub mod execute_proposal;
+pub mod fund_rewards;
 pub mod initialize;
+pub mod migrate;
+pub mod open_position;
+pub mod place_order;
+pub mod propose;
+pub mod quote_deposit;
+pub mod quote_position;
+pub mod quote_swap;
+pub mod quote_withdraw;
+pub mod remove_trader;
+pub mod set_authority;
+pub mod set_guardian;
+pub mod set_hook;
+pub mod set_oracle;
+pub mod set_permissioned;
+pub mod set_price_band;
+pub mod set_state;
+pub mod skim;
+pub mod stake;
 pub mod swap;
+pub mod sync;
+pub mod unstake;
+pub mod update_settings;
 pub mod withdraw;
+pub mod withdraw_proceeds;
 mod helper;
 
+pub use add_trader::*;
+pub use approve::*;
+pub use claim::*;
+pub use close_pool::*;
+pub use collect_creation_fees::*;
+pub use create_farm::*;
+pub use create_multisig::*;
+pub use create_settings::*;
+pub use create_twamm::*;
 pub use deposit::*;
+pub use execute::*;
+pub use execute_proposal::*;
+pub use fund_rewards::*;
 pub use initialize::*;
+pub use migrate::*;
+pub use open_position::*;
+pub use place_order::*;
+pub use propose::*;
+pub use quote_deposit::*;
+pub use quote_position::*;
+pub use quote_swap::*;
+pub use quote_withdraw::*;
+pub use remove_trader::*;
+pub use set_authority::*;
+pub use set_guardian::*;
+pub use set_hook::*;
+pub use set_oracle::*;
+pub use set_permissioned::*;
+pub use set_price_band::*;
+pub use set_state::*;
+pub use skim::*;
+pub use stake::*;
 pub use swap::*;
-pub use withdraw::*;
\ No newline at end of file
+pub use sync::*;
+pub use unstake::*;
+pub use update_settings::*;
+pub use withdraw::*;
+pub use withdraw_proceeds::*;
@@ -0,0 +1,88 @@
+use crate::instructions::helper::{
+    AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount,
+};
+use crate::LpPosition;
+use pinocchio::cpi::Seed;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct OpenPositionAccounts<'a> {
+    // 仓位的所有者 必须签名 同时支付仓位记录的租金。
+    pub user: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 仓位记录 PDA seeds = [b"position", config, user]
+    pub position: &'a AccountView,
+    // 系统程序 创建仓位记录需要。
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for OpenPositionAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [user, config, position, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(user)?;
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            user,
+            config,
+            position,
+            system_program,
+        })
+    }
+}
+
+pub struct OpenPosition<'a> {
+    pub accounts: OpenPositionAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for OpenPosition<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = OpenPositionAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> OpenPosition<'a> {
+    // 创建之后 Deposit / Withdraw 在可选账户中带上仓位记录就会更新成本
+    pub fn process(&self) -> ProgramResult {
+        let (position_address, bump) = Address::find_program_address(
+            &[
+                b"position",
+                self.accounts.config.address().as_ref(),
+                self.accounts.user.address().as_ref(),
+            ],
+            &crate::ID,
+        );
+        if position_address.ne(self.accounts.position.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let bump_binding = [bump];
+        let position_seeds = [
+            Seed::from(b"position"),
+            Seed::from(self.accounts.config.address().as_ref()),
+            Seed::from(self.accounts.user.address().as_ref()),
+            Seed::from(&bump_binding),
+        ];
+        // 账户已经存在的话 CreateAccount 会失败 所以不会重复创建
+        ProgramAccount::init::<LpPosition>(
+            self.accounts.user,
+            self.accounts.position,
+            &position_seeds,
+            LpPosition::LEN,
+        )?;
+
+        LpPosition::load_mut(self.accounts.position)?.set_inner(
+            self.accounts.config.address().clone(),
+            self.accounts.user.address().clone(),
+            bump_binding,
+            Clock::get()?.unix_timestamp,
+        );
+        Ok(())
+    }
+}
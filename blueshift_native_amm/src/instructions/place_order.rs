@@ -0,0 +1,271 @@
+use crate::instructions::helper::{
+    AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount,
+};
+use crate::twamm::{settle, RATE_PRECISION};
+use crate::{AllowlistEntry, AmmState, Config, Twamm, TwammOrder};
+use pinocchio::cpi::Seed;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_system::instructions::Transfer as SystemTransfer;
+use pinocchio_token::instructions::Transfer;
+
+pub struct PlaceOrderAccounts<'a> {
+    // 下单的用户 必须签名 同时支付订单账户和 TWAMM 账户扩容的租金。
+    pub user: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 池子的 TWAMM 账户。
+    pub twamm: &'a AccountView,
+    // 订单账户 PDA seeds = [b"twamm_order", config, user, order_id]
+    pub order: &'a AccountView,
+    // 用户卖出代币的代币账户。
+    pub user_ata: &'a AccountView,
+    // 卖出代币对应的池子金库 卖 X 时为 vault_x 卖 Y 时为 vault_y。
+    pub vault: &'a AccountView,
+    // SPL 代币程序账户。
+    pub token_program: &'a AccountView,
+    // 系统程序 创建订单账户和给 TWAMM 账户补租金需要。
+    pub system_program: &'a AccountView,
+    // 可选账户。许可池中需要传入用户的白名单记录。
+    pub remaining: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for PlaceOrderAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [user, config, twamm, order, user_ata, vault, token_program, system_program, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(user)?;
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            user,
+            config,
+            twamm,
+            order,
+            user_ata,
+            vault,
+            token_program,
+            system_program,
+            remaining,
+        })
+    }
+}
+
+pub struct PlaceOrderInstructionData {
+    // 用户自己选择的订单编号 同一个用户在同一个池子中不能重复
+    pub order_id: u64,
+    // true 表示卖出 X 换 Y false 表示卖出 Y 换 X
+    pub is_x: bool,
+    // 卖出的总数量 速度向下取整之后实际托管的数量可能略少 零头留在用户手里
+    pub amount: u64,
+    // 卖出持续的秒数 结束时间会向上对齐 见 Twamm::end_time
+    pub duration: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for PlaceOrderInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 3 + size_of::<u8>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let order_id = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let is_x = data[8] != 0;
+        let amount = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let duration = u64::from_le_bytes(data[17..25].try_into().unwrap());
+        if amount == 0 || duration == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self {
+            order_id,
+            is_x,
+            amount,
+            duration,
+        })
+    }
+}
+
+pub struct PlaceOrder<'a> {
+    pub accounts: PlaceOrderAccounts<'a>,
+    pub instruction_data: PlaceOrderInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for PlaceOrder<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = PlaceOrderAccounts::try_from(accounts)?;
+        let instruction_data = PlaceOrderInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> PlaceOrder<'a> {
+    pub fn process(&self) -> ProgramResult {
+        // 修改卖出速度之前 先把已有的订单执行到现在
+        let twamm = core::slice::from_ref(self.accounts.twamm);
+        settle(self.accounts.config, &mut twamm.iter())?;
+
+        let mut config = Config::load_mut(self.accounts.config)?;
+        if config.state() != (AmmState::Initialized as u8) || !config.has_twamm() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if config.is_permissioned() {
+            let entry = self
+                .accounts
+                .remaining
+                .first()
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            AllowlistEntry::check(entry, self.accounts.config, self.accounts.user)?;
+        }
+        let is_x = self.instruction_data.is_x;
+        let vault = match is_x {
+            true => config.vault_x(),
+            false => config.vault_y(),
+        };
+        if self.accounts.vault.address().ne(vault) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 按照对齐之后的实际时长计算卖出速度
+        let now = Clock::get()?.unix_timestamp;
+        let end_time = Twamm::end_time(now, self.instruction_data.duration)?;
+        let sell_rate = (self.instruction_data.amount as u128)
+            .checked_mul(RATE_PRECISION)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / (end_time - now) as u128;
+        if sell_rate == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let needs_snapshot = {
+            let twamm = Twamm::load(self.accounts.twamm)?;
+            twamm.check_config(self.accounts.config)?;
+            twamm.needs_snapshot(end_time)
+        };
+        if needs_snapshot {
+            self.grow_twamm()?;
+        }
+        let mut twamm = Twamm::load_mut(self.accounts.twamm)?;
+        let snapshot = twamm.add_order(is_x, sell_rate, end_time)?;
+        let earnings_start = twamm.earnings(is_x);
+        let live_start = twamm.live_time();
+        drop(twamm);
+        // 速度向下取整之后 只托管实际会卖出的数量
+        let amount = Twamm::escrow_amount(sell_rate, end_time - now)?;
+
+        // 卖出的代币在执行之前由 TWAMM 托管 不计入储备
+        let (escrow_x, escrow_y) = match is_x {
+            true => (
+                config.twamm_escrow_x().checked_add(amount),
+                Some(config.twamm_escrow_y()),
+            ),
+            false => (
+                Some(config.twamm_escrow_x()),
+                config.twamm_escrow_y().checked_add(amount),
+            ),
+        };
+        config.set_twamm_escrow(
+            escrow_x.ok_or(ProgramError::ArithmeticOverflow)?,
+            escrow_y.ok_or(ProgramError::ArithmeticOverflow)?,
+        )?;
+        drop(config);
+
+        self.init_order(
+            is_x,
+            sell_rate,
+            earnings_start,
+            now,
+            live_start,
+            end_time,
+            snapshot,
+        )?;
+
+        Transfer {
+            from: self.accounts.user_ata,
+            to: self.accounts.vault,
+            authority: self.accounts.user,
+            amount,
+        }
+        .invoke()
+    }
+
+    // 没有空闲的快照时给 TWAMM 账户增加一个快照的位置 租金由下单的用户支付
+    fn grow_twamm(&self) -> ProgramResult {
+        let snapshots = Twamm::load(self.accounts.twamm)?.snapshot_count();
+        let space = Twamm::space(snapshots + 1);
+        let lamports = Rent::get()?.try_minimum_balance(space)?;
+        let top_up = lamports.saturating_sub(self.accounts.twamm.lamports());
+        if top_up > 0 {
+            SystemTransfer {
+                from: self.accounts.user,
+                to: self.accounts.twamm,
+                lamports: top_up,
+            }
+            .invoke()?;
+        }
+        self.accounts.twamm.resize(space)?;
+        let mut data = self.accounts.twamm.try_borrow_mut()?;
+        unsafe { Twamm::from_bytes_unchecked_mut(&mut data) }.add_snapshot()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn init_order(
+        &self,
+        is_x: bool,
+        sell_rate: u128,
+        earnings_start: u128,
+        start_time: i64,
+        live_start: i64,
+        end_time: i64,
+        snapshot: u32,
+    ) -> ProgramResult {
+        let order_id = self.instruction_data.order_id.to_le_bytes();
+        let (order_address, bump) = Address::find_program_address(
+            &[
+                b"twamm_order",
+                self.accounts.config.address().as_ref(),
+                self.accounts.user.address().as_ref(),
+                &order_id,
+            ],
+            &crate::ID,
+        );
+        if order_address.ne(self.accounts.order.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let bump_binding = [bump];
+        let order_seeds = [
+            Seed::from(b"twamm_order"),
+            Seed::from(self.accounts.config.address().as_ref()),
+            Seed::from(self.accounts.user.address().as_ref()),
+            Seed::from(&order_id),
+            Seed::from(&bump_binding),
+        ];
+        // 订单编号已经被使用的话 CreateAccount 会失败
+        ProgramAccount::init::<TwammOrder>(
+            self.accounts.user,
+            self.accounts.order,
+            &order_seeds,
+            TwammOrder::LEN,
+        )?;
+        TwammOrder::load_mut(self.accounts.order)?.set_inner(
+            self.accounts.config.address().clone(),
+            self.accounts.user.address().clone(),
+            is_x,
+            sell_rate,
+            earnings_start,
+            start_time,
+            live_start,
+            end_time,
+            snapshot,
+            bump_binding,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use crate::instructions::helper::{
+    AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount,
+};
+use crate::{Multisig, Proposal};
+use pinocchio::cpi::Seed;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct ProposeAccounts<'a> {
+    // 提交提案的多签成员 必须签名 同时支付提案账户的租金。提交即视为批准。
+    pub proposer: &'a AccountView,
+    // 多签账户。
+    pub multisig: &'a AccountView,
+    // 提案账户 PDA seeds = [b"proposal", multisig, index]
+    pub proposal: &'a AccountView,
+    // 系统程序 创建提案账户需要。
+    pub system_program: &'a AccountView,
+    // 提案执行时需要传给 AMM 指令的全部账户 按指令要求的顺序排列
+    pub remaining: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ProposeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [proposer, multisig, proposal, system_program, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(proposer)?;
+        Ok(Self {
+            proposer,
+            multisig,
+            proposal,
+            system_program,
+            remaining,
+        })
+    }
+}
+
+pub struct Propose<'a> {
+    pub accounts: ProposeAccounts<'a>,
+    // 提案执行时的 AMM 指令数据 包括开头的指令编号
+    pub instruction_data: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Propose<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ProposeAccounts::try_from(accounts)?;
+        Ok(Self {
+            accounts,
+            instruction_data: data,
+        })
+    }
+}
+
+impl<'a> Propose<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut multisig = Multisig::load_mut(self.accounts.multisig)?;
+        let signer_index = multisig.signer_index(self.accounts.proposer.address())?;
+        let index = multisig.next_proposal()?;
+        drop(multisig);
+
+        let index_binding = index.to_le_bytes();
+        let (proposal_address, bump) = Address::find_program_address(
+            &[
+                b"proposal",
+                self.accounts.multisig.address().as_ref(),
+                &index_binding,
+            ],
+            &crate::ID,
+        );
+        if proposal_address.ne(self.accounts.proposal.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let bump_binding = [bump];
+        let proposal_seeds = [
+            Seed::from(b"proposal"),
+            Seed::from(self.accounts.multisig.address().as_ref()),
+            Seed::from(&index_binding),
+            Seed::from(&bump_binding),
+        ];
+        ProgramAccount::init::<Proposal>(
+            self.accounts.proposer,
+            self.accounts.proposal,
+            &proposal_seeds,
+            Proposal::LEN,
+        )?;
+        let mut proposal = Proposal::load_mut(self.accounts.proposal)?;
+        proposal.set_inner(
+            self.accounts.multisig.address().clone(),
+            index,
+            self.accounts.proposer.address().clone(),
+            self.instruction_data,
+            self.accounts.remaining,
+            bump_binding,
+        )?;
+        proposal.approve(signer_index)
+    }
+}
@@ -0,0 +1,110 @@
+use crate::instructions::helper::{AccountCheck, MintInterface, ProgramAccount};
+use crate::twamm::preview;
+use crate::{Config, Deposit};
+use pinocchio::cpi::set_return_data;
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+use pinocchio_token::state::Mint;
+
+pub struct QuoteDepositAccounts<'a> {
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 代表池流动性的铸币账户。
+    pub mint_lp: &'a AccountView,
+    // 可选账户。池子启用了 TWAMM 时需要传入 TWAMM 账户 先模拟结算长期订单再报价。
+    pub remaining: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for QuoteDepositAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config, mint_lp, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        MintInterface::check(mint_lp)?;
+        Ok(Self {
+            config,
+            mint_lp,
+            remaining,
+        })
+    }
+}
+
+pub struct QuoteDepositInstructionData {
+    // 用户希望接收的 LP 代币数量
+    pub amount: u64,
+    // 用户愿意存入的最大 Token X 数量 首次注入流动性时就是实际存入的数量
+    pub max_x: u64,
+    // 用户愿意存入的最大 Token Y 数量 首次注入流动性时就是实际存入的数量
+    pub max_y: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for QuoteDepositInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 3 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let max_x = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let max_y = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self {
+            amount,
+            max_x,
+            max_y,
+        })
+    }
+}
+
+pub struct QuoteDeposit<'a> {
+    pub accounts: QuoteDepositAccounts<'a>,
+    pub instruction_data: QuoteDepositInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for QuoteDeposit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = QuoteDepositAccounts::try_from(accounts)?;
+        let instruction_data = QuoteDepositInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> QuoteDeposit<'a> {
+    // 只读指令 不移动任何资金 通过 return data 返回
+    // [amount_x: u64, amount_y: u64, amount_lp: u64]
+    pub fn process(&self) -> ProgramResult {
+        // 和 Deposit 一样先把 TWAMM 订单执行到现在 只是不写回账户
+        let (reserve_x, reserve_y) =
+            preview(self.accounts.config, &mut self.accounts.remaining.iter())?;
+        let config = Config::load(self.accounts.config)?;
+        let mint_lp = Mint::from_account_view(self.accounts.mint_lp)?;
+        if self.accounts.mint_lp.address().ne(config.mint_lp()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (x, y) = Deposit::quote(
+            reserve_x,
+            reserve_y,
+            mint_lp.supply(),
+            self.instruction_data.amount,
+            self.instruction_data.max_x,
+            self.instruction_data.max_y,
+        )?;
+
+        let mut data = [0u8; size_of::<u64>() * 3];
+        data[0..8].copy_from_slice(&x.to_le_bytes());
+        data[8..16].copy_from_slice(&y.to_le_bytes());
+        data[16..24].copy_from_slice(&self.instruction_data.amount.to_le_bytes());
+        set_return_data(&data);
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use crate::instructions::helper::{AccountCheck, MintInterface, ProgramAccount};
+use crate::{Config, LpPosition, Withdraw};
+use pinocchio::cpi::set_return_data;
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+use pinocchio_token::state::Mint;
+
+pub struct QuotePositionAccounts<'a> {
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 代表池流动性的铸币账户。
+    pub mint_lp: &'a AccountView,
+    // 用户的仓位记录。
+    pub position: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for QuotePositionAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config, mint_lp, position, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        MintInterface::check(mint_lp)?;
+        Ok(Self {
+            config,
+            mint_lp,
+            position,
+        })
+    }
+}
+
+pub struct QuotePosition<'a> {
+    pub accounts: QuotePositionAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for QuotePosition<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = QuotePositionAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> QuotePosition<'a> {
+    // 只读指令 按当前储备计算仓位的盈亏 通过 return data 返回
+    // [lp_held: u64, value_x: u64, value_y: u64, cost_x: u64, cost_y: u64,
+    //  unrealized_x: i64, unrealized_y: i64, realized_x: i64, realized_y: i64]
+    // 盈亏按 x y 两种代币分别计算 换算成法币需要链下的价格
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+        let mint_lp = Mint::from_account_view(self.accounts.mint_lp)?;
+        if self.accounts.mint_lp.address().ne(config.mint_lp()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let position = LpPosition::load(self.accounts.position)?;
+        if position.config().ne(self.accounts.config.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 持有的 LP 按现在销毁可以取回的数量估值
+        let lp_held = position.lp_held();
+        let (value_x, value_y) = match lp_held.min(mint_lp.supply()) {
+            0 => (0, 0),
+            amount => Withdraw::quote(
+                config.reserve_x(),
+                config.reserve_y(),
+                mint_lp.supply(),
+                amount,
+            )?,
+        };
+        let (cost_x, cost_y) = position.cost();
+        let (realized_x, realized_y) = position.realized();
+        let to_i64 =
+            |value: i128| i64::try_from(value).map_err(|_| ProgramError::ArithmeticOverflow);
+
+        let mut data = [0u8; size_of::<u64>() * 9];
+        data[0..8].copy_from_slice(&lp_held.to_le_bytes());
+        data[8..16].copy_from_slice(&value_x.to_le_bytes());
+        data[16..24].copy_from_slice(&value_y.to_le_bytes());
+        data[24..32].copy_from_slice(&cost_x.to_le_bytes());
+        data[32..40].copy_from_slice(&cost_y.to_le_bytes());
+        data[40..48].copy_from_slice(&to_i64(value_x as i128 - cost_x as i128)?.to_le_bytes());
+        data[48..56].copy_from_slice(&to_i64(value_y as i128 - cost_y as i128)?.to_le_bytes());
+        data[56..64].copy_from_slice(&to_i64(realized_x)?.to_le_bytes());
+        data[64..72].copy_from_slice(&to_i64(realized_y)?.to_le_bytes());
+        set_return_data(&data);
+        Ok(())
+    }
+}
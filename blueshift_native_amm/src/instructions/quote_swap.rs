@@ -0,0 +1,106 @@
+use crate::instructions::helper::{AccountCheck, ProgramAccount};
+use crate::twamm::preview;
+use crate::{Config, Swap};
+use pinocchio::cpi::set_return_data;
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+pub struct QuoteSwapAccounts<'a> {
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 可选账户。池子启用了 TWAMM 时需要传入 TWAMM 账户 先模拟结算长期订单再报价。
+    pub remaining: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for QuoteSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self { config, remaining })
+    }
+}
+
+pub struct QuoteSwapInstructionData {
+    // 此交换是从代币 X 到代币 Y 或反之进行的。
+    pub is_x: bool,
+    // 用户准备支付的代币数量。
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for QuoteSwapInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<bool>() + size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let is_x = data[0] != 0;
+        let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self { is_x, amount })
+    }
+}
+
+pub struct QuoteSwap<'a> {
+    pub accounts: QuoteSwapAccounts<'a>,
+    pub instruction_data: QuoteSwapInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for QuoteSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = QuoteSwapAccounts::try_from(accounts)?;
+        let instruction_data = QuoteSwapInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> QuoteSwap<'a> {
+    // 只读指令 不移动任何资金 通过 return data 返回
+    // [amount_in: u64, fee: u64, amount_out: u64, price_impact_bps: u64]
+    pub fn process(&self) -> ProgramResult {
+        // 和 Swap 一样先把 TWAMM 订单执行到现在 只是不写回账户
+        let (reserve_x, reserve_y) =
+            preview(self.accounts.config, &mut self.accounts.remaining.iter())?;
+        let config = Config::load(self.accounts.config)?;
+        let (reserve_in, reserve_out) = match self.instruction_data.is_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+        let (amount_in, fee, amount_out) = Swap::quote(
+            reserve_x,
+            reserve_y,
+            config.fee(),
+            self.instruction_data.is_x,
+            self.instruction_data.amount,
+            0,
+        )?;
+
+        // 价格影响：扣除手续费后按现价应得的数量 和实际拿到的数量之间的差距 以基点表示
+        let spot_out = ((amount_in - fee) as u128)
+            .checked_mul(reserve_out as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / reserve_in as u128;
+        let price_impact = match spot_out {
+            0 => 0,
+            _ => spot_out.saturating_sub(amount_out as u128) * 10_000 / spot_out,
+        } as u64;
+
+        let mut data = [0u8; size_of::<u64>() * 4];
+        data[0..8].copy_from_slice(&amount_in.to_le_bytes());
+        data[8..16].copy_from_slice(&fee.to_le_bytes());
+        data[16..24].copy_from_slice(&amount_out.to_le_bytes());
+        data[24..32].copy_from_slice(&price_impact.to_le_bytes());
+        set_return_data(&data);
+        Ok(())
+    }
+}
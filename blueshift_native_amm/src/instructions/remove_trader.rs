@@ -0,0 +1,58 @@
+use crate::instructions::helper::{
+    AccountCheck, AccountClose, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount,
+};
+use crate::{AllowlistEntry, Config};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+pub struct RemoveTraderAccounts<'a> {
+    // AMM 的管理员 必须签名 同时接收白名单记录返还的租金。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 需要移除的白名单记录。
+    pub entry: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for RemoveTraderAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, entry] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            authority,
+            config,
+            entry,
+        })
+    }
+}
+
+pub struct RemoveTrader<'a> {
+    pub accounts: RemoveTraderAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for RemoveTrader<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = RemoveTraderAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> RemoveTrader<'a> {
+    pub fn process(&self) -> ProgramResult {
+        {
+            let config = Config::load(self.accounts.config)?;
+            ConfigAuthority::check(self.accounts.authority, &config)?;
+            let entry = AllowlistEntry::load(self.accounts.entry)?;
+            if entry.config().ne(self.accounts.config.address()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+        // 关闭记录之后 这个钱包只能在 WithdrawOnly 状态下取回流动性
+        ProgramAccount::close(self.accounts.entry, self.accounts.authority)
+    }
+}
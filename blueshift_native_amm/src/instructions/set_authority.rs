@@ -0,0 +1,68 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount,
+};
+use crate::Config;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct SetAuthorityAccounts<'a> {
+    // AMM 的管理员 必须签名。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct SetAuthorityInstructionData {
+    // 新的管理员 可以是单个钱包 也可以是多签账户 全 0 表示放弃管理权限 之后池子不可变
+    pub authority: Address,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetAuthorityInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let authority: [u8; 32] = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        Ok(Self {
+            authority: Address::from(authority),
+        })
+    }
+}
+
+pub struct SetAuthority<'a> {
+    pub accounts: SetAuthorityAccounts<'a>,
+    pub instruction_data: SetAuthorityInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetAuthority<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetAuthorityAccounts::try_from(accounts)?;
+        let instruction_data = SetAuthorityInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetAuthority<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+        config.set_authority(self.instruction_data.authority.clone())
+    }
+}
@@ -0,0 +1,68 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount,
+};
+use crate::Config;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct SetGuardianAccounts<'a> {
+    // AMM 的管理员 必须签名。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetGuardianAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct SetGuardianInstructionData {
+    // 新的守护者 全 0 表示移除守护者
+    pub guardian: Address,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetGuardianInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let guardian: [u8; 32] = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        Ok(Self {
+            guardian: Address::from(guardian),
+        })
+    }
+}
+
+pub struct SetGuardian<'a> {
+    pub accounts: SetGuardianAccounts<'a>,
+    pub instruction_data: SetGuardianInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetGuardian<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetGuardianAccounts::try_from(accounts)?;
+        let instruction_data = SetGuardianInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetGuardian<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+        config.set_guardian(self.instruction_data.guardian.clone())
+    }
+}
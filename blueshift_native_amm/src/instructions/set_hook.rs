@@ -0,0 +1,60 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount,
+};
+use crate::Config;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+pub struct SetHookAccounts<'a> {
+    // AMM 的管理员 必须签名。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 需要注册的 hook 程序。传入系统程序（全 0 地址）表示取消 hook。
+    pub hook_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetHookAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, hook_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            authority,
+            config,
+            hook_program,
+        })
+    }
+}
+
+pub struct SetHook<'a> {
+    pub accounts: SetHookAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetHook<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = SetHookAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> SetHook<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+
+        let hook_program = self.accounts.hook_program.address();
+        if hook_program.eq(&Address::default()) {
+            return config.set_hook_program(Address::default());
+        }
+        // hook 不能是 AMM 自己 否则会出现重入
+        if hook_program.eq(&crate::ID) || !self.accounts.hook_program.executable() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        config.set_hook_program(hook_program.clone())
+    }
+}
@@ -0,0 +1,111 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, MintInterface, ProgramAccount,
+};
+use crate::{Config, OraclePrice};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_token::state::Mint;
+
+pub struct SetOracleAccounts<'a> {
+    // AMM 的管理员 必须签名。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // Pyth 格式的价格账户。传入系统程序（全 0 地址）表示关闭预言机校验。
+    pub oracle: &'a AccountView,
+    // 代币 X 的铸币账户 用于读取小数位。
+    pub mint_x: &'a AccountView,
+    // 代币 Y 的铸币账户 用于读取小数位。
+    pub mint_y: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetOracleAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, oracle, mint_x, mint_y] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        MintInterface::check(mint_x)?;
+        MintInterface::check(mint_y)?;
+        Ok(Self {
+            authority,
+            config,
+            oracle,
+            mint_x,
+            mint_y,
+        })
+    }
+}
+
+pub struct SetOracleInstructionData {
+    // 成交价格与预言机价格允许的最大偏差 以基点表示
+    pub max_deviation: u16,
+    // 预言机价格允许的最大延迟（秒）
+    pub max_age: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetOracleInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() + size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let max_deviation = u16::from_le_bytes(data[0..2].try_into().unwrap());
+        let max_age = u64::from_le_bytes(data[2..10].try_into().unwrap());
+        Ok(Self {
+            max_deviation,
+            max_age,
+        })
+    }
+}
+
+pub struct SetOracle<'a> {
+    pub accounts: SetOracleAccounts<'a>,
+    pub instruction_data: SetOracleInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetOracle<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetOracleAccounts::try_from(accounts)?;
+        let instruction_data = SetOracleInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetOracle<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+
+        // 传入全 0 地址 关闭预言机校验
+        if self.accounts.oracle.address().eq(&Address::default()) {
+            return config.set_oracle(Address::default(), 0, 0, 0, 0);
+        }
+
+        // 确认价格账户的格式是正确的 避免把池子配置成无法交易的状态
+        OraclePrice::load(self.accounts.oracle)?;
+
+        if self.accounts.mint_x.address().ne(config.mint_x())
+            || self.accounts.mint_y.address().ne(config.mint_y())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let decimals_x = Mint::from_account_view(self.accounts.mint_x)?.decimals();
+        let decimals_y = Mint::from_account_view(self.accounts.mint_y)?.decimals();
+
+        config.set_oracle(
+            self.accounts.oracle.address().clone(),
+            self.instruction_data.max_deviation,
+            self.instruction_data.max_age,
+            decimals_x,
+            decimals_y,
+        )
+    }
+}
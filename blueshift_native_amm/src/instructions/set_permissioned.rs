@@ -0,0 +1,68 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount,
+};
+use crate::Config;
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+pub struct SetPermissionedAccounts<'a> {
+    // AMM 的管理员 必须签名。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetPermissionedAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct SetPermissionedInstructionData {
+    // true 表示只有白名单中的钱包可以交易和提供流动性
+    pub permissioned: bool,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetPermissionedInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let [permissioned] = data else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+        Ok(Self {
+            permissioned: *permissioned != 0,
+        })
+    }
+}
+
+pub struct SetPermissioned<'a> {
+    pub accounts: SetPermissionedAccounts<'a>,
+    pub instruction_data: SetPermissionedInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetPermissioned<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetPermissionedAccounts::try_from(accounts)?;
+        let instruction_data = SetPermissionedInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetPermissioned<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+        config.set_permissioned(self.instruction_data.permissioned)
+    }
+}
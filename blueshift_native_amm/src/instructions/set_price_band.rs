@@ -0,0 +1,67 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount,
+};
+use crate::Config;
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+pub struct SetPriceBandAccounts<'a> {
+    // AMM 的管理员 必须签名。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetPriceBandAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct SetPriceBandInstructionData {
+    // 同一个 slot 内价格相对开盘价允许的最大偏离 以基点表示 0 表示关闭限制
+    pub band: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetPriceBandInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let band = data
+            .try_into()
+            .map(u16::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        Ok(Self { band })
+    }
+}
+
+pub struct SetPriceBand<'a> {
+    pub accounts: SetPriceBandAccounts<'a>,
+    pub instruction_data: SetPriceBandInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetPriceBand<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetPriceBandAccounts::try_from(accounts)?;
+        let instruction_data = SetPriceBandInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetPriceBand<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+        config.set_slot_price_band(self.instruction_data.band)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::instructions::helper::{AccountCheck, ConfigGuardian, ProgramAccount};
+use crate::twamm::settle;
+use crate::Config;
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+pub struct SetStateAccounts<'a> {
+    // AMM 的管理员或者守护者 必须签名。守护者只能暂停池子。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 可选账户。启用了 TWAMM 的池子需要传入 TWAMM 账户。
+    pub remaining: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetStateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            authority,
+            config,
+            remaining,
+        })
+    }
+}
+
+pub struct SetStateInstructionData {
+    // 池子的新状态 取值见 AmmState
+    pub state: u8,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetStateInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let [state] = data else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+        Ok(Self { state: *state })
+    }
+}
+
+pub struct SetState<'a> {
+    pub accounts: SetStateAccounts<'a>,
+    pub instruction_data: SetStateInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetState<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetStateAccounts::try_from(accounts)?;
+        let instruction_data = SetStateInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetState<'a> {
+    pub fn process(&self) -> ProgramResult {
+        // 按照旧的状态把 TWAMM 订单执行到现在 暂停和恢复都从这一刻开始生效
+        settle(self.accounts.config, &mut self.accounts.remaining.iter())?;
+
+        let mut config = Config::load_mut(self.accounts.config)?;
+        ConfigGuardian::check(self.accounts.authority, &config, self.instruction_data.state)?;
+        config.set_state(self.instruction_data.state)
+    }
+}
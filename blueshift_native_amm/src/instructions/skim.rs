@@ -0,0 +1,115 @@
+use crate::instructions::helper::{
+    AccountCheck, ConfigAuthority, ConfigAuthorityCheck, ProgramAccount,
+};
+use crate::Config;
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+use pinocchio_token::instructions::Transfer;
+use pinocchio_token::state::TokenAccount;
+
+pub struct SkimAccounts<'a> {
+    // AMM 的管理员 必须签名。
+    pub authority: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 存储所有存入池中的 X 代币的代币账户。
+    pub vault_x: &'a AccountView,
+    // 存储所有存入池中的 Y 代币的代币账户。
+    pub vault_y: &'a AccountView,
+    // 接收多余 X 代币的代币账户。
+    pub destination_x: &'a AccountView,
+    // 接收多余 Y 代币的代币账户。
+    pub destination_y: &'a AccountView,
+    // SPL 代币程序账户。
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SkimAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, vault_x, vault_y, destination_x, destination_y, token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            authority,
+            config,
+            vault_x,
+            vault_y,
+            destination_x,
+            destination_y,
+            token_program,
+        })
+    }
+}
+
+pub struct Skim<'a> {
+    pub accounts: SkimAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for Skim<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = SkimAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Skim<'a> {
+    // 管理员取回金库中超出储备的代币 储备本身不会变化
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+        ConfigAuthority::check(self.accounts.authority, &config)?;
+        // 金库地址在 Initialize 时已经记录在 config 中 直接比较即可
+        if self.accounts.vault_x.address().ne(config.vault_x())
+            || self.accounts.vault_y.address().ne(config.vault_y())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let excess_x = TokenAccount::from_account_view(self.accounts.vault_x)?
+            .amount()
+            .saturating_sub(config.reserve_x())
+            .saturating_sub(config.twamm_escrow_x());
+        let excess_y = TokenAccount::from_account_view(self.accounts.vault_y)?
+            .amount()
+            .saturating_sub(config.reserve_y())
+            .saturating_sub(config.twamm_escrow_y());
+
+        // 构建config签名
+        let seed_array = config.seed().to_le_bytes();
+        let bump = config.config_bump();
+        let config_seeds = [
+            Seed::from(b"config"),
+            Seed::from(&seed_array),
+            Seed::from(config.mint_x().as_ref()),
+            Seed::from(config.mint_y().as_ref()),
+            Seed::from(&bump),
+        ];
+        let config_signer = [Signer::from(&config_seeds)];
+
+        if excess_x > 0 {
+            Transfer {
+                from: self.accounts.vault_x,
+                to: self.accounts.destination_x,
+                authority: self.accounts.config,
+                amount: excess_x,
+            }
+            .invoke_signed(&config_signer)?;
+        }
+        if excess_y > 0 {
+            Transfer {
+                from: self.accounts.vault_y,
+                to: self.accounts.destination_y,
+                authority: self.accounts.config,
+                amount: excess_y,
+            }
+            .invoke_signed(&config_signer)?;
+        }
+        Ok(())
+    }
+}
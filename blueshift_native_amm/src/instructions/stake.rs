@@ -0,0 +1,158 @@
+use crate::instructions::helper::{
+    AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount,
+};
+use crate::{Farm, StakePosition};
+use pinocchio::cpi::Seed;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_token::instructions::Transfer;
+
+pub struct StakeAccounts<'a> {
+    // 质押 LP 的用户 必须签名 第一次质押时支付质押记录的租金。
+    pub user: &'a AccountView,
+    // 池子的农场账户。
+    pub farm: &'a AccountView,
+    // 用户的质押记录 PDA seeds = [b"stake", farm, user] 不存在时会被创建
+    pub position: &'a AccountView,
+    // 用户的 LP 代币账户 LP 从这里转出。
+    pub user_lp_ata: &'a AccountView,
+    // 农场持有的 LP 代币账户。
+    pub stake_vault: &'a AccountView,
+    // SPL 代币程序账户。
+    pub token_program: &'a AccountView,
+    // 系统程序 创建质押记录需要。
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for StakeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [user, farm, position, user_lp_ata, stake_vault, token_program, system_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(user)?;
+        Ok(Self {
+            user,
+            farm,
+            position,
+            user_lp_ata,
+            stake_vault,
+            token_program,
+            system_program,
+        })
+    }
+}
+
+pub struct StakeInstructionData {
+    // 质押的 LP 数量
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for StakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let amount = u64::from_le_bytes(data.try_into().unwrap());
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self { amount })
+    }
+}
+
+pub struct Stake<'a> {
+    pub accounts: StakeAccounts<'a>,
+    pub instruction_data: StakeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Stake<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = StakeAccounts::try_from(accounts)?;
+        let instruction_data = StakeInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Stake<'a> {
+    pub fn process(&self) -> ProgramResult {
+        // 第一次质押 创建质押记录
+        if self.accounts.position.is_data_empty() {
+            self.init_position()?;
+        }
+
+        let mut farm = Farm::load_mut(self.accounts.farm)?;
+        if farm.stake_vault().ne(self.accounts.stake_vault.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut position = StakePosition::load_mut(self.accounts.position)?;
+        position.check_owner(self.accounts.farm, self.accounts.user)?;
+
+        // 先把之前的奖励结算到 pending 再增加质押数量
+        farm.update(Clock::get()?.unix_timestamp)?;
+        position.settle(&farm)?;
+        let amount = position
+            .amount()
+            .checked_add(self.instruction_data.amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        position.set_amount(&farm, amount)?;
+        let total_staked = farm
+            .total_staked()
+            .checked_add(self.instruction_data.amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        farm.set_total_staked(total_staked);
+        drop(position);
+        drop(farm);
+
+        Transfer {
+            from: self.accounts.user_lp_ata,
+            to: self.accounts.stake_vault,
+            authority: self.accounts.user,
+            amount: self.instruction_data.amount,
+        }
+        .invoke()
+    }
+
+    fn init_position(&self) -> ProgramResult {
+        let (position_address, bump) = Address::find_program_address(
+            &[
+                b"stake",
+                self.accounts.farm.address().as_ref(),
+                self.accounts.user.address().as_ref(),
+            ],
+            &crate::ID,
+        );
+        if position_address.ne(self.accounts.position.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let bump_binding = [bump];
+        let position_seeds = [
+            Seed::from(b"stake"),
+            Seed::from(self.accounts.farm.address().as_ref()),
+            Seed::from(self.accounts.user.address().as_ref()),
+            Seed::from(&bump_binding),
+        ];
+        ProgramAccount::init::<StakePosition>(
+            self.accounts.user,
+            self.accounts.position,
+            &position_seeds,
+            StakePosition::LEN,
+        )?;
+        StakePosition::load_mut(self.accounts.position)?.set_inner(
+            self.accounts.farm.address().clone(),
+            self.accounts.user.address().clone(),
+            bump_binding,
+        );
+        Ok(())
+    }
+}
@@ -1,20 +1,28 @@
 use crate::instructions::helper::{
-    AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck, SignerAccount,
+    AccountCheck, Deadline, SignerAccount, TokenAccount, TokenAccountOwnerCheck,
+};
+use crate::math::curve_error;
+use crate::twamm::settle;
+use blueshift_amm_math::{ConstantProduct, Curve, LiquidityPair};
+use crate::{
+    check_lamport_source, check_temporary_wsol, invoke_swap_hook, swap_hook_data, unwrap, wrap, AllowlistEntry,
+    AmmState, Config, HookPhase, OraclePrice, NATIVE_MINT,
 };
-use crate::{AmmState, Config};
 use pinocchio::{
     error::ProgramError,
     sysvars::{clock::Clock, Sysvar},
-    AccountView, Address, ProgramResult,
+    AccountView, ProgramResult,
 };
 use pinocchio::cpi::{Seed, Signer};
-use pinocchio_token::{instructions::Transfer, state::TokenAccount};
+use pinocchio_token::instructions::Transfer;
 use solana_program_log::log;
 
 pub struct SwapAccounts<'a> {
     // 将代币交换到 AMM 流动性中的用户。
     pub user: &'a AccountView,
     // 用户的代币 X 关联账户。此账户将接收或发送代币 X 到池中。
+    // 使用原生 SOL 支付时 支付一侧传入系统程序
+    // 使用原生 SOL 接收时 接收一侧传入用户的空临时 wSOL 账户 交易结束后关闭
     pub user_x_ata: &'a AccountView,
     // 用户的代币 Y 关联账户。此账户将接收或发送代币 Y 到池中。
     pub user_y_ata: &'a AccountView,
@@ -26,13 +34,20 @@ pub struct SwapAccounts<'a> {
     pub config: &'a AccountView,
     // SPL 代币程序账户。执行代币操作（如转账和铸造）所需。
     pub token_program: &'a AccountView,
+    // 可选账户 按顺序排列：
+    // 1. 池子启用了 TWAMM 时池子的 TWAMM 账户
+    // 2. 池子启用了预言机校验时 config 中记录的价格账户
+    // 3. 许可池中用户的白名单记录
+    // 4. 池子注册了 hook 时 hook 程序 后面跟着 hook 需要的全部账户
+    pub remaining: &'a [AccountView],
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for SwapAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [user, user_x_ata, user_y_ata, vault_x, vault_y, config, token_program] = accounts
+        let [user, user_x_ata, user_y_ata, vault_x, vault_y, config, token_program, remaining @ ..] =
+            accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
@@ -45,6 +60,7 @@ impl<'a> TryFrom<&'a [AccountView]> for SwapAccounts<'a> {
             vault_y,
             config,
             token_program,
+            remaining,
         })
     }
 }
@@ -58,6 +74,10 @@ pub struct SwapInstructionData {
     pub min: u64,
     // 此订单的过期时间。确保交易必须在一定时间内完成非常重要。
     pub expiration: i64,
+    // 可选 为 1 时池子中 wSOL 一侧直接使用原生 SOL 支付或接收
+    pub native: bool,
+    // 可选 订单过期的 slot 和 expiration 同时生效 0 表示不限制
+    pub expiration_slot: u64,
 }
 
 impl TryFrom<&[u8]> for SwapInstructionData {
@@ -65,13 +85,23 @@ impl TryFrom<&[u8]> for SwapInstructionData {
 
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
         log!("初始化数据");
-        if data.len() != size_of::<u64>() * 3 + size_of::<bool>() {
+        const SWAP_DATA_LEN: usize = size_of::<u64>() * 3 + size_of::<bool>();
+        const SWAP_DATA_LEN_WITH_NATIVE: usize = SWAP_DATA_LEN + size_of::<bool>();
+        const SWAP_DATA_LEN_WITH_SLOT: usize = SWAP_DATA_LEN_WITH_NATIVE + size_of::<u64>();
+        if !matches!(
+            data.len(),
+            SWAP_DATA_LEN | SWAP_DATA_LEN_WITH_NATIVE | SWAP_DATA_LEN_WITH_SLOT
+        ) {
             return Err(ProgramError::InvalidArgument);
         }
         let is_x = data.get(0) != Some(&0u8);
         let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
         let min = u64::from_le_bytes(data[9..17].try_into().unwrap());
         let expiration = i64::from_le_bytes(data[17..25].try_into().unwrap());
+        let native = data.get(25).is_some_and(|flag| *flag != 0);
+        let expiration_slot = data
+            .get(26..34)
+            .map_or(0, |slot| u64::from_le_bytes(slot.try_into().unwrap()));
 
         // Instruction Checks
         if amount <= 0 {
@@ -80,16 +110,14 @@ impl TryFrom<&[u8]> for SwapInstructionData {
         if min <= 0 {
             return Err(ProgramError::InvalidInstructionData);
         }
-        if Clock::get()?.unix_timestamp > expiration {
-            // 超时
-            return Err(ProgramError::InvalidInstructionData);
-        }
 
         Ok(Self {
             is_x,
             amount,
             min,
             expiration,
+            native,
+            expiration_slot,
         })
     }
 }
@@ -114,77 +142,162 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Swap<'a> {
 
 impl<'a> Swap<'a> {
     pub fn process(&self) -> ProgramResult {
+        // 超时 放在 process 中检查 指令数据的解析不依赖 Clock
+        Deadline::check(
+            self.instruction_data.expiration,
+            self.instruction_data.expiration_slot,
+        )?;
         // 根据题目要求 所有的ata都已经在指令外初始化了
         // 否则的话 对于用户接收代币的ata需要使用init_if_needed的处理防止账户不存在
         // 校验用户的ata账户地址有没有问题
         log!("开始校验");
+        // 先把 TWAMM 订单执行到现在 再按照最新的储备成交
+        let mut remaining = self.accounts.remaining.iter();
+        settle(self.accounts.config, &mut remaining)?;
         let config = Config::load(self.accounts.config)?;
         if config.state() != (AmmState::Initialized as u8) {
             return Err(ProgramError::InvalidArgument);
         }
-        AssociatedTokenAccount::check(
-            self.accounts.user_x_ata,
-            self.accounts.user,
-            config.mint_x(),
-            self.accounts.token_program,
-        )?;
-        AssociatedTokenAccount::check(
-            self.accounts.user_y_ata,
-            self.accounts.user,
-            config.mint_y(),
-            self.accounts.token_program,
-        )?;
-
-        // 校验 金库的地址有没有为题
-        // 其实 vault并不需要被初始化 因为这个地址是被计算出来的 只要计算出来 就可以标记里面有多少钱
-        // Check if the vault_x is valid
-        let (vault_x, _) = Address::find_program_address(
-            &[
-                self.accounts.config.address().as_ref(),
-                self.accounts.token_program.address().as_ref(),
-                config.mint_x().as_ref(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-        if vault_x.ne(self.accounts.vault_x.address()) {
-            return Err(ProgramError::InvalidAccountData);
+        // 按交易方向整理出支付一侧和接收一侧的账户
+        let (user_in, user_out, vault_in, vault_out, mint_in, mint_out) =
+            match self.instruction_data.is_x {
+                true => (
+                    self.accounts.user_x_ata,
+                    self.accounts.user_y_ata,
+                    self.accounts.vault_x,
+                    self.accounts.vault_y,
+                    config.mint_x(),
+                    config.mint_y(),
+                ),
+                false => (
+                    self.accounts.user_y_ata,
+                    self.accounts.user_x_ata,
+                    self.accounts.vault_y,
+                    self.accounts.vault_x,
+                    config.mint_y(),
+                    config.mint_x(),
+                ),
+            };
+        let native_in = self.instruction_data.native && mint_in.eq(&NATIVE_MINT);
+        let native_out = self.instruction_data.native && mint_out.eq(&NATIVE_MINT);
+        if self.instruction_data.native && !native_in && !native_out {
+            return Err(ProgramError::InvalidArgument);
         }
-        let (vault_y, _) = Address::find_program_address(
-            &[
-                self.accounts.config.address().as_ref(),
-                self.accounts.token_program.address().as_ref(),
-                config.mint_y().as_ref(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-        if vault_y.ne(self.accounts.vault_y.address()) {
+        // 直接支付 lamports 时不需要用户的 wSOL 账户
+        match native_in {
+            true => check_lamport_source(user_in)?,
+            false => TokenAccount::check_owner(user_in, self.accounts.user, mint_in)?,
+        }
+        TokenAccount::check_owner(user_out, self.accounts.user, mint_out)?;
+        if native_out {
+            check_temporary_wsol(user_out)?;
+        }
+
+        // 金库地址在 Initialize 时已经记录在 config 中 直接比较即可
+        if self.accounts.vault_x.address().ne(config.vault_x())
+            || self.accounts.vault_y.address().ne(config.vault_y())
+        {
             return Err(ProgramError::InvalidAccountData);
         }
-        // Deserialize the token accounts
-        let vault_x = unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_y)? };
+        // 使用池子记录的储备计算价格 而不是金库的实际余额 防止直接转账操纵价格
+        let reserve_x = config.reserve_x();
+        let reserve_y = config.reserve_y();
         // Swap Calculations
-        let mut curve = ConstantProduct::init(
-            vault_x.amount(),
-            vault_y.amount(),
-            vault_x.amount(),
-            config.fee(),
-            None,
-        )
-        .map_err(|_| ProgramError::Custom(1))?;
-        let p = match self.instruction_data.is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
-        };
         // 0- deposit 1-fee 2-withdraw
-        let swap_result = curve
-            .swap(p, self.instruction_data.amount, self.instruction_data.min)
-            .map_err(|_| ProgramError::Custom(1))?;
+        let swap_result = Self::quote(
+            reserve_x,
+            reserve_y,
+            config.fee(),
+            self.instruction_data.is_x,
+            self.instruction_data.amount,
+            self.instruction_data.min,
+        )?;
         // Check for correct values
         // 不允许支付金额或者提现金额为0
         if swap_result.0 == 0 || swap_result.2 == 0 {
             return Err(ProgramError::InvalidArgument);
         }
+        // 如果池子配置了预言机 校验成交价格有没有偏离预言机价格
+        if config.has_oracle() {
+            let oracle = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if oracle.address().ne(config.oracle()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let price = OraclePrice::load(oracle)?;
+            price.check_staleness(Clock::get()?.unix_timestamp, config.oracle_max_age())?;
+            price.check_confidence(config.oracle_max_deviation())?;
+            let (amount_x, amount_y) = match self.instruction_data.is_x {
+                true => (swap_result.0, swap_result.2),
+                false => (swap_result.2, swap_result.0),
+            };
+            price.check_price_band(
+                amount_x,
+                amount_y,
+                config.decimals_x(),
+                config.decimals_y(),
+                config.oracle_max_deviation(),
+            )?;
+        }
+        // 许可池只允许白名单中的钱包交易
+        if config.is_permissioned() {
+            let entry = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            AllowlistEntry::check(entry, self.accounts.config, self.accounts.user)?;
+        }
+        // 池子注册了 hook 的话 剩下的账户就是 hook 程序和它需要的账户
+        let hook = match config.has_hook() {
+            true => {
+                let hook_program = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+                if hook_program.address().ne(config.hook_program()) {
+                    return Err(ProgramError::IncorrectProgramId);
+                }
+                Some((hook_program, remaining.as_slice()))
+            }
+            false => None,
+        };
+        // 用户支付的全部代币（包括手续费）进入储备 用户拿走的代币从储备中扣除
+        let (new_reserve_x, new_reserve_y) = match self.instruction_data.is_x {
+            true => (
+                reserve_x.checked_add(swap_result.0),
+                reserve_y.checked_sub(swap_result.2),
+            ),
+            false => (
+                reserve_x.checked_sub(swap_result.2),
+                reserve_y.checked_add(swap_result.0),
+            ),
+        };
+        let new_reserve_x = new_reserve_x.ok_or(ProgramError::ArithmeticOverflow)?;
+        let new_reserve_y = new_reserve_y.ok_or(ProgramError::ArithmeticOverflow)?;
+        drop(config);
+        // 交易前调用 hook hook 可以返回错误来否决这笔交易 这时 hook 看到的还是交易之前的储备
+        if let Some((hook_program, hook_accounts)) = hook {
+            invoke_swap_hook(
+                hook_program,
+                self.accounts.config,
+                self.accounts.user,
+                hook_accounts,
+                &swap_hook_data(
+                    HookPhase::PreSwap,
+                    self.instruction_data.is_x,
+                    swap_result.0,
+                    swap_result.2,
+                    swap_result.1,
+                ),
+            )?;
+        }
+        // 先更新储备再转账 转账的时候 config 不能处于可变借用状态
+        {
+            let mut config = Config::load_mut(self.accounts.config)?;
+            // 同一个 slot 内价格不能偏离开盘价太多 防止被三明治攻击
+            config.check_slot_price_band(
+                Clock::get()?.slot,
+                reserve_x,
+                reserve_y,
+                new_reserve_x,
+                new_reserve_y,
+            )?;
+            config.set_reserves(new_reserve_x, new_reserve_y)?;
+        }
+        let config = Config::load(self.accounts.config)?;
         // 进行交易
         // 构建config签名
         let seed_bytes = config.seed().to_le_bytes();
@@ -200,206 +313,62 @@ impl<'a> Swap<'a> {
         ];
 
         let config_signer = [Signer::from(&config_seeds)];
-        // 因为支付的是代币 所以需要使用 pinocchio_token 里面的 Transfer
-        if self.instruction_data.is_x {
-            // 如果是 支付x 获取 y
-            // 从用户的 x ata里面转移 amount 个代币到 金库
-            Transfer {
-                from: self.accounts.user_x_ata,
-                to: self.accounts.vault_x,
+        // 支付原生 SOL 时 lamports 直接转入 wSOL 金库 否则使用 pinocchio_token 里面的 Transfer
+        match native_in {
+            true => wrap(self.accounts.user, vault_in, self.instruction_data.amount)?,
+            false => Transfer {
+                from: user_in,
+                to: vault_in,
                 authority: self.accounts.user,
                 amount: self.instruction_data.amount,
             }
-            .invoke()?;
-            Transfer {
-                from: self.accounts.vault_y,
-                to: self.accounts.user_y_ata,
-                authority: self.accounts.config,
-                amount: swap_result.2,
-            }
-            .invoke_signed(&config_signer)?;
-        } else {
-            // 如果是 支付y 获取 x
-            // 从用户的 y ata里面转移 amount 个代币到 金库
-            Transfer {
-                from: self.accounts.user_y_ata,
-                to: self.accounts.vault_y,
-                authority: self.accounts.user,
-                amount: self.instruction_data.amount,
-            }
-            .invoke()?;
-            Transfer {
-                from: self.accounts.vault_x,
-                to: self.accounts.user_x_ata,
-                authority: self.accounts.config,
-                amount: swap_result.2,
-            }
-            .invoke_signed(&config_signer)?;
+            .invoke()?,
         }
-        Ok(())
-    }
-}
-
-/// 下面的代码都是从根据task的要求引入的git上复制过来的
-/// 根据自己的理解写了一些注释
-// 这里应该是校验数组中的全部数据是否包含 0
-macro_rules! assert_non_zero {
-    ($array:expr) => {
-        if $array.contains(&0u64) {
-            return Err(ProgramError::InvalidArgument);
+        Transfer {
+            from: vault_out,
+            to: user_out,
+            authority: self.accounts.config,
+            amount: swap_result.2,
         }
-    };
-}
-
-// 这个宏的意思是比较两个2个数的大小 如果 第一个数比第二个数的就报错
-macro_rules! swap_slippage {
-    ($x:expr, $x_min:expr) => {
-        if $x < $x_min {
-            return Err(ProgramError::InvalidArgument);
+        .invoke_signed(&config_signer)?;
+        // 接收原生 SOL 时关闭用户临时的 wSOL 账户 把 lamports 还给用户
+        if native_out {
+            unwrap(user_out, self.accounts.user)?;
         }
-    };
-}
-
-#[derive(Debug)]
-pub enum LiquidityPair {
-    X,
-    Y,
-}
-
-// x-代币x的金额 y-代币y的金额 l-代币x的金额 fee-费率 precision-精度
-// 这里 l 为代币x的金额其实是因为在初始化里面会有个逻辑 当代币x的数量为0的时候会取x和y的最大值
-#[derive(Debug)]
-pub struct ConstantProduct {
-    x: u64,   // Balance of Token X
-    y: u64,   // Balance of Token Y
-    l: u64,   // LP Token Balance
-    fee: u16, // Fee in basis points, ie: 100 = 1%
-    precision: u32,
-}
-impl ConstantProduct {
-    // Create a new Constant Product Curve
-    pub fn init(
-        x: u64,
-        y: u64,
-        l: u64,
-        fee: u16,
-        precision: Option<u8>,
-    ) -> Result<ConstantProduct, ProgramError> {
-        // Assert non-zero values of X and Y
-        assert_non_zero!([x, y]);
-        // 给一个默认的精度
-        let precision = match precision {
-            Some(p) => 10u32
-                .checked_pow(p as u32)
-                .ok_or(ProgramError::InvalidArgument)?,
-            None => 1_000_000,
-        };
-
-        // If L is zero, make it the higher value of either X or Y, as this will have less rounding errors
-        let l = match l > 0 {
-            true => l,
-            false => x.max(y),
-        };
-
-        Ok(ConstantProduct {
-            x,
-            y,
-            l,
-            fee,
-            precision,
-        })
+        // 交易完成后再调用一次 hook 比如记录积分或者检查交易后的状态
+        if let Some((hook_program, hook_accounts)) = hook {
+            invoke_swap_hook(
+                hook_program,
+                self.accounts.config,
+                self.accounts.user,
+                hook_accounts,
+                &swap_hook_data(
+                    HookPhase::PostSwap,
+                    self.instruction_data.is_x,
+                    swap_result.0,
+                    swap_result.2,
+                    swap_result.1,
+                ),
+            )?;
+        }
+        Ok(())
     }
 
-    // p: x代表 支付x 获取y；y代表 支付y 获取x
-    // a: 愿意支付的代币数量
-    // min: 愿意接受的最小代币数量
-    // Swap asset X for asset Y or vice versa with slippage protection
-    pub fn swap(
-        &mut self,
-        p: LiquidityPair,
-        a: u64,
+    // 根据储备计算交换结果 Swap 和 QuoteSwap 共用这一份计算 保证报价和实际成交一致
+    // 返回 (支付的代币数量, 手续费, 用户拿到的代币数量)
+    pub fn quote(
+        reserve_x: u64,
+        reserve_y: u64,
+        fee: u16,
+        is_x: bool,
+        amount: u64,
         min: u64,
     ) -> Result<(u64, u64, u64), ProgramError> {
-        // 支付金额 * (10_000 - 费率) / 10_000
-        // 这里是计算出扣掉费率之后 实际能用来买新代币的代币数量
-        // 这里的 10_000 就是 x * y 的值 也就是 k
-        let a2 = (a as u128)
-            .checked_mul((10_000 - self.fee) as u128)
-            .ok_or(ProgramError::InvalidArgument)?
-            .checked_div(10_000)
-            .ok_or(ProgramError::InvalidArgument)? as u64;
-
-        // 返回的是新的x值 新的y值和 支付给用户的代币数量
-        let (new_x, new_y, withdraw) = match p {
-            // 支付x 获取y
-            LiquidityPair::X => {
-                // 金库的代币x数量 + 买代币的数量 = 如果交易成功最新的x代币数量
-                let x2 = self
-                    .x
-                    .checked_add(a2)
-                    .ok_or(ProgramError::InvalidArgument)?;
-                // 获取y2的数量
-                let y2 = Self::y2_from_x_swap_amount(self.x, self.y, a2)?;
-                let delta_y = Self::delta_y_from_x_swap_amount(self.x, self.y, a2)?;
-                (x2, y2, delta_y)
-            }
-            // 支付y 获取x
-            LiquidityPair::Y => {
-                // 计算出新的x代币的数量
-                let x2 = Self::x2_from_y_swap_amount(self.x, self.y, a)?;
-                // 金库的代币x数量 + 买代币的数量 = 如果交易成功最新的x代币数量
-                let y2 = self.y.checked_add(a).ok_or(ProgramError::InvalidArgument)?;
-                let delta_x = Self::delta_x_from_y_swap_amount(self.x, self.y, a)?;
-                (x2, y2, delta_x)
-            }
+        let curve = ConstantProduct::init(reserve_x, reserve_y, fee).map_err(curve_error)?;
+        let p = match is_x {
+            true => LiquidityPair::X,
+            false => LiquidityPair::Y,
         };
-        // 如果提现的数据小于用户愿意获取的最小值 报错返回
-        swap_slippage!(withdraw, min);
-        // 费率也就是支付给AMM的钱 就是 用户支付的代币金额 - 用户实际用于支付的代币金额
-        let fee = a.checked_sub(a2).ok_or(ProgramError::InvalidArgument)?;
-        self.x = new_x;
-        self.y = new_y;
-
-        Ok((a, fee, withdraw))
-    }
-
-    // x-之前的代币x的数量 y-之前的代币y的数量 a-新代币x的数量
-    fn y2_from_x_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, ProgramError> {
-        Self::x2_from_y_swap_amount(y, x, a)
-    }
-
-    // 这里是一个公用方法 x转y 和 y转x 都走这个方法 但是这里的x和y传的值不一样
-    // 这里等于是计算出原有的k值 然后把新的 y 值计算出来 然后再用 k 除以最新的y值 得到新的 x值
-    // Calculate new value of X after depositing Y
-    // When we swap amount A of Y for X, we must calculate the new balance of X from invariant K
-    // Y₂ = Y₁ + Amount
-    // X₂ = K / Y₂
-    pub fn x2_from_y_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, ProgramError> {
-        let k = Self::k_from_xy(x, y)?;
-        let x_new = (y as u128)
-            .checked_add(a as u128)
-            .ok_or(ProgramError::InvalidArgument)?;
-        Ok(k.checked_div(x_new).ok_or(ProgramError::InvalidArgument)? as u64)
-    }
-
-    // 就是旧的代币数量减去新的代币数量 也就是用户买到的代币数量
-    // Calculate the withdraw amount of X from swapping in Y
-    // ΔX = X₁ - X₂
-    pub fn delta_x_from_y_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, ProgramError> {
-        Ok(x.checked_sub(Self::x2_from_y_swap_amount(x, y, a)?)
-            .ok_or(ProgramError::InvalidArgument)?)
-    }
-
-    // Calculate difference in Y from swapping in X
-    // ΔY = Y₁ - Y₂
-    pub fn delta_y_from_x_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, ProgramError> {
-        Self::delta_x_from_y_swap_amount(y, x, a)
-    }
-
-    // 这里就是校验 x 和 y都 不为0 之后 获取 x*y 也就是k的值 然后返回
-    // Static Invariant calculation
-    pub fn k_from_xy(x: u64, y: u64) -> Result<u128, ProgramError> {
-        assert_non_zero!([x, y]);
-        Ok((x as u128).checked_mul(y as u128).unwrap())
+        curve.quote_swap(p, amount, min).map_err(curve_error)
     }
 }
@@ -0,0 +1,81 @@
+use crate::instructions::helper::{AccountCheck, MintInterface, ProgramAccount};
+use crate::Config;
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+use pinocchio_token::state::{Mint, TokenAccount};
+
+pub struct SyncAccounts<'a> {
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 代表池流动性的铸币账户。
+    pub mint_lp: &'a AccountView,
+    // 存储所有存入池中的 X 代币的代币账户。
+    pub vault_x: &'a AccountView,
+    // 存储所有存入池中的 Y 代币的代币账户。
+    pub vault_y: &'a AccountView,
+    // SPL 代币程序账户。
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SyncAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config, mint_lp, vault_x, vault_y, token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        ProgramAccount::check(config)?;
+        MintInterface::check(mint_lp)?;
+        Ok(Self {
+            config,
+            mint_lp,
+            vault_x,
+            vault_y,
+            token_program,
+        })
+    }
+}
+
+pub struct Sync<'a> {
+    pub accounts: SyncAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for Sync<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = SyncAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Sync<'a> {
+    // 任何人都可以调用 把直接转入金库的代币计入储备 这部分代币归 LP 所有
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+        // 金库地址在 Initialize 时已经记录在 config 中 直接比较即可
+        if self.accounts.vault_x.address().ne(config.vault_x())
+            || self.accounts.vault_y.address().ne(config.vault_y())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 没有 LP 的时候同步储备会让后续的首次注入流动性无法进行 这种情况只能用 Skim 取回
+        let mint_lp = Mint::from_account_view(self.accounts.mint_lp)?;
+        if self.accounts.mint_lp.address().ne(config.mint_lp()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if mint_lp.supply() == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        // 属于 TWAMM 订单的代币不计入储备
+        let amount_x = TokenAccount::from_account_view(self.accounts.vault_x)?
+            .amount()
+            .saturating_sub(config.twamm_escrow_x());
+        let amount_y = TokenAccount::from_account_view(self.accounts.vault_y)?
+            .amount()
+            .saturating_sub(config.twamm_escrow_y());
+
+        drop(config);
+        Config::update_reserves(self.accounts.config, amount_x, amount_y)
+    }
+}
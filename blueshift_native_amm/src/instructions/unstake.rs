@@ -0,0 +1,117 @@
+use crate::instructions::helper::{AccountCheck, SignerAccount};
+use crate::{Farm, StakePosition};
+use pinocchio::cpi::Signer;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+use pinocchio_token::instructions::Transfer;
+
+pub struct UnstakeAccounts<'a> {
+    // 取回 LP 的用户 必须签名。
+    pub user: &'a AccountView,
+    // 池子的农场账户。
+    pub farm: &'a AccountView,
+    // 用户的质押记录。
+    pub position: &'a AccountView,
+    // 用户的 LP 代币账户 LP 转回到这里。
+    pub user_lp_ata: &'a AccountView,
+    // 农场持有的 LP 代币账户。
+    pub stake_vault: &'a AccountView,
+    // SPL 代币程序账户。
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for UnstakeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [user, farm, position, user_lp_ata, stake_vault, token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(user)?;
+        Ok(Self {
+            user,
+            farm,
+            position,
+            user_lp_ata,
+            stake_vault,
+            token_program,
+        })
+    }
+}
+
+pub struct UnstakeInstructionData {
+    // 取回的 LP 数量
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UnstakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let amount = u64::from_le_bytes(data.try_into().unwrap());
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self { amount })
+    }
+}
+
+pub struct Unstake<'a> {
+    pub accounts: UnstakeAccounts<'a>,
+    pub instruction_data: UnstakeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Unstake<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = UnstakeAccounts::try_from(accounts)?;
+        let instruction_data = UnstakeInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Unstake<'a> {
+    pub fn process(&self) -> ProgramResult {
+        let mut farm = Farm::load_mut(self.accounts.farm)?;
+        if farm.stake_vault().ne(self.accounts.stake_vault.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut position = StakePosition::load_mut(self.accounts.position)?;
+        position.check_owner(self.accounts.farm, self.accounts.user)?;
+
+        // 奖励结算到 pending 之后通过 Claim 领取
+        farm.update(Clock::get()?.unix_timestamp)?;
+        position.settle(&farm)?;
+        let amount = position
+            .amount()
+            .checked_sub(self.instruction_data.amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        position.set_amount(&farm, amount)?;
+        let total_staked = farm
+            .total_staked()
+            .checked_sub(self.instruction_data.amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        farm.set_total_staked(total_staked);
+        let config = farm.config().clone();
+        let bump = farm.bump();
+        drop(position);
+        drop(farm);
+
+        // 农场是 LP 金库的所有者 需要用农场的 seeds 签名
+        let farm_seeds = Farm::signer_seeds(&config, &bump);
+        Transfer {
+            from: self.accounts.stake_vault,
+            to: self.accounts.user_lp_ata,
+            authority: self.accounts.farm,
+            amount: self.instruction_data.amount,
+        }
+        .invoke_signed(&[Signer::from(&farm_seeds)])
+    }
+}
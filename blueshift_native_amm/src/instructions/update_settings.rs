@@ -0,0 +1,53 @@
+use crate::{Settings, SettingsInstructionData};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+pub struct UpdateSettingsAccounts<'a> {
+    // 协议管理员 必须签名。
+    pub admin: &'a AccountView,
+    // 全局设置账户。
+    pub settings: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for UpdateSettingsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [admin, settings] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        Ok(Self { admin, settings })
+    }
+}
+
+pub struct UpdateSettings<'a> {
+    pub accounts: UpdateSettingsAccounts<'a>,
+    pub instruction_data: SettingsInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for UpdateSettings<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = UpdateSettingsAccounts::try_from(accounts)?;
+        let instruction_data = SettingsInstructionData::try_from(data)?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UpdateSettings<'a> {
+    // 整体替换管理员、创建费和费率档位 已经创建的池子不受影响
+    pub fn process(&self) -> ProgramResult {
+        // 和 Initialize / CollectCreationFees 一样先确认是 [b"settings"] 推导出来的账户
+        Settings::check(self.accounts.settings)?.check_admin(self.accounts.admin)?;
+        let mut settings = Settings::load_mut(self.accounts.settings)?;
+        settings.set_params(
+            self.instruction_data.admin.clone(),
+            self.instruction_data.creation_fee,
+            self.instruction_data.fee_tiers(),
+        );
+        Ok(())
+    }
+}
@@ -1,15 +1,16 @@
 use crate::instructions::helper::{
-    AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck, MintInterface,
-    ProgramAccount, SignerAccount,
+    AccountCheck, Deadline, MintInterface, ProgramAccount, SignerAccount, TokenAccount,
+    TokenAccountOwnerCheck,
 };
-use crate::{AmmState, Config};
+use crate::math::curve_error;
+use crate::twamm::settle;
+use blueshift_amm_math::{ConstantProduct, Curve};
+use crate::{AllowlistEntry, AmmState, Config, LpPosition};
 use pinocchio::cpi::{Seed, Signer};
-use pinocchio::sysvars::clock::Clock;
-use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
 use pinocchio::{error::ProgramError, AccountView, ProgramResult};
 use pinocchio_token::instructions::{Burn, MintTo, Transfer};
-use pinocchio_token::state::{Mint, TokenAccount};
-use solana_address::Address;
+use pinocchio_token::state::Mint;
 
 pub struct WithdrawAccounts<'a> {
     // 将代币提取到 AMM 流动性中的用户。
@@ -30,13 +31,19 @@ pub struct WithdrawAccounts<'a> {
     pub config: &'a AccountView,
     // SPL 代币程序账户。这是执行代币操作（如转账和铸造）所需的。
     pub token_program: &'a AccountView,
+    // 可选账户 按顺序排列：
+    // 1. 池子启用了 TWAMM 时池子的 TWAMM 账户
+    // 2. 许可池中用户的白名单记录
+    // 3. 用户的 LP 仓位记录 传入时记录这次操作的成本 见 OpenPosition
+    //    WithdrawOnly 状态下不需要白名单记录 仓位记录直接跟在 TWAMM 账户后面
+    pub remaining: &'a [AccountView],
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for WithdrawAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program] =
+        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program, remaining @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
@@ -55,6 +62,7 @@ impl<'a> TryFrom<&'a [AccountView]> for WithdrawAccounts<'a> {
             user_lp_ata,
             config,
             token_program,
+            remaining,
         })
     }
 }
@@ -68,19 +76,25 @@ pub struct WithdrawInstructionData {
     pub min_y: u64,
     // 此订单的过期时间。确保交易必须在一定时间内完成非常重要。
     pub expiration: i64,
+    // 可选 订单过期的 slot 和 expiration 同时生效 0 表示不限制
+    pub expiration_slot: u64,
 }
 
 impl TryFrom<&[u8]> for WithdrawInstructionData {
     type Error = ProgramError;
 
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        if data.len() != size_of::<u64>() * 4 {
+        const WITHDRAW_DATA_LEN: usize = size_of::<u64>() * 4;
+        if data.len() != WITHDRAW_DATA_LEN && data.len() != WITHDRAW_DATA_LEN + size_of::<u64>() {
             return Err(ProgramError::InvalidArgument);
         };
         let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
         let min_x = u64::from_le_bytes(data[8..16].try_into().unwrap());
         let min_y = u64::from_le_bytes(data[16..24].try_into().unwrap());
         let expiration = i64::from_le_bytes(data[24..32].try_into().unwrap());
+        let expiration_slot = data
+            .get(32..40)
+            .map_or(0, |slot| u64::from_le_bytes(slot.try_into().unwrap()));
 
         // Instruction Checks
         if amount <= 0 {
@@ -92,15 +106,12 @@ impl TryFrom<&[u8]> for WithdrawInstructionData {
         if min_y <= 0 {
             return Err(ProgramError::InvalidInstructionData);
         }
-        if Clock::get()?.unix_timestamp > expiration {
-            // 超时
-            return Err(ProgramError::InvalidInstructionData);
-        }
         Ok(Self {
             amount,
             min_x,
             min_y,
             expiration,
+            expiration_slot,
         })
     }
 }
@@ -125,80 +136,72 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Withdraw<'a> {
 
 impl<'a> Withdraw<'a> {
     pub fn process(&self) -> ProgramResult {
+        // 超时 放在 process 中检查 指令数据的解析不依赖 Clock
+        Deadline::check(
+            self.instruction_data.expiration,
+            self.instruction_data.expiration_slot,
+        )?;
+        // 先把 TWAMM 订单执行到现在 再按照最新的储备计算
+        let mut remaining = self.accounts.remaining.iter();
+        settle(self.accounts.config, &mut remaining)?;
         // 校验用户的ata账户地址有没有问题
         let config = Config::load(self.accounts.config)?;
-        if config.state() != (AmmState::Initialized as u8) {
+        // WithdrawOnly 状态下 任何人都可以取回流动性 被移出白名单的用户也可以退出
+        let withdraw_only = config.state() == (AmmState::WithdrawOnly as u8);
+        if config.state() != (AmmState::Initialized as u8) && !withdraw_only {
             return Err(ProgramError::InvalidArgument);
         }
-        AssociatedTokenAccount::check(
-            self.accounts.user_x_ata,
-            self.accounts.user,
-            config.mint_x(),
-            self.accounts.token_program,
-        )?;
-        AssociatedTokenAccount::check(
-            self.accounts.user_y_ata,
-            self.accounts.user,
-            config.mint_y(),
-            self.accounts.token_program,
-        )?;
-        AssociatedTokenAccount::check(
+        // 正常状态下 许可池只允许白名单中的钱包提取流动性
+        if config.is_permissioned() && !withdraw_only {
+            let entry = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            AllowlistEntry::check(entry, self.accounts.config, self.accounts.user)?;
+        }
+        // LP 铸币必须是这个池子的
+        if self.accounts.mint_lp.address().ne(config.mint_lp()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        TokenAccount::check_owner(self.accounts.user_x_ata, self.accounts.user, config.mint_x())?;
+        TokenAccount::check_owner(self.accounts.user_y_ata, self.accounts.user, config.mint_y())?;
+        TokenAccount::check_owner(
             self.accounts.user_lp_ata,
             self.accounts.user,
             self.accounts.mint_lp.address(),
-            self.accounts.token_program,
         )?;
 
-        // 校验 金库的地址有没有为题
-        // 其实 vault并不需要被初始化 因为这个地址是被计算出来的 只要计算出来 就可以标记里面有多少钱
-        // Check if the vault_x is valid
-        let (vault_x, _) = Address::find_program_address(
-            &[
-                self.accounts.config.address().as_ref(),
-                self.accounts.token_program.address().as_ref(),
-                config.mint_x().as_ref(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-        if vault_x.ne(self.accounts.vault_x.address()) {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        let (vault_y, _) = Address::find_program_address(
-            &[
-                self.accounts.config.address().as_ref(),
-                self.accounts.token_program.address().as_ref(),
-                config.mint_y().as_ref(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-        if vault_y.ne(self.accounts.vault_y.address()) {
+        // 金库地址在 Initialize 时已经记录在 config 中 直接比较即可
+        if self.accounts.vault_x.address().ne(config.vault_x())
+            || self.accounts.vault_y.address().ne(config.vault_y())
+        {
             return Err(ProgramError::InvalidAccountData);
         }
 
         // 把账户从 AccountView 转化成功能账户
         let mint_lp = unsafe { Mint::from_account_view_unchecked(self.accounts.mint_lp)? };
-        let vault_x = unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_y)? };
+        // 使用池子记录的储备 而不是金库的实际余额
+        let reserve_x = config.reserve_x();
+        let reserve_y = config.reserve_y();
         // 计算需要从vault转给用户的代币数量
-        let (x, y) = match mint_lp.supply() == self.instruction_data.amount {
-            true => (vault_x.amount(), vault_y.amount()),
-            false => {
-                let amounts = Self::xy_withdraw_amounts_from_l(
-                    vault_x.amount(),
-                    vault_y.amount(),
-                    mint_lp.supply(),
-                    self.instruction_data.amount,
-                    6,
-                )
-                .map_err(|_| ProgramError::InvalidArgument)?;
-                (amounts.0, amounts.1)
-            }
-        };
+        let (x, y) = Self::quote(
+            reserve_x,
+            reserve_y,
+            mint_lp.supply(),
+            self.instruction_data.amount,
+        )?;
 
         // Check for slippage
         if !(x >= self.instruction_data.min_x && y >= self.instruction_data.min_y) {
             return Err(ProgramError::InvalidArgument);
         }
+        // 先更新储备再转账 转账的时候 config 不能处于可变借用状态
+        let new_reserve_x = reserve_x
+            .checked_sub(x)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let new_reserve_y = reserve_y
+            .checked_sub(y)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        drop(config);
+        Config::update_reserves(self.accounts.config, new_reserve_x, new_reserve_y)?;
+        let config = Config::load(self.accounts.config)?;
         // 1.把对应x,y代币转移到用户的ata账户
         // 构建config签名
         let seed_bytes = config.seed().to_le_bytes();
@@ -239,52 +242,31 @@ impl<'a> Withdraw<'a> {
             authority: self.accounts.user,
         }
         .invoke()?;
+
+        // 传入了仓位记录时 按比例扣除销毁的 LP 对应的成本
+        if let Some(position) = remaining.next() {
+            let mut position = LpPosition::load_mut(position)?;
+            position.check_owner(self.accounts.config, self.accounts.user)?;
+            position.record_withdraw(
+                x,
+                y,
+                self.instruction_data.amount,
+                Clock::get()?.unix_timestamp,
+            )?;
+        }
         Ok(())
     }
 
-    // Get amount of X and Y to withdraw from liquidity token amount
-    // 这个是根据git上直接搬过来的 错误类型改了一下
-    // x: 库存的x代币数量
-    // y: 库存的y代币数量
-    // l: 流动性代币lp的流通量
-    // a: 用户希望销毁的 LP 代币数量
-    // precision: 精度
-    fn xy_withdraw_amounts_from_l(
-        x: u64,
-        y: u64,
-        l: u64,
-        a: u64,
-        precision: u32,
+    // 根据储备计算销毁 amount 个 LP 可以取回的 x y 数量 Withdraw 和 QuoteWithdraw 共用
+    pub fn quote(
+        reserve_x: u64,
+        reserve_y: u64,
+        supply: u64,
+        amount: u64,
     ) -> Result<(u64, u64), ProgramError> {
-        // ((lp代币总量 - 用户希望销毁的lp代币数量) * 精度) / 流通代币总量
-        // 上面的约等于是 用户希望销毁的代币数量后 总的流通代币占流通总量的占比 * 精度
-        // 简单来说就是总流动性的剩余比例
-        let ratio = ((l - a) as u128)
-            .checked_mul(precision as u128)
-            .ok_or(ProgramError::InvalidArgument)?
-            .checked_div(l as u128)
-            .ok_or(ProgramError::InvalidArgument)?;
-        // 库存代币x的总量 - (库存x代币总量 * 总流动性的剩余比例 / 精度)
-        let withdraw_x = (x as u128)
-            .checked_sub(
-                (x as u128)
-                    .checked_mul(ratio)
-                    .ok_or(ProgramError::InvalidArgument)?
-                    .checked_div(precision as u128)
-                    .ok_or(ProgramError::InvalidArgument)?,
-            )
-            .ok_or(ProgramError::InvalidArgument)? as u64;
-        // 库存代币y的总量 - (库存y代币总量 * 总流动性的剩余比例 / 精度)
-        let withdraw_y = (y as u128)
-            .checked_sub(
-                (y as u128)
-                    .checked_mul(ratio)
-                    .ok_or(ProgramError::InvalidArgument)?
-                    .checked_div(precision as u128)
-                    .ok_or(ProgramError::InvalidArgument)?,
-            )
-            .ok_or(ProgramError::InvalidArgument)? as u64;
-        // 总结下来就是根据用户希望销毁的代币占总流通量的比例 直接从库存的x,y代币中直接按比例提取。
-        Ok((withdraw_x, withdraw_y))
+        // 取款报价和费率无关 销毁超过流通量的 LP 会报错
+        ConstantProduct::new(reserve_x, reserve_y, 0)
+            .and_then(|curve| curve.quote_withdraw(supply, amount))
+            .map_err(curve_error)
     }
 }
@@ -0,0 +1,162 @@
+use crate::instructions::helper::{AccountCheck, AccountClose, ProgramAccount, SignerAccount};
+use crate::twamm::settle;
+use crate::{Config, Twamm, TwammOrder};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+use pinocchio_token::instructions::Transfer;
+
+pub struct WithdrawProceedsAccounts<'a> {
+    // 订单的所有者 必须签名 订单结束后接收返还的租金。
+    pub user: &'a AccountView,
+    // AMM 池的配置账户。
+    pub config: &'a AccountView,
+    // 池子的 TWAMM 账户。
+    pub twamm: &'a AccountView,
+    // 用户的订单账户。
+    pub order: &'a AccountView,
+    // 用户接收收益的代币账户 卖 X 的订单收到 Y 卖 Y 的订单收到 X。
+    pub user_ata: &'a AccountView,
+    // 收益代币对应的池子金库。
+    pub vault: &'a AccountView,
+    // 用户卖出代币的代币账户 订单结束时接收暂停期间没有卖出的部分。
+    pub user_sell_ata: &'a AccountView,
+    // 卖出代币对应的池子金库。
+    pub sell_vault: &'a AccountView,
+    // SPL 代币程序账户。
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for WithdrawProceedsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [user, config, twamm, order, user_ata, vault, user_sell_ata, sell_vault, token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(user)?;
+        ProgramAccount::check(config)?;
+        Ok(Self {
+            user,
+            config,
+            twamm,
+            order,
+            user_ata,
+            vault,
+            user_sell_ata,
+            sell_vault,
+            token_program,
+        })
+    }
+}
+
+pub struct WithdrawProceeds<'a> {
+    pub accounts: WithdrawProceedsAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for WithdrawProceeds<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = WithdrawProceedsAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> WithdrawProceeds<'a> {
+    // 订单执行期间可以随时领取已经成交的部分 订单结束之后领取剩余的收益并关闭订单
+    // 池子暂停期间订单没有卖出的部分在订单结束时一起退还
+    pub fn process(&self) -> ProgramResult {
+        let twamm = core::slice::from_ref(self.accounts.twamm);
+        settle(self.accounts.config, &mut twamm.iter())?;
+
+        let mut order = TwammOrder::load_mut(self.accounts.order)?;
+        if order.config().ne(self.accounts.config.address())
+            || order.owner().ne(self.accounts.user.address())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let is_x = order.is_x();
+        let end_time = order.end_time();
+        let snapshot = order.snapshot();
+
+        let mut twamm = Twamm::load_mut(self.accounts.twamm)?;
+        twamm.check_config(self.accounts.config)?;
+        let proceeds = order.settle(twamm.order_earnings(is_x, end_time, snapshot)?)?;
+        // 订单已经执行完的话 计算需要退还的部分 并释放到期记录中的位置
+        let refund = match twamm.order_live_time(end_time, snapshot)? {
+            Some(live_end) => Some(order.refund(live_end)?),
+            None => None,
+        };
+        if refund.is_some() {
+            twamm.close_order(end_time, snapshot)?;
+        }
+        drop(twamm);
+        drop(order);
+
+        let mut config = Config::load_mut(self.accounts.config)?;
+        let (vault, sell_vault) = match is_x {
+            true => (config.vault_y(), config.vault_x()),
+            false => (config.vault_x(), config.vault_y()),
+        };
+        if self.accounts.vault.address().ne(vault)
+            || self.accounts.sell_vault.address().ne(sell_vault)
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // 收益和退还的部分离开托管
+        let refunded = refund.unwrap_or(0);
+        let (escrow_x, escrow_y) = match is_x {
+            true => (
+                config.twamm_escrow_x().checked_sub(refunded),
+                config.twamm_escrow_y().checked_sub(proceeds),
+            ),
+            false => (
+                config.twamm_escrow_x().checked_sub(proceeds),
+                config.twamm_escrow_y().checked_sub(refunded),
+            ),
+        };
+        config.set_twamm_escrow(
+            escrow_x.ok_or(ProgramError::ArithmeticOverflow)?,
+            escrow_y.ok_or(ProgramError::ArithmeticOverflow)?,
+        )?;
+        drop(config);
+
+        if proceeds > 0 || refunded > 0 {
+            let config = Config::load(self.accounts.config)?;
+            let seed_array = config.seed().to_le_bytes();
+            let bump = config.config_bump();
+            let config_seeds = [
+                Seed::from(b"config"),
+                Seed::from(&seed_array),
+                Seed::from(config.mint_x().as_ref()),
+                Seed::from(config.mint_y().as_ref()),
+                Seed::from(&bump),
+            ];
+            if proceeds > 0 {
+                Transfer {
+                    from: self.accounts.vault,
+                    to: self.accounts.user_ata,
+                    authority: self.accounts.config,
+                    amount: proceeds,
+                }
+                .invoke_signed(&[Signer::from(&config_seeds)])?;
+            }
+            if refunded > 0 {
+                Transfer {
+                    from: self.accounts.sell_vault,
+                    to: self.accounts.user_sell_ata,
+                    authority: self.accounts.config,
+                    amount: refunded,
+                }
+                .invoke_signed(&[Signer::from(&config_seeds)])?;
+            }
+        }
+
+        if refund.is_some() {
+            ProgramAccount::close(self.accounts.order, self.accounts.user)?;
+        }
+        Ok(())
+    }
+}
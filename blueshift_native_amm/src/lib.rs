@@ -1,7 +1,9 @@
-use pinocchio::{AccountView, Address, ProgramResult, entrypoint, error::ProgramError};
+use pinocchio::{AccountView, Address, ProgramResult, error::ProgramError};
 use solana_address::declare_id;
 
-entrypoint!(process_instruction);
+// 其他程序依赖这个 crate 时启用 no-entrypoint 避免入口符号冲突
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
 
 pub mod instructions;
 pub use instructions::*;
@@ -9,9 +11,32 @@ pub use instructions::*;
 pub mod state;
 pub use state::*;
 
+pub mod oracle;
+pub use oracle::*;
+
+pub mod hook;
+pub use hook::*;
+
+pub mod math;
+
+pub mod twamm;
+pub use twamm::*;
+
+pub mod native;
+pub use native::*;
+
+pub mod multisig;
+pub use multisig::*;
+
+pub mod settings;
+pub use settings::*;
+
+#[cfg(feature = "cpi")]
+pub mod cpi;
+
 declare_id!("22222222222222222222222222222222222222222222");
 
-fn process_instruction(
+pub fn process_instruction(
     _program_id: &Address,
     accounts: &[AccountView],
     instruction_data: &[u8],
@@ -22,6 +47,40 @@ fn process_instruction(
         1 => Deposit::try_from((data, accounts))?.process(),
         2 => Withdraw::try_from((data, accounts))?.process(),
         3 => Swap::try_from((data, accounts))?.process(),
+        4 => SetOracle::try_from((data, accounts))?.process(),
+        5 => SetState::try_from((data, accounts))?.process(),
+        6 => SetPermissioned::try_from((data, accounts))?.process(),
+        7 => AddTrader::try_from(accounts)?.process(),
+        8 => RemoveTrader::try_from(accounts)?.process(),
+        9 => SetHook::try_from(accounts)?.process(),
+        10 => Sync::try_from(accounts)?.process(),
+        11 => Skim::try_from(accounts)?.process(),
+        12 => ClosePool::try_from(accounts)?.process(),
+        13 => QuoteSwap::try_from((data, accounts))?.process(),
+        14 => QuoteDeposit::try_from((data, accounts))?.process(),
+        15 => QuoteWithdraw::try_from((data, accounts))?.process(),
+        16 => Migrate::try_from(accounts)?.process(),
+        17 => CreateFarm::try_from((data, accounts))?.process(),
+        18 => FundRewards::try_from((data, accounts))?.process(),
+        19 => Stake::try_from((data, accounts))?.process(),
+        20 => Unstake::try_from((data, accounts))?.process(),
+        21 => Claim::try_from(accounts)?.process(),
+        22 => CreateTwamm::try_from(accounts)?.process(),
+        23 => PlaceOrder::try_from((data, accounts))?.process(),
+        24 => Execute::try_from(accounts)?.process(),
+        25 => WithdrawProceeds::try_from(accounts)?.process(),
+        26 => SetPriceBand::try_from((data, accounts))?.process(),
+        27 => CreateMultisig::try_from((data, accounts))?.process(),
+        28 => Propose::try_from((data, accounts))?.process(),
+        29 => Approve::try_from(accounts)?.process(),
+        30 => ExecuteProposal::try_from(accounts)?.process(),
+        31 => SetAuthority::try_from((data, accounts))?.process(),
+        32 => SetGuardian::try_from((data, accounts))?.process(),
+        33 => OpenPosition::try_from(accounts)?.process(),
+        34 => QuotePosition::try_from(accounts)?.process(),
+        35 => CreateSettings::try_from((data, accounts))?.process(),
+        36 => UpdateSettings::try_from((data, accounts))?.process(),
+        37 => CollectCreationFees::try_from(accounts)?.process(),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -0,0 +1,27 @@
+use blueshift_amm_math::CurveError;
+use pinocchio::error::ProgramError;
+
+// 比例计算的实现在 blueshift_amm_math 中 这里只是把错误转换成 ProgramError
+// 调用方需要明确选择取整方向 原则是让池子占便宜：
+// 用户拿走的数量向下取整 用户需要支付的数量向上取整
+
+// 曲线计算的错误对应的 ProgramError
+#[inline(always)]
+pub fn curve_error(error: CurveError) -> ProgramError {
+    match error {
+        CurveError::Overflow => ProgramError::ArithmeticOverflow,
+        _ => ProgramError::InvalidArgument,
+    }
+}
+
+// a * b / c 向下取整
+#[inline(always)]
+pub fn mul_div_floor(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
+    blueshift_amm_math::mul_div_floor(a, b, c).map_err(curve_error)
+}
+
+// a * b / c 向上取整
+#[inline(always)]
+pub fn mul_div_ceil(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
+    blueshift_amm_math::mul_div_ceil(a, b, c).map_err(curve_error)
+}
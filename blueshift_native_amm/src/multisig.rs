@@ -0,0 +1,303 @@
+use pinocchio::account::{Ref, RefMut};
+use pinocchio::cpi::{invoke_signed_with_slice, Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::instruction::{InstructionAccount, InstructionView};
+use pinocchio::{AccountView, Address, ProgramResult};
+
+// 由 AMM 持有的 M-of-N 多签
+// 把 config 的管理员设置成多签账户之后 管理指令需要先提交提案 收集到足够的批准后再执行
+// 执行提案时程序用多签 PDA 签名 CPI 调用自己 所以所有的管理指令不需要任何改动就可以接受多签
+// 注意多签账户本身带有数据 不能作为 CreateAccount 的付款方 需要管理员付租金的指令（CreateFarm / CreateTwamm）
+// 需要在交给多签之前由单个管理员完成 AddTrader 的租金由单独的付款方支付 付款方在执行提案的交易中签名即可
+
+// 多签成员的最大数量 批准记录用 u16 的位图保存
+pub const MAX_SIGNERS: usize = 10;
+// 提案中保存的指令数据的最大长度（包括指令编号）
+pub const MAX_PROPOSAL_DATA: usize = 128;
+// 提案中保存的账户的最大数量
+pub const MAX_PROPOSAL_ACCOUNTS: usize = 16;
+
+// 多签账户 seeds = [b"multisig", creator, seed]
+#[repr(C)]
+pub struct Multisig {
+    creator: Address,
+    seed: [u8; 8],
+    // 执行提案需要的最少批准数量
+    threshold: u8,
+    signer_count: u8,
+    signers: [Address; MAX_SIGNERS],
+    // 已经创建的提案数量 同时也是下一个提案的编号
+    proposal_count: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl Multisig {
+    pub const LEN: usize = size_of::<Multisig>();
+
+    #[inline(always)]
+    pub fn load(account: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account.try_borrow()?, |data| unsafe {
+            &*(data.as_ptr() as *const Multisig)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account.try_borrow_mut()?, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut Multisig)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn creator(&self) -> &Address {
+        &self.creator
+    }
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+    #[inline(always)]
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+    #[inline(always)]
+    pub fn signers(&self) -> &[Address] {
+        &self.signers[..self.signer_count as usize]
+    }
+    #[inline(always)]
+    pub fn proposal_count(&self) -> u64 {
+        u64::from_le_bytes(self.proposal_count)
+    }
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    // 成员在多签中的位置 不是成员返回错误
+    #[inline(always)]
+    pub fn signer_index(&self, signer: &Address) -> Result<usize, ProgramError> {
+        self.signers()
+            .iter()
+            .position(|address| address.eq(signer))
+            .ok_or(ProgramError::IncorrectAuthority)
+    }
+
+    pub fn set_inner(
+        &mut self,
+        creator: Address,
+        seed: u64,
+        threshold: u8,
+        signers: &[Address],
+        bump: [u8; 1],
+    ) -> Result<(), ProgramError> {
+        if signers.is_empty() || signers.len() > MAX_SIGNERS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if threshold == 0 || threshold as usize > signers.len() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        // 重复的成员会让一个人拥有多票
+        for (i, signer) in signers.iter().enumerate() {
+            if signers[..i].contains(signer) {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        }
+        self.creator = creator;
+        self.seed = seed.to_le_bytes();
+        self.threshold = threshold;
+        self.signer_count = signers.len() as u8;
+        self.signers[..signers.len()].clone_from_slice(signers);
+        self.proposal_count = 0u64.to_le_bytes();
+        self.bump = bump;
+        Ok(())
+    }
+
+    // 分配下一个提案编号
+    #[inline(always)]
+    pub fn next_proposal(&mut self) -> Result<u64, ProgramError> {
+        let index = self.proposal_count();
+        self.proposal_count = index
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+        Ok(index)
+    }
+
+    #[inline(always)]
+    pub fn signer_seeds<'a>(
+        creator: &'a Address,
+        seed: &'a [u8; 8],
+        bump: &'a [u8; 1],
+    ) -> [Seed<'a>; 4] {
+        [
+            Seed::from(b"multisig"),
+            Seed::from(creator.as_ref()),
+            Seed::from(seed),
+            Seed::from(bump),
+        ]
+    }
+}
+
+// 提案 seeds = [b"proposal", multisig, index]
+// 保存一条完整的 AMM 指令 执行时原样 CPI 调用
+#[repr(C)]
+pub struct Proposal {
+    multisig: Address,
+    index: [u8; 8],
+    proposer: Address,
+    // 已经批准的成员 第 i 位对应多签中的第 i 个成员
+    approvals: [u8; 2],
+    executed: u8,
+    data_len: u8,
+    data: [u8; MAX_PROPOSAL_DATA],
+    account_count: u8,
+    accounts: [Address; MAX_PROPOSAL_ACCOUNTS],
+    bump: [u8; 1],
+}
+
+impl Proposal {
+    pub const LEN: usize = size_of::<Proposal>();
+
+    #[inline(always)]
+    pub fn load_mut(account: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account.try_borrow_mut()?, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut Proposal)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn multisig(&self) -> &Address {
+        &self.multisig
+    }
+    #[inline(always)]
+    pub fn index(&self) -> u64 {
+        u64::from_le_bytes(self.index)
+    }
+    #[inline(always)]
+    pub fn proposer(&self) -> &Address {
+        &self.proposer
+    }
+    #[inline(always)]
+    pub fn is_executed(&self) -> bool {
+        self.executed != 0
+    }
+    #[inline(always)]
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.data_len as usize]
+    }
+    #[inline(always)]
+    pub fn accounts(&self) -> &[Address] {
+        &self.accounts[..self.account_count as usize]
+    }
+    #[inline(always)]
+    pub fn approvals(&self) -> u32 {
+        u16::from_le_bytes(self.approvals).count_ones()
+    }
+
+    pub fn set_inner(
+        &mut self,
+        multisig: Address,
+        index: u64,
+        proposer: Address,
+        data: &[u8],
+        accounts: &[AccountView],
+        bump: [u8; 1],
+    ) -> Result<(), ProgramError> {
+        if data.is_empty() || data.len() > MAX_PROPOSAL_DATA {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if accounts.len() > MAX_PROPOSAL_ACCOUNTS {
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.multisig = multisig;
+        self.index = index.to_le_bytes();
+        self.proposer = proposer;
+        self.approvals = 0u16.to_le_bytes();
+        self.executed = 0;
+        self.data_len = data.len() as u8;
+        self.data[..data.len()].copy_from_slice(data);
+        self.account_count = accounts.len() as u8;
+        for (slot, account) in self.accounts.iter_mut().zip(accounts) {
+            *slot = account.address().clone();
+        }
+        self.bump = bump;
+        Ok(())
+    }
+
+    // 记录第 signer_index 个成员的批准 重复批准不会增加票数
+    #[inline(always)]
+    pub fn approve(&mut self, signer_index: usize) -> Result<(), ProgramError> {
+        if self.is_executed() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let approvals = u16::from_le_bytes(self.approvals) | (1u16 << signer_index);
+        self.approvals = approvals.to_le_bytes();
+        Ok(())
+    }
+
+    // 批准数量达到门槛之后标记为已执行 每个提案只能执行一次
+    #[inline(always)]
+    pub fn mark_executed(&mut self, threshold: u8) -> Result<(), ProgramError> {
+        if self.is_executed() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if self.approvals() < threshold as u32 {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        self.executed = 1;
+        Ok(())
+    }
+}
+
+// 用多签 PDA 签名 CPI 调用 AMM 自己
+// 传入的账户必须和提案中记录的账户完全一致 多签账户本身作为签名者 其余账户保留原有的签名和可写属性
+pub fn invoke_proposal(
+    multisig: &AccountView,
+    expected: &[Address],
+    data: &[u8],
+    accounts: &[AccountView],
+    multisig_signer: &[Signer],
+) -> ProgramResult {
+    if accounts.len() != expected.len() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let mut instruction_accounts = Vec::with_capacity(accounts.len());
+    let mut account_views = Vec::with_capacity(accounts.len());
+    for (account, address) in accounts.iter().zip(expected) {
+        if account.address().ne(address) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let is_signer = account.is_signer() || account.address().eq(multisig.address());
+        instruction_accounts.push(InstructionAccount::new(
+            account.address(),
+            account.is_writable(),
+            is_signer,
+        ));
+        account_views.push(account);
+    }
+
+    let instruction = InstructionView {
+        program_id: &crate::ID,
+        data,
+        accounts: &instruction_accounts,
+    };
+    invoke_signed_with_slice(&instruction, &account_views, multisig_signer)
+}
@@ -0,0 +1,53 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_system::instructions::Transfer as SystemTransfer;
+use pinocchio_token::instructions::{CloseAccount, SyncNative};
+use pinocchio_token::state::TokenAccount;
+use solana_address::address;
+
+// 原生 SOL 对应的铸币地址（wrapped SOL）
+pub const NATIVE_MINT: Address = address!("So11111111111111111111111111111111111111112");
+
+// 直接用 lamports 支付时 用户代币账户的位置传入系统程序
+#[inline(always)]
+pub fn check_lamport_source(account: &AccountView) -> Result<(), ProgramError> {
+    if account.address().ne(&pinocchio_system::ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// 接收原生 SOL 时输出账户会在交易结束时关闭 只接受空的临时 wSOL 账户
+// 防止把用户 wSOL 关联账户里原有的 wSOL 一起换成 SOL 并关闭这个账户
+#[inline(always)]
+pub fn check_temporary_wsol(account: &AccountView) -> Result<(), ProgramError> {
+    if TokenAccount::from_account_view(account)?.amount() != 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+// 把用户的 lamports 直接转入 wSOL 金库 再 SyncNative 让代币余额和 lamports 保持一致
+#[inline(always)]
+pub fn wrap(user: &AccountView, vault: &AccountView, lamports: u64) -> ProgramResult {
+    SystemTransfer {
+        from: user,
+        to: vault,
+        lamports,
+    }
+    .invoke()?;
+    SyncNative {
+        native_token: vault,
+    }
+    .invoke()
+}
+
+// 关闭用户临时的 wSOL 账户 里面的 wSOL 和租金都以 lamports 的形式返还给用户
+#[inline(always)]
+pub fn unwrap(account: &AccountView, user: &AccountView) -> ProgramResult {
+    CloseAccount {
+        account,
+        destination: user,
+        authority: user,
+    }
+    .invoke()
+}
@@ -0,0 +1,234 @@
+use pinocchio::error::ProgramError;
+use pinocchio::AccountView;
+
+// Pyth 旧版（v2）价格账户的布局，这里只解析需要用到的字段
+// 参考 pyth-sdk-solana 里面的 SolanaPriceAccount
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+const PYTH_VERSION: u32 = 2;
+const PYTH_ACCOUNT_TYPE_PRICE: u32 = 3;
+const PYTH_STATUS_TRADING: u32 = 1;
+
+const MAGIC_OFFSET: usize = 0;
+const VERSION_OFFSET: usize = 4;
+const ACCOUNT_TYPE_OFFSET: usize = 8;
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+
+#[derive(Debug, Clone, Copy)]
+pub struct OraclePrice {
+    // 聚合价格 真实价格 = price * 10^expo
+    pub price: i64,
+    // 价格的置信区间 和 price 使用同一个 expo
+    pub conf: u64,
+    // 价格的指数
+    pub expo: i32,
+    // 价格最后一次更新的 unix 时间戳
+    pub timestamp: i64,
+}
+
+impl OraclePrice {
+    // 价格账户的最小长度 只需要覆盖到 agg.status 即可
+    pub const MIN_LEN: usize = AGG_STATUS_OFFSET + size_of::<u32>();
+
+    #[inline(always)]
+    pub fn load(account: &AccountView) -> Result<Self, ProgramError> {
+        let data = account.try_borrow()?;
+        Self::from_bytes(&data)
+    }
+
+    // 直接从字节解析 这样在链下也可以用本地构造的账户数据来测试
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::MIN_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if read_u32(data, MAGIC_OFFSET) != PYTH_MAGIC
+            || read_u32(data, VERSION_OFFSET) != PYTH_VERSION
+            || read_u32(data, ACCOUNT_TYPE_OFFSET) != PYTH_ACCOUNT_TYPE_PRICE
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // 只有处于 Trading 状态的价格才是可信的
+        if read_u32(data, AGG_STATUS_OFFSET) != PYTH_STATUS_TRADING {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let price = read_i64(data, AGG_PRICE_OFFSET);
+        if price <= 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            price,
+            conf: read_i64(data, AGG_CONF_OFFSET) as u64,
+            expo: read_u32(data, EXPO_OFFSET) as i32,
+            timestamp: read_i64(data, TIMESTAMP_OFFSET),
+        })
+    }
+
+    // 校验价格是否过期
+    // now: 当前的 unix 时间戳
+    // max_age: 允许的最大价格延迟（秒）
+    pub fn check_staleness(&self, now: i64, max_age: u64) -> Result<(), ProgramError> {
+        let age = now.saturating_sub(self.timestamp);
+        if age < 0 || age as u64 > max_age {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    // 校验价格的置信区间 区间比允许的偏差还宽时价格本身就不可信 偏差校验也就没有意义
+    // max_deviation: 允许的最大偏差 以基点表示
+    pub fn check_confidence(&self, max_deviation: u16) -> Result<(), ProgramError> {
+        // conf / price <= max_deviation / 10_000
+        if self.conf as u128 * 10_000 > self.price as u128 * max_deviation as u128 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    // 校验成交价格和预言机价格的偏差是否在允许的区间内
+    // 预言机价格表示的是 1 个 X 值多少个 Y（都是带小数位的真实单位）
+    // amount_x / amount_y: 本次交易中 X 和 Y 的原始数量（最小单位）
+    // decimals_x / decimals_y: X 和 Y 的小数位
+    // max_deviation: 允许的最大偏差 以基点表示
+    pub fn check_price_band(
+        &self,
+        amount_x: u64,
+        amount_y: u64,
+        decimals_x: u8,
+        decimals_y: u8,
+        max_deviation: u16,
+    ) -> Result<(), ProgramError> {
+        // 先按照预言机价格算出 amount_x 应该对应多少 Y
+        // expected_y = amount_x * price * 10^(expo + decimals_y - decimals_x)
+        let expected_y = (amount_x as u128)
+            .checked_mul(self.price as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let exponent = self.expo as i64 + decimals_y as i64 - decimals_x as i64;
+        let expected_y = if exponent >= 0 {
+            10u128
+                .checked_pow(exponent as u32)
+                .and_then(|scale| expected_y.checked_mul(scale))
+                .ok_or(ProgramError::ArithmeticOverflow)?
+        } else {
+            match 10u128.checked_pow(exponent.unsigned_abs() as u32) {
+                Some(scale) => expected_y / scale,
+                // 缩放系数超过 u128 说明结果必然为 0
+                None => 0,
+            }
+        };
+        if expected_y == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        // |amount_y - expected_y| / expected_y <= max_deviation / 10_000
+        let diff = (amount_y as u128).abs_diff(expected_y);
+        let lhs = diff
+            .checked_mul(10_000)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let rhs = expected_y
+            .checked_mul(max_deviation as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if lhs > rhs {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
+#[inline(always)]
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+#[inline(always)]
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按 Pyth v2 的布局手动拼出一个价格账户
+    fn pack(price: i64, conf: u64, expo: i32, timestamp: i64, status: u32) -> Vec<u8> {
+        let mut data = vec![0u8; 240];
+        data[MAGIC_OFFSET..MAGIC_OFFSET + 4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[VERSION_OFFSET..VERSION_OFFSET + 4].copy_from_slice(&PYTH_VERSION.to_le_bytes());
+        data[ACCOUNT_TYPE_OFFSET..ACCOUNT_TYPE_OFFSET + 4]
+            .copy_from_slice(&PYTH_ACCOUNT_TYPE_PRICE.to_le_bytes());
+        data[EXPO_OFFSET..EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+        data[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + 8].copy_from_slice(&timestamp.to_le_bytes());
+        data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[AGG_CONF_OFFSET..AGG_CONF_OFFSET + 8].copy_from_slice(&conf.to_le_bytes());
+        data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+        data
+    }
+
+    // 1 X = 2.00000000 Y
+    fn valid() -> Vec<u8> {
+        pack(200_000_000, 100_000, -8, 1_000, PYTH_STATUS_TRADING)
+    }
+
+    #[test]
+    fn parses_valid_price() {
+        let price = OraclePrice::from_bytes(&valid()).unwrap();
+        assert_eq!(price.price, 200_000_000);
+        assert_eq!(price.conf, 100_000);
+        assert_eq!(price.expo, -8);
+        assert_eq!(price.timestamp, 1_000);
+        assert!(price.check_staleness(1_060, 60).is_ok());
+        assert!(price.check_confidence(100).is_ok());
+        // X 和 Y 都是 6 位小数 1 X 换 2 Y 正好等于预言机价格
+        assert!(price
+            .check_price_band(1_000_000, 2_000_000, 6, 6, 100)
+            .is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_accounts() {
+        let mut data = valid();
+        data[MAGIC_OFFSET] ^= 1;
+        assert!(OraclePrice::from_bytes(&data).is_err());
+        assert!(OraclePrice::from_bytes(&valid()[..OraclePrice::MIN_LEN - 1]).is_err());
+        // 不在 Trading 状态 或者价格不是正数
+        assert!(OraclePrice::from_bytes(&pack(200_000_000, 0, -8, 1_000, 0)).is_err());
+        assert!(OraclePrice::from_bytes(&pack(0, 0, -8, 1_000, PYTH_STATUS_TRADING)).is_err());
+    }
+
+    #[test]
+    fn rejects_stale_price() {
+        let price = OraclePrice::from_bytes(&valid()).unwrap();
+        assert!(price.check_staleness(1_061, 60).is_err());
+        // 时间戳比当前时间还新也不接受
+        assert!(price.check_staleness(999, 60).is_err());
+    }
+
+    #[test]
+    fn rejects_wide_confidence() {
+        // 置信区间 1% 超过允许的 0.5%
+        let data = pack(200_000_000, 2_000_000, -8, 1_000, PYTH_STATUS_TRADING);
+        let price = OraclePrice::from_bytes(&data).unwrap();
+        assert!(price.check_confidence(100).is_ok());
+        assert!(price.check_confidence(50).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_band_trade() {
+        let price = OraclePrice::from_bytes(&valid()).unwrap();
+        // 偏离 1% 在 1% 的范围内 偏离 1.01% 超出
+        assert!(price
+            .check_price_band(1_000_000, 2_020_000, 6, 6, 100)
+            .is_ok());
+        assert!(price
+            .check_price_band(1_000_000, 1_979_800, 6, 6, 100)
+            .is_err());
+        // 小数位不同时按真实单位比较 Y 有 9 位小数
+        assert!(price
+            .check_price_band(1_000_000, 2_000_000_000, 6, 9, 100)
+            .is_ok());
+        assert!(price
+            .check_price_band(1_000_000, 2_000_000, 6, 9, 100)
+            .is_err());
+    }
+}
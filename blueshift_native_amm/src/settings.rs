@@ -0,0 +1,153 @@
+use pinocchio::account::{Ref, RefMut};
+use pinocchio::error::ProgramError;
+use pinocchio::{AccountView, Address};
+use solana_address::address;
+
+// 全局的 AMM 设置 整个程序只有一个 PDA seeds = [b"settings"]
+// 由协议管理员控制 规定创建池子时允许使用的费率档位和需要支付的创建费
+// 同一个交易对只能在几个固定的费率上建池 流动性不会分散到任意费率的池子中
+// 程序部署之后需要先由升级权限持有者调用 CreateSettings 在此之前 Initialize 无法创建池子
+// 创建费以 lamports 的形式留在设置账户中 管理员通过 CollectCreationFees 取出
+
+// 费率档位的最大数量
+pub const MAX_FEE_TIERS: usize = 8;
+
+// 可升级程序的 loader 程序的 ProgramData 账户记录了升级权限
+pub const BPF_LOADER_UPGRADEABLE_ID: Address =
+    address!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+#[repr(C)]
+pub struct Settings {
+    // 协议管理员 可以是多签账户
+    admin: Address,
+    // 创建池子需要支付的 lamports
+    creation_fee: [u8; 8],
+    fee_tier_count: u8,
+    // 允许的费率档位 以基点表示
+    fee_tiers: [[u8; 2]; MAX_FEE_TIERS],
+    bump: [u8; 1],
+}
+
+impl Settings {
+    pub const LEN: usize = size_of::<Settings>();
+
+    #[inline(always)]
+    pub fn load(account: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account.try_borrow()?, |data| unsafe {
+            &*(data.as_ptr() as *const Settings)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account.try_borrow_mut()?, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut Settings)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn admin(&self) -> &Address {
+        &self.admin
+    }
+    #[inline(always)]
+    pub fn creation_fee(&self) -> u64 {
+        u64::from_le_bytes(self.creation_fee)
+    }
+    #[inline(always)]
+    pub fn fee_tiers(&self) -> impl Iterator<Item = u16> + '_ {
+        self.fee_tiers[..self.fee_tier_count as usize]
+            .iter()
+            .map(|tier| u16::from_le_bytes(*tier))
+    }
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn is_allowed_fee(&self, fee: u16) -> bool {
+        self.fee_tiers().any(|tier| tier == fee)
+    }
+
+    #[inline(always)]
+    pub fn set_bump(&mut self, bump: [u8; 1]) {
+        self.bump = bump;
+    }
+
+    // 费率档位在解析指令数据时已经校验过
+    #[inline(always)]
+    pub fn set_params(&mut self, admin: Address, creation_fee: u64, fee_tiers: &[u16]) {
+        self.admin = admin;
+        self.creation_fee = creation_fee.to_le_bytes();
+        self.fee_tier_count = fee_tiers.len() as u8;
+        self.fee_tiers = [[0; 2]; MAX_FEE_TIERS];
+        for (slot, tier) in self.fee_tiers.iter_mut().zip(fee_tiers) {
+            *slot = tier.to_le_bytes();
+        }
+    }
+
+    // 校验设置账户是 [b"settings"] 推导出来的 防止其他同样长度的程序账户冒充
+    #[inline(always)]
+    pub fn check(account: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
+        let settings = Self::load(account)?;
+        let address = Address::create_program_address(&[b"settings", &settings.bump()], &crate::ID)
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+        if address.ne(account.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(settings)
+    }
+
+    // 只有协议管理员可以修改设置
+    #[inline(always)]
+    pub fn check_admin(&self, admin: &AccountView) -> Result<(), ProgramError> {
+        if !admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if self.admin.ne(admin.address()) {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+        Ok(())
+    }
+}
+
+// 校验 authority 是这个程序的升级权限持有者
+// ProgramData 的布局：[tag: u32 = 3, slot: u64, upgrade_authority: Option<Address>]
+#[inline(always)]
+pub fn check_upgrade_authority(
+    program_data: &AccountView,
+    authority: &AccountView,
+) -> Result<(), ProgramError> {
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !program_data.owned_by(&BPF_LOADER_UPGRADEABLE_ID) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let (address, _) =
+        Address::find_program_address(&[crate::ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+    if address.ne(program_data.address()) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let data = program_data.try_borrow()?;
+    if data.len() < 45 || data[0..4] != 3u32.to_le_bytes() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // 升级权限为 None 的程序不可升级 也就没有人可以创建设置
+    if data[12] != 1 || data[13..45] != *authority.address().as_ref() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+    Ok(())
+}
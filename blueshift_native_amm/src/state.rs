@@ -1,9 +1,12 @@
 use pinocchio::account::{Ref, RefMut};
+use pinocchio::cpi::Seed;
 use pinocchio::error::ProgramError;
 use pinocchio::{AccountView, Address};
 
 #[repr(C)]
 pub struct Config {
+    // 账户布局的版本号 之后新增字段从 reserved 中划出 并递增版本号
+    version: u8,
     state: u8,
     seed: [u8; 8],
     authority: Address,
@@ -11,6 +14,43 @@ pub struct Config {
     mint_y: Address,
     fee: [u8; 2],
     config_bump: [u8; 1],
+    // 价格预言机账户 全 0 表示未启用预言机校验
+    oracle: Address,
+    // 成交价格与预言机价格允许的最大偏差 以基点表示
+    oracle_max_deviation: [u8; 2],
+    // 预言机价格允许的最大延迟（秒）
+    oracle_max_age: [u8; 8],
+    // 代币 X 和 Y 的小数位 用于把成交数量换算成预言机价格的单位
+    decimals_x: u8,
+    decimals_y: u8,
+    // 是否为许可池 许可池只允许白名单中的钱包交易和提供流动性
+    permissioned: u8,
+    // 交易前后需要 CPI 调用的 hook 程序 全 0 表示未启用
+    hook_program: Address,
+    // 池子记录的储备数量 只会被程序指令更新 直接转入金库的代币不会影响价格
+    reserve_x: [u8; 8],
+    reserve_y: [u8; 8],
+    // 创建池子的账户 关闭池子时可以代替管理员取回租金
+    initializer: Address,
+    // 初始化时推导好的金库和 LP 铸币地址 热路径上直接比较地址 不再重新推导 PDA
+    vault_x: Address,
+    vault_y: Address,
+    mint_lp: Address,
+    // 版本 2: 是否启用了 TWAMM 长期订单 启用后改变储备的指令都需要先结算长期订单
+    twamm: u8,
+    // 版本 2: 金库中属于 TWAMM 订单的代币 包括还没有卖出的部分和还没有领取的收益 不计入储备
+    twamm_escrow_x: [u8; 8],
+    twamm_escrow_y: [u8; 8],
+    // 版本 3: 同一个 slot 内价格相对该 slot 开盘价允许的最大偏离 以基点表示 0 表示不限制
+    slot_price_band: [u8; 2],
+    // 版本 3: 开盘价记录所在的 slot 以及该 slot 第一笔交易之前的储备
+    open_slot: [u8; 8],
+    open_reserve_x: [u8; 8],
+    open_reserve_y: [u8; 8],
+    // 版本 4: 紧急情况下只能暂停池子的守护者 全 0 表示未设置
+    guardian: Address,
+    // 预留空间 新增字段时不需要再 realloc 默认值为 0
+    reserved: [u8; 181],
 }
 
 #[repr(u8)]
@@ -23,6 +63,10 @@ pub enum AmmState {
 
 impl Config {
     pub const LEN: usize = size_of::<Config>();
+    // 当前的布局版本
+    pub const VERSION: u8 = 4;
+    // 没有版本号的旧布局 只到 config_bump 为止 字段顺序和当前布局一致 只是少了开头的 version
+    pub const LEGACY_LEN: usize = core::mem::offset_of!(Config, oracle) - 1;
 
     #[inline(always)]
     pub fn load(account: &AccountView) -> Result<Ref<Self>, ProgramError> {
@@ -32,9 +76,11 @@ impl Config {
         if !account.owned_by(&crate::ID) {
             return Err(ProgramError::InvalidAccountOwner);
         }
-        Ok(Ref::map(account.try_borrow()?, |data| unsafe {
+        let config = Ref::map(account.try_borrow()?, |data| unsafe {
             Self::from_bytes_unchecked(data)
-        }))
+        });
+        config.check_version()?;
+        Ok(config)
     }
 
     #[inline(always)]
@@ -45,7 +91,18 @@ impl Config {
         if !account.owned_by(&crate::ID) {
             return Err(ProgramError::InvalidAccountOwner);
         }
-        Ok(unsafe { Self::from_bytes_unchecked(&account.borrow_unchecked()) })
+        let config = unsafe { Self::from_bytes_unchecked(account.borrow_unchecked()) };
+        config.check_version()?;
+        Ok(config)
+    }
+
+    // 只接受已知的版本 旧布局的账户需要先调用 Migrate
+    #[inline(always)]
+    pub fn check_version(&self) -> Result<(), ProgramError> {
+        if self.version == 0 || self.version > Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
     }
 
     #[inline(always)]
@@ -59,6 +116,10 @@ impl Config {
     }
     // Getter methods for safe field access
     #[inline(always)]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+    #[inline(always)]
     pub fn state(&self) -> u8 {
         self.state
     }
@@ -86,6 +147,85 @@ impl Config {
     pub fn config_bump(&self) -> [u8; 1] {
         self.config_bump
     }
+    #[inline(always)]
+    pub fn oracle(&self) -> &Address {
+        &self.oracle
+    }
+    #[inline(always)]
+    pub fn oracle_max_deviation(&self) -> u16 {
+        u16::from_le_bytes(self.oracle_max_deviation)
+    }
+    #[inline(always)]
+    pub fn oracle_max_age(&self) -> u64 {
+        u64::from_le_bytes(self.oracle_max_age)
+    }
+    #[inline(always)]
+    pub fn decimals_x(&self) -> u8 {
+        self.decimals_x
+    }
+    #[inline(always)]
+    pub fn decimals_y(&self) -> u8 {
+        self.decimals_y
+    }
+    #[inline(always)]
+    pub fn is_permissioned(&self) -> bool {
+        self.permissioned != 0
+    }
+    #[inline(always)]
+    pub fn hook_program(&self) -> &Address {
+        &self.hook_program
+    }
+    #[inline(always)]
+    pub fn initializer(&self) -> &Address {
+        &self.initializer
+    }
+    #[inline(always)]
+    pub fn vault_x(&self) -> &Address {
+        &self.vault_x
+    }
+    #[inline(always)]
+    pub fn vault_y(&self) -> &Address {
+        &self.vault_y
+    }
+    #[inline(always)]
+    pub fn mint_lp(&self) -> &Address {
+        &self.mint_lp
+    }
+    #[inline(always)]
+    pub fn has_twamm(&self) -> bool {
+        self.twamm != 0
+    }
+    #[inline(always)]
+    pub fn twamm_escrow_x(&self) -> u64 {
+        u64::from_le_bytes(self.twamm_escrow_x)
+    }
+    #[inline(always)]
+    pub fn twamm_escrow_y(&self) -> u64 {
+        u64::from_le_bytes(self.twamm_escrow_y)
+    }
+    #[inline(always)]
+    pub fn slot_price_band(&self) -> u16 {
+        u16::from_le_bytes(self.slot_price_band)
+    }
+    #[inline(always)]
+    pub fn open_slot(&self) -> u64 {
+        u64::from_le_bytes(self.open_slot)
+    }
+    #[inline(always)]
+    pub fn open_reserves(&self) -> (u64, u64) {
+        (
+            u64::from_le_bytes(self.open_reserve_x),
+            u64::from_le_bytes(self.open_reserve_y),
+        )
+    }
+    #[inline(always)]
+    pub fn reserve_x(&self) -> u64 {
+        u64::from_le_bytes(self.reserve_x)
+    }
+    #[inline(always)]
+    pub fn reserve_y(&self) -> u64 {
+        u64::from_le_bytes(self.reserve_y)
+    }
 
     #[inline(always)]
     pub fn load_mut(account_info: &AccountView) -> Result<RefMut<Self>, ProgramError> {
@@ -95,14 +235,44 @@ impl Config {
         if !account_info.owned_by(&crate::ID) {
             return Err(ProgramError::InvalidAccountOwner);
         }
-        Ok(RefMut::map(account_info.try_borrow_mut()?, |data| unsafe {
+        let config = RefMut::map(account_info.try_borrow_mut()?, |data| unsafe {
             Self::from_bytes_unchecked_mut(data)
-        }))
+        });
+        config.check_version()?;
+        Ok(config)
+    }
+
+    // 新创建的 config 还没有版本号 这里写入当前版本后返回可变引用 只在 Initialize 中使用
+    #[inline(always)]
+    pub fn init(account_info: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_info.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let mut config = RefMut::map(account_info.try_borrow_mut()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        });
+        if config.version != 0 || config.state != AmmState::Uninitialized as u8 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        config.version = Self::VERSION;
+        Ok(config)
+    }
+
+    #[inline(always)]
+    pub fn set_version(&mut self, version: u8) -> Result<(), ProgramError> {
+        if version == 0 || version > Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.version = version;
+        Ok(())
     }
 
     #[inline(always)]
     pub fn set_state(&mut self, state: u8) -> Result<(), ProgramError> {
-        if state.ge(&(AmmState::WithdrawOnly as u8)) {
+        if state.gt(&(AmmState::WithdrawOnly as u8)) {
             return Err(ProgramError::InvalidAccountData);
         }
         self.state = state as u8;
@@ -148,6 +318,140 @@ impl Config {
         Ok(())
     }
 
+    #[inline(always)]
+    pub fn set_permissioned(&mut self, permissioned: bool) -> Result<(), ProgramError> {
+        self.permissioned = permissioned as u8;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_initializer(&mut self, initializer: Address) -> Result<(), ProgramError> {
+        self.initializer = initializer;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_vaults(&mut self, vault_x: Address, vault_y: Address) -> Result<(), ProgramError> {
+        self.vault_x = vault_x;
+        self.vault_y = vault_y;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_mint_lp(&mut self, mint_lp: Address) -> Result<(), ProgramError> {
+        self.mint_lp = mint_lp;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_reserves(&mut self, reserve_x: u64, reserve_y: u64) -> Result<(), ProgramError> {
+        self.reserve_x = reserve_x.to_le_bytes();
+        self.reserve_y = reserve_y.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_twamm(&mut self, twamm: bool) -> Result<(), ProgramError> {
+        self.twamm = twamm as u8;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_twamm_escrow(&mut self, escrow_x: u64, escrow_y: u64) -> Result<(), ProgramError> {
+        self.twamm_escrow_x = escrow_x.to_le_bytes();
+        self.twamm_escrow_y = escrow_y.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_slot_price_band(&mut self, band: u16) -> Result<(), ProgramError> {
+        if band >= 10_000 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.slot_price_band = band.to_le_bytes();
+        Ok(())
+    }
+
+    // 每个 slot 的第一笔交易把交易前的储备记为开盘价
+    // 之后同一个 slot 内的交易 成交后的价格都不能偏离开盘价超过 slot_price_band
+    // 三明治攻击需要在同一个 slot 内先推高价格再砸回来 带宽限制了推高的幅度
+    pub fn check_slot_price_band(
+        &mut self,
+        slot: u64,
+        reserve_x: u64,
+        reserve_y: u64,
+        new_reserve_x: u64,
+        new_reserve_y: u64,
+    ) -> Result<(), ProgramError> {
+        let band = self.slot_price_band();
+        if band == 0 {
+            return Ok(());
+        }
+        if slot != self.open_slot() {
+            self.open_slot = slot.to_le_bytes();
+            self.open_reserve_x = reserve_x.to_le_bytes();
+            self.open_reserve_y = reserve_y.to_le_bytes();
+        }
+        let (open_x, open_y) = self.open_reserves();
+        // 价格用 Y / X 表示 左移 64 位保留精度 u64 左移 64 位不会超过 u128
+        let price = |x: u64, y: u64| -> Result<u128, ProgramError> {
+            if x == 0 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Ok(((y as u128) << 64) / x as u128)
+        };
+        let open_price = price(open_x, open_y)?;
+        let new_price = price(new_reserve_x, new_reserve_y)?;
+        // |new_price - open_price| / open_price <= band / 10_000
+        let lhs = new_price
+            .abs_diff(open_price)
+            .checked_mul(10_000)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let rhs = open_price
+            .checked_mul(band as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if lhs > rhs {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    // 更新储备的快捷方法 调用前需要先释放对 config 的借用
+    #[inline(always)]
+    pub fn update_reserves(
+        account: &AccountView,
+        reserve_x: u64,
+        reserve_y: u64,
+    ) -> Result<(), ProgramError> {
+        Self::load_mut(account)?.set_reserves(reserve_x, reserve_y)
+    }
+
+    #[inline(always)]
+    pub fn set_hook_program(&mut self, hook_program: Address) -> Result<(), ProgramError> {
+        self.hook_program = hook_program;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_oracle(
+        &mut self,
+        oracle: Address,
+        max_deviation: u16,
+        max_age: u64,
+        decimals_x: u8,
+        decimals_y: u8,
+    ) -> Result<(), ProgramError> {
+        if max_deviation.ge(&10_000) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.oracle = oracle;
+        self.oracle_max_deviation = max_deviation.to_le_bytes();
+        self.oracle_max_age = max_age.to_le_bytes();
+        self.decimals_x = decimals_x;
+        self.decimals_y = decimals_y;
+        Ok(())
+    }
+
     #[inline(always)]
     pub fn set_inner(
         &mut self,
@@ -167,14 +471,689 @@ impl Config {
         self.set_config_bump(config_bump)?;
         Ok(())
     }
+    #[inline(always)]
+    pub fn has_guardian(&self) -> Option<&Address> {
+        if self.guardian.ne(&Address::default()) {
+            Some(&self.guardian)
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    pub fn set_guardian(&mut self, guardian: Address) -> Result<(), ProgramError> {
+        self.guardian = guardian;
+        Ok(())
+    }
+
     #[inline(always)]
     pub fn has_authority(&self) -> Option<Address> {
-        let bytes = self.authority();
-        let chunks: &[u64; 4] = unsafe { &*(bytes.to_bytes().as_ptr() as *const [u64; 4]) };
-        if chunks.iter().any(|&x| x != 0) {
+        // 全 0 地址表示已经放弃管理权限
+        if self.authority.ne(&Address::default()) {
             Some(self.authority.clone())
         } else {
             None
         }
     }
-}
\ No newline at end of file
+
+    #[inline(always)]
+    pub fn has_oracle(&self) -> bool {
+        self.oracle.ne(&Address::default())
+    }
+
+    #[inline(always)]
+    pub fn has_hook(&self) -> bool {
+        self.hook_program.ne(&Address::default())
+    }
+}
+
+// 许可池的白名单记录 每个 (config, trader) 对应一个 PDA
+// seeds = [b"allowlist", config, trader]
+#[repr(C)]
+pub struct AllowlistEntry {
+    config: Address,
+    trader: Address,
+    bump: [u8; 1],
+}
+
+impl AllowlistEntry {
+    pub const LEN: usize = size_of::<AllowlistEntry>();
+
+    #[inline(always)]
+    pub fn load(account: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account.try_borrow()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account.try_borrow_mut()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        }))
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` 的长度必须至少为 `AllowlistEntry::LEN`。
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        unsafe { &*(bytes.as_ptr() as *const AllowlistEntry) }
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` 的长度必须至少为 `AllowlistEntry::LEN`。
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        unsafe { &mut *(bytes.as_mut_ptr() as *mut AllowlistEntry) }
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Address {
+        &self.config
+    }
+    #[inline(always)]
+    pub fn trader(&self) -> &Address {
+        &self.trader
+    }
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, config: Address, trader: Address, bump: [u8; 1]) {
+        self.config = config;
+        self.trader = trader;
+        self.bump = bump;
+    }
+
+    // 校验白名单记录是否属于这个池子和这个钱包
+    // 这里用记录中的 bump 重新推导一次地址 防止其他同样长度的程序账户冒充
+    #[inline(always)]
+    pub fn check(
+        account: &AccountView,
+        config: &AccountView,
+        trader: &AccountView,
+    ) -> Result<(), ProgramError> {
+        let entry = Self::load(account)?;
+        if entry.config().ne(config.address()) || entry.trader().ne(trader.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let address = Address::create_program_address(
+            &[
+                b"allowlist",
+                config.address().as_ref(),
+                trader.address().as_ref(),
+                &entry.bump(),
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+        if address.ne(account.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+}
+
+// LP 质押挖矿的农场 每个池子对应一个 PDA
+// seeds = [b"farm", config]
+#[repr(C)]
+pub struct Farm {
+    config: Address,
+    mint_lp: Address,
+    reward_mint: Address,
+    // 农场持有的 LP 和奖励代币的 ATA 创建农场时推导好并记录下来
+    stake_vault: Address,
+    reward_vault: Address,
+    // 每秒释放的奖励数量
+    reward_rate: [u8; 8],
+    // 每一份 LP 累计可以领取的奖励 放大了 ACC_PRECISION 倍
+    acc_reward_per_share: [u8; 16],
+    // 上一次更新累加器的 unix 时间戳
+    last_update: [u8; 8],
+    // 当前质押的 LP 总量
+    total_staked: [u8; 8],
+    // 已经注入但还没有释放的奖励 释放完之后不再增加累加器
+    rewards_remaining: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl Farm {
+    pub const LEN: usize = size_of::<Farm>();
+    pub const ACC_PRECISION: u128 = 1_000_000_000_000;
+
+    #[inline(always)]
+    pub fn load(account: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account.try_borrow()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account.try_borrow_mut()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        }))
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` 的长度必须至少为 `Farm::LEN`。
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        unsafe { &*(bytes.as_ptr() as *const Farm) }
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` 的长度必须至少为 `Farm::LEN`。
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        unsafe { &mut *(bytes.as_mut_ptr() as *mut Farm) }
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Address {
+        &self.config
+    }
+    #[inline(always)]
+    pub fn mint_lp(&self) -> &Address {
+        &self.mint_lp
+    }
+    #[inline(always)]
+    pub fn reward_mint(&self) -> &Address {
+        &self.reward_mint
+    }
+    #[inline(always)]
+    pub fn stake_vault(&self) -> &Address {
+        &self.stake_vault
+    }
+    #[inline(always)]
+    pub fn reward_vault(&self) -> &Address {
+        &self.reward_vault
+    }
+    #[inline(always)]
+    pub fn reward_rate(&self) -> u64 {
+        u64::from_le_bytes(self.reward_rate)
+    }
+    #[inline(always)]
+    pub fn acc_reward_per_share(&self) -> u128 {
+        u128::from_le_bytes(self.acc_reward_per_share)
+    }
+    #[inline(always)]
+    pub fn last_update(&self) -> i64 {
+        i64::from_le_bytes(self.last_update)
+    }
+    #[inline(always)]
+    pub fn total_staked(&self) -> u64 {
+        u64::from_le_bytes(self.total_staked)
+    }
+    #[inline(always)]
+    pub fn rewards_remaining(&self) -> u64 {
+        u64::from_le_bytes(self.rewards_remaining)
+    }
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        config: Address,
+        mint_lp: Address,
+        reward_mint: Address,
+        stake_vault: Address,
+        reward_vault: Address,
+        bump: [u8; 1],
+    ) {
+        self.config = config;
+        self.mint_lp = mint_lp;
+        self.reward_mint = reward_mint;
+        self.stake_vault = stake_vault;
+        self.reward_vault = reward_vault;
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn set_reward_rate(&mut self, reward_rate: u64) {
+        self.reward_rate = reward_rate.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_last_update(&mut self, now: i64) {
+        self.last_update = now.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_total_staked(&mut self, total_staked: u64) {
+        self.total_staked = total_staked.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn add_rewards(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let remaining = self
+            .rewards_remaining()
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.rewards_remaining = remaining.to_le_bytes();
+        Ok(())
+    }
+
+    // 把累加器推进到 now 质押量或者数量变化之前必须先调用
+    // 没有人质押的时候不释放奖励 剩余奖励不足时只释放剩下的部分
+    pub fn update(&mut self, now: i64) -> Result<(), ProgramError> {
+        let elapsed = now.saturating_sub(self.last_update());
+        if elapsed <= 0 {
+            return Ok(());
+        }
+        let total_staked = self.total_staked();
+        if total_staked > 0 {
+            let emitted = (self.reward_rate() as u128)
+                .saturating_mul(elapsed as u128)
+                .min(self.rewards_remaining() as u128);
+            let acc = emitted
+                .checked_mul(Self::ACC_PRECISION)
+                .map(|scaled| scaled / total_staked as u128)
+                .and_then(|delta| self.acc_reward_per_share().checked_add(delta))
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            self.acc_reward_per_share = acc.to_le_bytes();
+            self.rewards_remaining = (self.rewards_remaining() - emitted as u64).to_le_bytes();
+        }
+        self.set_last_update(now);
+        Ok(())
+    }
+
+    // 按当前累加器计算 amount 份 LP 对应的累计奖励 向下取整
+    #[inline(always)]
+    pub fn accrued(&self, amount: u64) -> Result<u128, ProgramError> {
+        (amount as u128)
+            .checked_mul(self.acc_reward_per_share())
+            .map(|scaled| scaled / Self::ACC_PRECISION)
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    // 农场 PDA 签名用的 seeds
+    #[inline(always)]
+    pub fn signer_seeds<'a>(config: &'a Address, bump: &'a [u8; 1]) -> [Seed<'a>; 3] {
+        [
+            Seed::from(b"farm"),
+            Seed::from(config.as_ref()),
+            Seed::from(bump),
+        ]
+    }
+}
+
+// 用户在农场中的质押记录 每个 (farm, owner) 对应一个 PDA
+// seeds = [b"stake", farm, owner]
+#[repr(C)]
+pub struct StakePosition {
+    farm: Address,
+    owner: Address,
+    // 质押的 LP 数量
+    amount: [u8; 8],
+    // 上一次结算时 amount 对应的累计奖励 用来计算新增的奖励
+    reward_debt: [u8; 16],
+    // 已经结算但还没有领取的奖励
+    pending: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl StakePosition {
+    pub const LEN: usize = size_of::<StakePosition>();
+
+    #[inline(always)]
+    pub fn load_mut(account: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account.try_borrow_mut()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        }))
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` 的长度必须至少为 `StakePosition::LEN`。
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        unsafe { &mut *(bytes.as_mut_ptr() as *mut StakePosition) }
+    }
+
+    #[inline(always)]
+    pub fn farm(&self) -> &Address {
+        &self.farm
+    }
+    #[inline(always)]
+    pub fn owner(&self) -> &Address {
+        &self.owner
+    }
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+    #[inline(always)]
+    pub fn reward_debt(&self) -> u128 {
+        u128::from_le_bytes(self.reward_debt)
+    }
+    #[inline(always)]
+    pub fn pending(&self) -> u64 {
+        u64::from_le_bytes(self.pending)
+    }
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, farm: Address, owner: Address, bump: [u8; 1]) {
+        self.farm = farm;
+        self.owner = owner;
+        self.bump = bump;
+    }
+
+    // 校验记录属于这个农场和这个用户
+    #[inline(always)]
+    pub fn check_owner(&self, farm: &AccountView, owner: &AccountView) -> Result<(), ProgramError> {
+        if self.farm.ne(farm.address()) || self.owner.ne(owner.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    // 按照农场当前的累加器结算奖励 之后调用 set_amount 更新质押数量
+    #[inline(always)]
+    pub fn settle(&mut self, farm: &Farm) -> Result<(), ProgramError> {
+        let accrued = farm.accrued(self.amount())?;
+        let earned = u64::try_from(accrued.saturating_sub(self.reward_debt()))
+            .map_err(|_| ProgramError::ArithmeticOverflow)?;
+        let pending = self
+            .pending()
+            .checked_add(earned)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.pending = pending.to_le_bytes();
+        self.reward_debt = accrued.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_amount(&mut self, farm: &Farm, amount: u64) -> Result<(), ProgramError> {
+        self.amount = amount.to_le_bytes();
+        self.reward_debt = farm.accrued(amount)?.to_le_bytes();
+        Ok(())
+    }
+
+    // 取出全部待领取的奖励
+    #[inline(always)]
+    pub fn take_pending(&mut self) -> u64 {
+        let pending = self.pending();
+        self.pending = 0u64.to_le_bytes();
+        pending
+    }
+}
+
+// 用户在池子中的 LP 仓位记录 每个 (config, owner) 对应一个 PDA
+// seeds = [b"position", config, owner]
+// 只记录带着这个账户调用的 Deposit / Withdraw LP 在钱包之间转移不会反映在这里
+#[repr(C)]
+pub struct LpPosition {
+    config: Address,
+    owner: Address,
+    // 累计存入和取回的 x y 数量
+    deposited_x: [u8; 8],
+    deposited_y: [u8; 8],
+    withdrawn_x: [u8; 8],
+    withdrawn_y: [u8; 8],
+    // 累计铸造和销毁的 LP 数量
+    lp_minted: [u8; 8],
+    lp_burned: [u8; 8],
+    // 还持有的 LP 对应的成本 按平均成本法在取回时按比例扣除
+    cost_x: [u8; 8],
+    cost_y: [u8; 8],
+    // 创建记录、最近一次存入、最近一次取回的 unix 时间戳
+    opened_at: [u8; 8],
+    last_deposit_at: [u8; 8],
+    last_withdraw_at: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl LpPosition {
+    pub const LEN: usize = size_of::<LpPosition>();
+
+    #[inline(always)]
+    pub fn load(account: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account.try_borrow()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account.try_borrow_mut()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        }))
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` 的长度必须至少为 `LpPosition::LEN`。
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        unsafe { &*(bytes.as_ptr() as *const LpPosition) }
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` 的长度必须至少为 `LpPosition::LEN`。
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        unsafe { &mut *(bytes.as_mut_ptr() as *mut LpPosition) }
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Address {
+        &self.config
+    }
+    #[inline(always)]
+    pub fn owner(&self) -> &Address {
+        &self.owner
+    }
+    #[inline(always)]
+    pub fn deposited(&self) -> (u64, u64) {
+        (
+            u64::from_le_bytes(self.deposited_x),
+            u64::from_le_bytes(self.deposited_y),
+        )
+    }
+    #[inline(always)]
+    pub fn withdrawn(&self) -> (u64, u64) {
+        (
+            u64::from_le_bytes(self.withdrawn_x),
+            u64::from_le_bytes(self.withdrawn_y),
+        )
+    }
+    #[inline(always)]
+    pub fn lp_minted(&self) -> u64 {
+        u64::from_le_bytes(self.lp_minted)
+    }
+    #[inline(always)]
+    pub fn lp_burned(&self) -> u64 {
+        u64::from_le_bytes(self.lp_burned)
+    }
+    #[inline(always)]
+    pub fn cost(&self) -> (u64, u64) {
+        (
+            u64::from_le_bytes(self.cost_x),
+            u64::from_le_bytes(self.cost_y),
+        )
+    }
+    #[inline(always)]
+    pub fn opened_at(&self) -> i64 {
+        i64::from_le_bytes(self.opened_at)
+    }
+    #[inline(always)]
+    pub fn last_deposit_at(&self) -> i64 {
+        i64::from_le_bytes(self.last_deposit_at)
+    }
+    #[inline(always)]
+    pub fn last_withdraw_at(&self) -> i64 {
+        i64::from_le_bytes(self.last_withdraw_at)
+    }
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    // 记录中还持有的 LP 从别处转入的 LP 不在记录中
+    #[inline(always)]
+    pub fn lp_held(&self) -> u64 {
+        self.lp_minted().saturating_sub(self.lp_burned())
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, config: Address, owner: Address, bump: [u8; 1], now: i64) {
+        self.config = config;
+        self.owner = owner;
+        self.bump = bump;
+        self.opened_at = now.to_le_bytes();
+    }
+
+    // 校验记录属于这个池子和这个用户
+    #[inline(always)]
+    pub fn check_owner(
+        &self,
+        config: &AccountView,
+        owner: &AccountView,
+    ) -> Result<(), ProgramError> {
+        if self.config.ne(config.address()) || self.owner.ne(owner.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    // 存入 x y 铸造 lp 个 LP 存入的代币全部计入成本
+    pub fn record_deposit(
+        &mut self,
+        x: u64,
+        y: u64,
+        lp: u64,
+        now: i64,
+    ) -> Result<(), ProgramError> {
+        self.deposited_x = checked_add_le(self.deposited_x, x)?;
+        self.deposited_y = checked_add_le(self.deposited_y, y)?;
+        self.cost_x = checked_add_le(self.cost_x, x)?;
+        self.cost_y = checked_add_le(self.cost_y, y)?;
+        self.lp_minted = checked_add_le(self.lp_minted, lp)?;
+        self.last_deposit_at = now.to_le_bytes();
+        Ok(())
+    }
+
+    // 销毁 lp 个 LP 取回 x y 按销毁数量占持有数量的比例扣除成本
+    // 超出记录持有量的部分是从别处转入的 LP 它们取回的代币不计入这个记录
+    pub fn record_withdraw(
+        &mut self,
+        x: u64,
+        y: u64,
+        lp: u64,
+        now: i64,
+    ) -> Result<(), ProgramError> {
+        let held = self.lp_held();
+        let covered = lp.min(held);
+        if covered > 0 {
+            let (cost_x, cost_y) = self.cost();
+            // 最后一次取完时成本正好清零 不会留下取整的尾数
+            let removed = |cost: u64| (cost as u128 * covered as u128 / held as u128) as u64;
+            self.cost_x = (cost_x - removed(cost_x)).to_le_bytes();
+            self.cost_y = (cost_y - removed(cost_y)).to_le_bytes();
+            // covered <= lp 结果不会超过 u64
+            let share = |amount: u64| (amount as u128 * covered as u128 / lp as u128) as u64;
+            self.withdrawn_x = checked_add_le(self.withdrawn_x, share(x))?;
+            self.withdrawn_y = checked_add_le(self.withdrawn_y, share(y))?;
+            self.lp_burned = checked_add_le(self.lp_burned, covered)?;
+        }
+        self.last_withdraw_at = now.to_le_bytes();
+        Ok(())
+    }
+
+    // 已实现盈亏 = 取回的数量 - 已经卖出的 LP 的成本 按 x y 分别计算 可能为负
+    #[inline(always)]
+    pub fn realized(&self) -> (i128, i128) {
+        let (deposited_x, deposited_y) = self.deposited();
+        let (withdrawn_x, withdrawn_y) = self.withdrawn();
+        let (cost_x, cost_y) = self.cost();
+        (
+            withdrawn_x as i128 - (deposited_x - cost_x) as i128,
+            withdrawn_y as i128 - (deposited_y - cost_y) as i128,
+        )
+    }
+}
+
+#[inline(always)]
+fn checked_add_le(value: [u8; 8], amount: u64) -> Result<[u8; 8], ProgramError> {
+    u64::from_le_bytes(value)
+        .checked_add(amount)
+        .map(u64::to_le_bytes)
+        .ok_or(ProgramError::ArithmeticOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_authority_treats_zero_as_none() {
+        let mut data = vec![0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+        assert_eq!(config.has_authority(), None);
+
+        let mut authority = [0u8; 32];
+        authority[31] = 1;
+        config.set_authority(Address::new_from_array(authority)).unwrap();
+        assert_eq!(
+            config.has_authority(),
+            Some(Address::new_from_array(authority))
+        );
+    }
+}
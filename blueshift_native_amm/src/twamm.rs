@@ -0,0 +1,890 @@
+use crate::math::mul_div_floor;
+use crate::{AmmState, Config, Swap};
+use core::slice::Iter;
+use pinocchio::account::{Ref, RefMut};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{AccountView, Address};
+
+// 时间加权的长期订单（TWAMM）
+// 每个订单按照固定的速度在一段时间内卖出 所有同方向订单的速度加在一起 由池子统一虚拟执行
+// 执行的时候先把两个方向的卖单按照当前价格互相撮合 剩下的部分再走 ConstantProduct
+// 订单的结束时间按 ORDER_INTERVAL 的倍数对齐 同一时间结束的订单共用一个到期记录
+// 到期记录在到期时立即释放 到期那一刻的累计收益记在快照里 订单按照快照领取
+
+// 最小的结束时间对齐间隔（秒）
+pub const ORDER_INTERVAL: i64 = 3600;
+// 离现在越远 结束时间的对齐间隔越大 第 k 级的间隔为 ORDER_INTERVAL << k
+// 每一级覆盖到 ALIGN_STEPS 个间隔为止 最长的订单大约 21 天
+pub const ALIGN_STEPS: i64 = 8;
+pub const ALIGN_LEVELS: u32 = 7;
+// 同时存在的不同结束时间的最大数量
+// 第 0 级最多 ALIGN_STEPS 个 之后每一级只剩下后半段 最多 ALIGN_STEPS / 2 个
+// 所以到期记录不会被占满 任何人都不能通过下很多小订单阻止别人下单
+pub const MAX_EXPIRIES: usize =
+    (ALIGN_STEPS + ALIGN_STEPS / 2 * (ALIGN_LEVELS as i64 - 1)) as usize;
+// 卖出速度的放大倍数 速度 = 数量 * RATE_PRECISION / 秒数
+pub const RATE_PRECISION: u128 = 1_000_000_000;
+// 每单位速度累计收益的放大倍数
+pub const EARNINGS_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+// 同一时间结束 还没有到期的订单汇总 到期之后这个位置立即可以复用
+#[repr(C)]
+pub struct TwammExpiry {
+    end_time: [u8; 8],
+    // 到期时需要从总速度中减去的部分
+    rate_x: [u8; 16],
+    rate_y: [u8; 16],
+    // 这些订单对应的快照位置
+    snapshot: [u8; 4],
+    active: u8,
+}
+
+impl TwammExpiry {
+    #[inline(always)]
+    pub fn end_time(&self) -> i64 {
+        i64::from_le_bytes(self.end_time)
+    }
+    #[inline(always)]
+    pub fn snapshot(&self) -> u32 {
+        u32::from_le_bytes(self.snapshot)
+    }
+    #[inline(always)]
+    pub fn is_active(&self) -> bool {
+        self.active != 0
+    }
+}
+
+// 到期那一刻的快照 接在 TWAMM 账户数据的末尾 数量随着订单增加
+// 到期的订单按照这里的值结算 所有订单都领取之后这个位置可以复用
+#[repr(C)]
+pub struct TwammSnapshot {
+    end_time: [u8; 8],
+    // 到期那一刻的累计收益
+    earnings_x: [u8; 16],
+    earnings_y: [u8; 16],
+    // 到期那一刻的累计执行时长 用来计算订单暂停期间没有卖出的部分
+    live_time: [u8; 8],
+    // 还没有领取完收益的订单数量 为 0 时这个位置可以复用
+    orders: [u8; 4],
+    executed: u8,
+}
+
+impl TwammSnapshot {
+    pub const LEN: usize = size_of::<TwammSnapshot>();
+
+    #[inline(always)]
+    pub fn end_time(&self) -> i64 {
+        i64::from_le_bytes(self.end_time)
+    }
+    #[inline(always)]
+    pub fn orders(&self) -> u32 {
+        u32::from_le_bytes(self.orders)
+    }
+    #[inline(always)]
+    pub fn is_executed(&self) -> bool {
+        self.executed != 0
+    }
+    #[inline(always)]
+    pub fn earnings(&self, is_x: bool) -> u128 {
+        match is_x {
+            true => u128::from_le_bytes(self.earnings_x),
+            false => u128::from_le_bytes(self.earnings_y),
+        }
+    }
+    #[inline(always)]
+    pub fn live_time(&self) -> i64 {
+        i64::from_le_bytes(self.live_time)
+    }
+}
+
+// 池子的 TWAMM 状态 每个池子对应一个 PDA
+// seeds = [b"twamm", config]
+#[repr(C)]
+pub struct Twamm {
+    config: Address,
+    // 虚拟订单已经执行到的 unix 时间戳
+    last_execution: [u8; 8],
+    // 所有卖 X / 卖 Y 的订单速度之和
+    sell_rate_x: [u8; 16],
+    sell_rate_y: [u8; 16],
+    // 卖 X 的订单每单位速度累计获得的 Y 卖 Y 的订单每单位速度累计获得的 X
+    earnings_x: [u8; 16],
+    earnings_y: [u8; 16],
+    // 每次执行卖出数量向下取整后剩下的零头 带到下一次执行 避免多次执行累积误差
+    sold_remainder_x: [u8; 16],
+    sold_remainder_y: [u8; 16],
+    // 订单实际在卖出的累计秒数 池子暂停或者没有流动性的时间不计入
+    live_time: [u8; 8],
+    expiries: [TwammExpiry; MAX_EXPIRIES],
+    // 账户末尾快照的数量
+    snapshots: [u8; 4],
+    bump: [u8; 1],
+}
+
+// 一次执行之后池子的储备和订单托管的代币数量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TwammBalances {
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub escrow_x: u64,
+    pub escrow_y: u64,
+}
+
+impl Twamm {
+    // 不含快照的长度 创建时没有快照
+    pub const LEN: usize = size_of::<Twamm>();
+
+    // 带有 snapshots 个快照时账户的长度
+    #[inline(always)]
+    pub fn space(snapshots: usize) -> usize {
+        Self::LEN + snapshots * TwammSnapshot::LEN
+    }
+
+    #[inline(always)]
+    pub fn load(account: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
+        if account.data_len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let twamm = Ref::map(account.try_borrow()?, |data| unsafe {
+            &*(data.as_ptr() as *const Twamm)
+        });
+        if account.data_len() != Self::space(twamm.snapshot_count()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(twamm)
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.data_len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let twamm = RefMut::map(account.try_borrow_mut()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        });
+        if account.data_len() != Self::space(twamm.snapshot_count()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(twamm)
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` 的长度必须至少为 `Twamm::space(snapshots)` 其中 snapshots 是数据中记录的快照数量。
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        unsafe { &mut *(bytes.as_mut_ptr() as *mut Twamm) }
+    }
+
+    // 账户末尾的快照 load 时已经校验过账户长度和快照数量一致
+    #[inline(always)]
+    pub fn snapshots(&self) -> &[TwammSnapshot] {
+        unsafe {
+            core::slice::from_raw_parts(
+                (self as *const Twamm).add(1) as *const TwammSnapshot,
+                self.snapshot_count(),
+            )
+        }
+    }
+
+    #[inline(always)]
+    fn snapshots_mut(&mut self) -> &mut [TwammSnapshot] {
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                (self as *mut Twamm).add(1) as *mut TwammSnapshot,
+                self.snapshot_count(),
+            )
+        }
+    }
+
+    #[inline(always)]
+    pub fn snapshot_count(&self) -> usize {
+        u32::from_le_bytes(self.snapshots) as usize
+    }
+
+    // 账户扩容一个快照的长度之后调用 新增的快照全部为 0 表示空闲
+    #[inline(always)]
+    pub fn add_snapshot(&mut self) -> Result<(), ProgramError> {
+        let snapshots = u32::from_le_bytes(self.snapshots)
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.snapshots = snapshots.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Address {
+        &self.config
+    }
+    #[inline(always)]
+    pub fn last_execution(&self) -> i64 {
+        i64::from_le_bytes(self.last_execution)
+    }
+    #[inline(always)]
+    pub fn sell_rate(&self, is_x: bool) -> u128 {
+        match is_x {
+            true => u128::from_le_bytes(self.sell_rate_x),
+            false => u128::from_le_bytes(self.sell_rate_y),
+        }
+    }
+    #[inline(always)]
+    pub fn earnings(&self, is_x: bool) -> u128 {
+        match is_x {
+            true => u128::from_le_bytes(self.earnings_x),
+            false => u128::from_le_bytes(self.earnings_y),
+        }
+    }
+    #[inline(always)]
+    pub fn live_time(&self) -> i64 {
+        i64::from_le_bytes(self.live_time)
+    }
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, config: Address, now: i64, bump: [u8; 1]) {
+        self.config = config;
+        self.last_execution = now.to_le_bytes();
+        self.bump = bump;
+    }
+
+    // 校验 TWAMM 账户属于这个池子
+    #[inline(always)]
+    pub fn check_config(&self, config: &AccountView) -> Result<(), ProgramError> {
+        if self.config.ne(config.address()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    // 订单的结束时间 向上对齐到能覆盖这个时长的最小一级间隔
+    // 在第 k 级对齐的结束时间离现在越来越近 但始终是第 k 级及以下间隔的倍数
+    // 所以离现在 (ALIGN_STEPS / 2 * 间隔, ALIGN_STEPS * 间隔] 的结束时间最多只有 ALIGN_STEPS / 2 个
+    pub fn end_time(now: i64, duration: u64) -> Result<i64, ProgramError> {
+        let end = i64::try_from(duration)
+            .ok()
+            .and_then(|duration| now.checked_add(duration))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        for level in 0..ALIGN_LEVELS {
+            let interval = ORDER_INTERVAL << level;
+            let aligned = end
+                .checked_add(interval - 1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            let aligned = aligned - aligned.rem_euclid(interval);
+            if aligned - now <= ALIGN_STEPS * interval {
+                return Ok(aligned);
+            }
+        }
+        Err(ProgramError::InvalidArgument)
+    }
+
+    // 把虚拟订单执行到 now 返回执行之后的储备和托管数量
+    // live 为 false 表示池子暂停 池子没有流动性的时候也无法定价
+    // 这两种情况下订单停止卖出 时间照常推进 恢复之后不会把暂停期间的数量集中在一次执行里卖出
+    // 暂停期间没有卖出的托管在订单结束之后退还 见 TwammOrder::refund
+    pub fn execute(
+        &mut self,
+        now: i64,
+        fee: u16,
+        live: bool,
+        mut balances: TwammBalances,
+    ) -> Result<TwammBalances, ProgramError> {
+        while self.last_execution() < now {
+            // 执行到下一个到期时间 到期的订单在那一刻之后不再卖出
+            let target = self
+                .expiries
+                .iter()
+                .filter(|expiry| expiry.is_active())
+                .map(|expiry| expiry.end_time())
+                .filter(|end_time| *end_time > self.last_execution())
+                .fold(now, i64::min);
+            let elapsed = target - self.last_execution();
+            if live && balances.reserve_x > 0 && balances.reserve_y > 0 {
+                balances = self.execute_window(elapsed, fee, balances)?;
+                let live_time = self
+                    .live_time()
+                    .checked_add(elapsed)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+                self.live_time = live_time.to_le_bytes();
+            }
+            self.last_execution = target.to_le_bytes();
+            self.expire(target)?;
+        }
+        Ok(balances)
+    }
+
+    // 执行一段时间内的虚拟订单
+    fn execute_window(
+        &mut self,
+        elapsed: i64,
+        fee: u16,
+        balances: TwammBalances,
+    ) -> Result<TwammBalances, ProgramError> {
+        let in_x = self.sold(true, elapsed)?;
+        let in_y = self.sold(false, elapsed)?;
+        if in_x == 0 && in_y == 0 {
+            return Ok(balances);
+        }
+        let (x, y) = (balances.reserve_x, balances.reserve_y);
+
+        // 先按照当前价格撮合两个方向的卖单 剩下的净卖单和池子成交
+        // proceeds_x: 卖 X 的订单得到的 Y  proceeds_y: 卖 Y 的订单得到的 X
+        let in_y_as_x = mul_div_floor(in_y, x, y)?;
+        let (reserve_x, reserve_y, proceeds_x, proceeds_y) = if in_x >= in_y_as_x {
+            let net = in_x - in_y_as_x;
+            let out_y = Self::swap_out(x, y, fee, true, net)?;
+            (
+                x.checked_add(net).ok_or(ProgramError::ArithmeticOverflow)?,
+                y - out_y,
+                in_y.checked_add(out_y)
+                    .ok_or(ProgramError::ArithmeticOverflow)?,
+                in_y_as_x,
+            )
+        } else {
+            let in_x_as_y = mul_div_floor(in_x, y, x)?;
+            let net = in_y - in_x_as_y;
+            let out_x = Self::swap_out(x, y, fee, false, net)?;
+            (
+                x - out_x,
+                y.checked_add(net).ok_or(ProgramError::ArithmeticOverflow)?,
+                in_x_as_y,
+                in_x.checked_add(out_x)
+                    .ok_or(ProgramError::ArithmeticOverflow)?,
+            )
+        };
+        self.accrue(true, proceeds_x)?;
+        self.accrue(false, proceeds_y)?;
+
+        // 卖出的部分离开托管 收益进入托管 等待订单领取
+        let escrow_x = balances
+            .escrow_x
+            .checked_sub(in_x)
+            .and_then(|escrow| escrow.checked_add(proceeds_y))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let escrow_y = balances
+            .escrow_y
+            .checked_sub(in_y)
+            .and_then(|escrow| escrow.checked_add(proceeds_x))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(TwammBalances {
+            reserve_x,
+            reserve_y,
+            escrow_x,
+            escrow_y,
+        })
+    }
+
+    // elapsed 秒内按照总速度卖出的数量 向下取整 零头留到下一次执行
+    #[inline(always)]
+    fn sold(&mut self, is_x: bool, elapsed: i64) -> Result<u64, ProgramError> {
+        let remainder = match is_x {
+            true => u128::from_le_bytes(self.sold_remainder_x),
+            false => u128::from_le_bytes(self.sold_remainder_y),
+        };
+        let total = self
+            .sell_rate(is_x)
+            .checked_mul(elapsed as u128)
+            .and_then(|sold| sold.checked_add(remainder))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let remainder = (total % RATE_PRECISION).to_le_bytes();
+        match is_x {
+            true => self.sold_remainder_x = remainder,
+            false => self.sold_remainder_y = remainder,
+        }
+        u64::try_from(total / RATE_PRECISION).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    // 按 rate 卖出 duration 秒需要托管的数量 向上取整
+    // 执行时卖出的总数不会超过 Σ rate * duration 向上取整之后托管一定足够 零头留在用户手里
+    #[inline(always)]
+    pub fn escrow_amount(rate: u128, duration: i64) -> Result<u64, ProgramError> {
+        let escrow = rate
+            .checked_mul(duration as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .div_ceil(RATE_PRECISION);
+        u64::try_from(escrow).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    // 净卖单按照 ConstantProduct 成交 收取和普通交换一样的手续费
+    #[inline(always)]
+    fn swap_out(x: u64, y: u64, fee: u16, is_x: bool, amount: u64) -> Result<u64, ProgramError> {
+        if amount == 0 {
+            return Ok(0);
+        }
+        let (_, _, out) = Swap::quote(x, y, fee, is_x, amount, 0)?;
+        Ok(out)
+    }
+
+    // 把收益按照速度分摊到累计收益上
+    fn accrue(&mut self, is_x: bool, proceeds: u64) -> Result<(), ProgramError> {
+        let rate = self.sell_rate(is_x);
+        if rate == 0 || proceeds == 0 {
+            return Ok(());
+        }
+        let earnings = (proceeds as u128)
+            .checked_mul(EARNINGS_PRECISION)
+            .map(|scaled| scaled / rate)
+            .and_then(|delta| self.earnings(is_x).checked_add(delta))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        match is_x {
+            true => self.earnings_x = earnings.to_le_bytes(),
+            false => self.earnings_y = earnings.to_le_bytes(),
+        }
+        Ok(())
+    }
+
+    // 在 end_time 到期的订单停止卖出 把这一刻的累计收益记到快照里 同时释放到期记录
+    fn expire(&mut self, end_time: i64) -> Result<(), ProgramError> {
+        let Some(index) = self
+            .expiries
+            .iter()
+            .position(|expiry| expiry.is_active() && expiry.end_time() == end_time)
+        else {
+            return Ok(());
+        };
+        let expiry = &self.expiries[index];
+        let rate_x = self
+            .sell_rate(true)
+            .checked_sub(u128::from_le_bytes(expiry.rate_x))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let rate_y = self
+            .sell_rate(false)
+            .checked_sub(u128::from_le_bytes(expiry.rate_y))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let snapshot = expiry.snapshot() as usize;
+        self.sell_rate_x = rate_x.to_le_bytes();
+        self.sell_rate_y = rate_y.to_le_bytes();
+        self.expiries[index].active = 0;
+
+        let (earnings_x, earnings_y, live_time) =
+            (self.earnings_x, self.earnings_y, self.live_time);
+        let snapshot = self
+            .snapshots_mut()
+            .get_mut(snapshot)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        snapshot.earnings_x = earnings_x;
+        snapshot.earnings_y = earnings_y;
+        snapshot.live_time = live_time;
+        snapshot.executed = 1;
+        Ok(())
+    }
+
+    // 新的结束时间没有空闲的快照可以用 需要先给账户扩容 见 PlaceOrder::process
+    pub fn needs_snapshot(&self, end_time: i64) -> bool {
+        !self
+            .expiries
+            .iter()
+            .any(|expiry| expiry.is_active() && expiry.end_time() == end_time)
+            && !self
+                .snapshots()
+                .iter()
+                .any(|snapshot| snapshot.orders() == 0)
+    }
+
+    // 新增一个订单 调用前需要先执行到当前时间 返回订单对应的快照位置
+    pub fn add_order(
+        &mut self,
+        is_x: bool,
+        rate: u128,
+        end_time: i64,
+    ) -> Result<u32, ProgramError> {
+        if end_time <= self.last_execution() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        // 优先复用同一个结束时间的记录 没有的话占用一个空位和一个空闲的快照
+        let index = match self
+            .expiries
+            .iter()
+            .position(|expiry| expiry.is_active() && expiry.end_time() == end_time)
+        {
+            Some(index) => index,
+            None => {
+                // 结束时间的对齐方式保证空位足够 见 Twamm::end_time
+                let index = self
+                    .expiries
+                    .iter()
+                    .position(|expiry| !expiry.is_active())
+                    .ok_or(ProgramError::AccountDataTooSmall)?;
+                let snapshot = self
+                    .snapshots()
+                    .iter()
+                    .position(|snapshot| snapshot.orders() == 0)
+                    .ok_or(ProgramError::AccountDataTooSmall)?;
+                let live_time = self.live_time;
+                let entry = &mut self.snapshots_mut()[snapshot];
+                entry.end_time = end_time.to_le_bytes();
+                entry.earnings_x = [0; 16];
+                entry.earnings_y = [0; 16];
+                entry.live_time = live_time;
+                entry.executed = 0;
+                let expiry = &mut self.expiries[index];
+                expiry.end_time = end_time.to_le_bytes();
+                expiry.rate_x = [0; 16];
+                expiry.rate_y = [0; 16];
+                expiry.snapshot = (snapshot as u32).to_le_bytes();
+                expiry.active = 1;
+                index
+            }
+        };
+        let total = self
+            .sell_rate(is_x)
+            .checked_add(rate)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let expiry = &mut self.expiries[index];
+        match is_x {
+            true => {
+                let ending = u128::from_le_bytes(expiry.rate_x) + rate;
+                expiry.rate_x = ending.to_le_bytes();
+                self.sell_rate_x = total.to_le_bytes();
+            }
+            false => {
+                let ending = u128::from_le_bytes(expiry.rate_y) + rate;
+                expiry.rate_y = ending.to_le_bytes();
+                self.sell_rate_y = total.to_le_bytes();
+            }
+        }
+        let snapshot = self.expiries[index].snapshot();
+        let entry = &mut self.snapshots_mut()[snapshot as usize];
+        let orders = entry
+            .orders()
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        entry.orders = orders.to_le_bytes();
+        Ok(snapshot)
+    }
+
+    // 已经到期的订单对应的快照
+    #[inline(always)]
+    fn expired(&self, end_time: i64, snapshot: u32) -> Result<&TwammSnapshot, ProgramError> {
+        self.snapshots()
+            .get(snapshot as usize)
+            .filter(|entry| {
+                entry.end_time() == end_time && entry.is_executed() && entry.orders() > 0
+            })
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+
+    // 订单当前可以结算到的累计收益 到期的订单使用到期那一刻的值
+    pub fn order_earnings(
+        &self,
+        is_x: bool,
+        end_time: i64,
+        snapshot: u32,
+    ) -> Result<u128, ProgramError> {
+        if end_time > self.last_execution() {
+            return Ok(self.earnings(is_x));
+        }
+        Ok(self.expired(end_time, snapshot)?.earnings(is_x))
+    }
+
+    // 订单到期时的累计执行时长 订单还没有到期时返回 None
+    pub fn order_live_time(
+        &self,
+        end_time: i64,
+        snapshot: u32,
+    ) -> Result<Option<i64>, ProgramError> {
+        if end_time > self.last_execution() {
+            return Ok(None);
+        }
+        Ok(Some(self.expired(end_time, snapshot)?.live_time()))
+    }
+
+    // 到期的订单领取完收益之后 快照中的订单数量减一 减到 0 时快照可以复用
+    pub fn close_order(&mut self, end_time: i64, snapshot: u32) -> Result<(), ProgramError> {
+        let orders = self.expired(end_time, snapshot)?.orders() - 1;
+        self.snapshots_mut()[snapshot as usize].orders = orders.to_le_bytes();
+        Ok(())
+    }
+}
+
+// 用户的长期订单 每个 (config, owner, order_id) 对应一个 PDA
+// seeds = [b"twamm_order", config, owner, order_id]
+#[repr(C)]
+pub struct TwammOrder {
+    config: Address,
+    owner: Address,
+    // 1 表示卖出 X 换 Y 0 表示卖出 Y 换 X
+    is_x: u8,
+    sell_rate: [u8; 16],
+    // 上一次领取收益时的累计收益
+    earnings_start: [u8; 16],
+    // 下单的时间和那一刻 TWAMM 的累计执行时长
+    start_time: [u8; 8],
+    live_start: [u8; 8],
+    end_time: [u8; 8],
+    // 到期之后用来结算的快照位置
+    snapshot: [u8; 4],
+    bump: [u8; 1],
+}
+
+impl TwammOrder {
+    pub const LEN: usize = size_of::<TwammOrder>();
+
+    #[inline(always)]
+    pub fn load(account: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account.try_borrow()?, |data| unsafe {
+            &*(data.as_ptr() as *const TwammOrder)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account.try_borrow_mut()?, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut TwammOrder)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Address {
+        &self.config
+    }
+    #[inline(always)]
+    pub fn owner(&self) -> &Address {
+        &self.owner
+    }
+    #[inline(always)]
+    pub fn is_x(&self) -> bool {
+        self.is_x != 0
+    }
+    #[inline(always)]
+    pub fn sell_rate(&self) -> u128 {
+        u128::from_le_bytes(self.sell_rate)
+    }
+    #[inline(always)]
+    pub fn earnings_start(&self) -> u128 {
+        u128::from_le_bytes(self.earnings_start)
+    }
+    #[inline(always)]
+    pub fn start_time(&self) -> i64 {
+        i64::from_le_bytes(self.start_time)
+    }
+    #[inline(always)]
+    pub fn live_start(&self) -> i64 {
+        i64::from_le_bytes(self.live_start)
+    }
+    #[inline(always)]
+    pub fn end_time(&self) -> i64 {
+        i64::from_le_bytes(self.end_time)
+    }
+    #[inline(always)]
+    pub fn snapshot(&self) -> u32 {
+        u32::from_le_bytes(self.snapshot)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        config: Address,
+        owner: Address,
+        is_x: bool,
+        sell_rate: u128,
+        earnings_start: u128,
+        start_time: i64,
+        live_start: i64,
+        end_time: i64,
+        snapshot: u32,
+        bump: [u8; 1],
+    ) {
+        self.config = config;
+        self.owner = owner;
+        self.is_x = is_x as u8;
+        self.sell_rate = sell_rate.to_le_bytes();
+        self.earnings_start = earnings_start.to_le_bytes();
+        self.start_time = start_time.to_le_bytes();
+        self.live_start = live_start.to_le_bytes();
+        self.end_time = end_time.to_le_bytes();
+        self.snapshot = snapshot.to_le_bytes();
+        self.bump = bump;
+    }
+
+    // 按照当前的累计收益结算 返回可以领取的数量
+    #[inline(always)]
+    pub fn settle(&mut self, earnings: u128) -> Result<u64, ProgramError> {
+        let proceeds = earnings
+            .saturating_sub(self.earnings_start())
+            .checked_mul(self.sell_rate())
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / EARNINGS_PRECISION;
+        self.earnings_start = earnings.to_le_bytes();
+        u64::try_from(proceeds).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    // 订单结束之后退还暂停期间没有卖出的托管 向下取整
+    // live_end 是订单到期那一刻 TWAMM 的累计执行时长
+    #[inline(always)]
+    pub fn refund(&self, live_end: i64) -> Result<u64, ProgramError> {
+        let paused = (self.end_time() - self.start_time())
+            .checked_sub(live_end - self.live_start())
+            .filter(|paused| *paused >= 0)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let refund = self
+            .sell_rate()
+            .checked_mul(paused as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / RATE_PRECISION;
+        u64::try_from(refund).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+}
+
+// 只读的报价指令用来模拟结算 在 TWAMM 账户数据的副本上执行 不修改任何账户
+// 返回结算之后的储备 和 Swap 先调用 settle 再计算的结果一致
+pub fn preview(
+    config: &AccountView,
+    remaining: &mut Iter<'_, AccountView>,
+) -> Result<(u64, u64), ProgramError> {
+    let pool = Config::load(config)?;
+    if !pool.has_twamm() {
+        return Ok((pool.reserve_x(), pool.reserve_y()));
+    }
+    let twamm = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    Twamm::load(twamm)?.check_config(config)?;
+    let mut data = twamm.try_borrow()?.to_vec();
+    let balances = unsafe { Twamm::from_bytes_unchecked_mut(&mut data) }.execute(
+        Clock::get()?.unix_timestamp,
+        pool.fee(),
+        pool.state() == AmmState::Initialized as u8,
+        TwammBalances {
+            reserve_x: pool.reserve_x(),
+            reserve_y: pool.reserve_y(),
+            escrow_x: pool.twamm_escrow_x(),
+            escrow_y: pool.twamm_escrow_y(),
+        },
+    )?;
+    Ok((balances.reserve_x, balances.reserve_y))
+}
+
+// 池子被调用时先结算 TWAMM 订单 启用了 TWAMM 的池子需要在剩余账户中最先传入 TWAMM 账户
+// 池子不在正常交易状态时订单暂停执行 修改池子状态之前也需要先结算 让暂停从那一刻开始计算
+pub fn settle(
+    config: &AccountView,
+    remaining: &mut Iter<'_, AccountView>,
+) -> Result<(), ProgramError> {
+    let mut pool = Config::load_mut(config)?;
+    if !pool.has_twamm() {
+        return Ok(());
+    }
+    let twamm = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let mut twamm = Twamm::load_mut(twamm)?;
+    twamm.check_config(config)?;
+    let balances = twamm.execute(
+        Clock::get()?.unix_timestamp,
+        pool.fee(),
+        pool.state() == AmmState::Initialized as u8,
+        TwammBalances {
+            reserve_x: pool.reserve_x(),
+            reserve_y: pool.reserve_y(),
+            escrow_x: pool.twamm_escrow_x(),
+            escrow_y: pool.twamm_escrow_y(),
+        },
+    )?;
+    pool.set_reserves(balances.reserve_x, balances.reserve_y)?;
+    pool.set_twamm_escrow(balances.escrow_x, balances.escrow_y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BALANCES: TwammBalances = TwammBalances {
+        reserve_x: 1_000_000_000,
+        reserve_y: 1_000_000_000,
+        escrow_x: 3_600,
+        escrow_y: 0,
+    };
+
+    fn twamm(data: &mut [u8]) -> &mut Twamm {
+        unsafe { Twamm::from_bytes_unchecked_mut(data) }
+    }
+
+    // 和 PlaceOrder 一样 没有空闲的快照时先扩容
+    fn add_order(data: &mut Vec<u8>, rate: u128, end_time: i64) -> u32 {
+        if twamm(data).needs_snapshot(end_time) {
+            data.resize(data.len() + TwammSnapshot::LEN, 0);
+            twamm(data).add_snapshot().unwrap();
+        }
+        twamm(data).add_order(true, rate, end_time).unwrap()
+    }
+
+    #[test]
+    fn paused_time_is_not_sold_later() {
+        let mut data = vec![0u8; Twamm::LEN];
+        twamm(&mut data).set_inner(Address::default(), 0, [0]);
+        // 一小时卖出 3600 个 X
+        let snapshot = add_order(&mut data, RATE_PRECISION, ORDER_INTERVAL);
+        let twamm = twamm(&mut data);
+
+        // 前半个小时池子暂停 不卖出
+        let paused = twamm.execute(1_800, 30, false, BALANCES).unwrap();
+        assert_eq!(paused, BALANCES);
+        assert_eq!(twamm.last_execution(), 1_800);
+        assert_eq!(twamm.live_time(), 0);
+
+        // 恢复之后只卖出后半个小时的部分
+        let live = twamm.execute(3_600, 30, true, paused).unwrap();
+        assert_eq!(live.escrow_x, 1_800);
+        assert_eq!(live.reserve_x, BALANCES.reserve_x + 1_800);
+        assert_eq!(twamm.order_live_time(3_600, snapshot).unwrap(), Some(1_800));
+    }
+
+    #[test]
+    fn end_times_never_exceed_expiry_slots() {
+        let mut data = vec![0u8; Twamm::LEN];
+        twamm(&mut data).set_inner(Address::default(), 0, [0]);
+
+        // 每个整点结束一个订单 一直到最长的时长 对齐之后正好占满所有到期记录
+        let mut end_times = Vec::new();
+        for hours in 1.. {
+            let Ok(end_time) = Twamm::end_time(0, hours * ORDER_INTERVAL as u64) else {
+                break;
+            };
+            add_order(&mut data, 1, end_time);
+            end_times.push(end_time);
+        }
+        end_times.dedup();
+        assert_eq!(end_times.len(), MAX_EXPIRIES);
+        assert!(twamm(&mut data)
+            .expiries
+            .iter()
+            .all(|expiry| expiry.is_active()));
+        // 到期记录占满之后 任意时长的新订单都会落在已有的结束时间上
+        let end_time = Twamm::end_time(0, 100 * ORDER_INTERVAL as u64 + 1).unwrap();
+        assert!(end_times.contains(&end_time));
+        add_order(&mut data, 1, end_time);
+
+        // 第一个结束时间到期之后 即使订单没有领取 到期记录也会释放
+        let first = end_times[0];
+        let snapshot = twamm(&mut data).expiries[0].snapshot();
+        twamm(&mut data).execute(first, 30, true, BALANCES).unwrap();
+        let end_time = Twamm::end_time(first, ALIGN_STEPS as u64 * ORDER_INTERVAL as u64).unwrap();
+        assert!(!end_times.contains(&end_time));
+        add_order(&mut data, 1, end_time);
+
+        // 没有领取的订单仍然可以按照快照结算
+        let twamm = twamm(&mut data);
+        assert!(twamm.order_earnings(true, first, snapshot).is_ok());
+        twamm.close_order(first, snapshot).unwrap();
+        assert!(!twamm.needs_snapshot(end_time + ORDER_INTERVAL));
+    }
+}
@@ -0,0 +1,76 @@
+use pinocchio::cpi::{invoke_signed, Signer};
+use pinocchio::instruction::{InstructionAccount, InstructionView};
+use pinocchio::{AccountView, ProgramResult};
+
+// 其他程序通过 CPI 调用金库的辅助结构 启用 cpi feature 之后可用
+// 用法和 pinocchio-token 一样 填好账户和参数之后调用 invoke / invoke_signed
+
+// 把 lamports 存入 owner 的金库 金库必须是空的
+// 账户：
+//   0. `[WRITE, SIGNER]` 金库的所有者
+//   1. `[WRITE]` 金库 PDA seeds = [b"vault", owner]
+//   2. `[]` 系统程序
+pub struct Deposit<'a> {
+    pub owner: &'a AccountView,
+    pub vault: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub amount: u64,
+}
+
+impl Deposit<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let mut data = [0u8; 1 + 8];
+        data[0] = 0;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        invoke_vault(self.owner, self.vault, self.system_program, &data, signers)
+    }
+}
+
+// 取出金库中的全部 lamports
+// 账户：
+//   0. `[WRITE, SIGNER]` 金库的所有者
+//   1. `[WRITE]` 金库 PDA
+//   2. `[]` 系统程序
+pub struct Withdraw<'a> {
+    pub owner: &'a AccountView,
+    pub vault: &'a AccountView,
+    pub system_program: &'a AccountView,
+}
+
+impl Withdraw<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        invoke_vault(self.owner, self.vault, self.system_program, &[1], signers)
+    }
+}
+
+// 两个指令的账户完全一样
+#[inline(always)]
+fn invoke_vault(
+    owner: &AccountView,
+    vault: &AccountView,
+    system_program: &AccountView,
+    data: &[u8],
+    signers: &[Signer],
+) -> ProgramResult {
+    let instruction_accounts = [
+        InstructionAccount::writable_signer(owner.address()),
+        InstructionAccount::writable(vault.address()),
+        InstructionAccount::readonly(system_program.address()),
+    ];
+    let instruction = InstructionView {
+        program_id: &crate::ID,
+        accounts: &instruction_accounts,
+        data,
+    };
+    invoke_signed(&instruction, &[owner, vault, system_program], signers)
+}
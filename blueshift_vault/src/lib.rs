@@ -1,16 +1,21 @@
-use pinocchio::{entrypoint, error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
-entrypoint!(process_instruction);
+// 其他程序依赖这个 crate 时启用 no-entrypoint 避免入口符号冲突
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
 
 pub mod instructions;
 pub use instructions::*;
+
+#[cfg(feature = "cpi")]
+pub mod cpi;
 use solana_address::declare_id;
 
 // 22222222222222222222222222222222222222222222
 
 declare_id!("22222222222222222222222222222222222222222222");
 
-fn process_instruction(
+pub fn process_instruction(
     _program_id: &Address,
     accounts: &[AccountView],
     instruction_data: &[u8],